@@ -0,0 +1,421 @@
+use crate::food::FoodKind;
+use crate::grid::CellGrid;
+use crate::{Cell, Direction, DirectionName, Position, Universe, UniverseTopology};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const DIRECTIONS: [DirectionName; 4] = [
+    DirectionName::Up,
+    DirectionName::Down,
+    DirectionName::Left,
+    DirectionName::Right,
+];
+
+// Decides where a snake turns next; asked once per tick before it moves.
+pub trait Controller {
+    fn next_direction(&mut self, view: &BoardView) -> DirectionName;
+}
+
+// What a controlled snake can see: the board plus its own body and heading.
+pub struct BoardView<'a> {
+    width: u32,
+    height: u32,
+    topology: UniverseTopology,
+    cells: &'a CellGrid,
+    body: &'a VecDeque<Position>,
+    direction: Direction,
+    food: Vec<(u32, u32)>,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn topology(&self) -> UniverseTopology {
+        self.topology
+    }
+
+    pub fn cell(&self, x: u32, y: u32) -> Cell {
+        self.cells.get((y * self.width + x) as usize)
+    }
+
+    pub fn head(&self) -> (u32, u32) {
+        let head = self.body.front().unwrap();
+        (head.x, head.y)
+    }
+
+    pub fn heading(&self) -> DirectionName {
+        self.direction.name()
+    }
+
+    pub fn length(&self) -> usize {
+        self.body.len()
+    }
+
+    // Food worth heading for; poison is left out.
+    pub fn food(&self) -> &[(u32, u32)] {
+        &self.food
+    }
+
+    // Controllers steer around portals rather than through them, and around hazards.
+    pub fn is_blocked(&self, x: u32, y: u32) -> bool {
+        matches!(
+            self.cell(x, y),
+            Cell::SnakeHead | Cell::SnakeBody | Cell::Wall | Cell::Portal | Cell::Hazard
+        )
+    }
+
+    // The neighbouring cell in `direction`, or None past an edge that doesn't wrap.
+    pub fn step(&self, (x, y): (u32, u32), direction: DirectionName) -> Option<(u32, u32)> {
+        let d = Direction::from_name(direction);
+        let nx = x as i32 + d.vx;
+        let ny = y as i32 + d.vy;
+
+        if (!self.topology.wraps_x() && (nx < 0 || nx >= self.width as i32))
+            || (!self.topology.wraps_y() && (ny < 0 || ny >= self.height as i32))
+        {
+            None
+        } else {
+            Some((nx.rem_euclid(self.width as i32) as u32, ny.rem_euclid(self.height as i32) as u32))
+        }
+    }
+
+    pub fn safe_moves(&self) -> Vec<DirectionName> {
+        DIRECTIONS
+            .iter()
+            .copied()
+            .filter(|&direction| !Direction::from_name(direction).is_opposite(&self.direction))
+            .filter(|&direction| {
+                self.step(self.head(), direction)
+                    .is_some_and(|(x, y)| !self.is_blocked(x, y))
+            })
+            .collect()
+    }
+
+    pub fn distance(&self, (ax, ay): (u32, u32), (bx, by): (u32, u32)) -> u32 {
+        let mut dx = ax.abs_diff(bx);
+        let mut dy = ay.abs_diff(by);
+        if self.topology.wraps_x() {
+            dx = dx.min(self.width - dx);
+        }
+        if self.topology.wraps_y() {
+            dy = dy.min(self.height - dy);
+        }
+        dx + dy
+    }
+
+    // Open cells reachable from `from`, counting `from` itself.
+    pub fn reachable(&self, from: (u32, u32)) -> usize {
+        let mut seen = vec![false; self.cells.len()];
+        let mut stack = vec![from];
+        seen[self.index(from)] = true;
+        let mut count = 0;
+
+        while let Some(cell) = stack.pop() {
+            count += 1;
+            for &direction in &DIRECTIONS {
+                if let Some(next) = self.step(cell, direction) {
+                    let idx = self.index(next);
+                    if !seen[idx] && !self.is_blocked(next.0, next.1) {
+                        seen[idx] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    fn index(&self, (x, y): (u32, u32)) -> usize {
+        (y * self.width + x) as usize
+    }
+
+    // The safe move that leaves the most room, or straight on if nothing is safe.
+    fn roomiest_move(&self) -> DirectionName {
+        self.safe_moves()
+            .into_iter()
+            .max_by_key(|&direction| self.step(self.head(), direction).map_or(0, |cell| self.reachable(cell)))
+            .unwrap_or_else(|| self.heading())
+    }
+}
+
+// === Strategies ===
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AiStrategy {
+    Greedy,
+    AStar,
+    Hamiltonian,
+}
+
+impl AiStrategy {
+    pub fn controller(self) -> Box<dyn Controller> {
+        match self {
+            AiStrategy::Greedy => Box::new(Greedy),
+            AiStrategy::AStar => Box::new(AStar),
+            AiStrategy::Hamiltonian => Box::new(Hamiltonian::default()),
+        }
+    }
+}
+
+// Takes whichever safe move gets closest to the nearest food.
+pub struct Greedy;
+
+impl Controller for Greedy {
+    fn next_direction(&mut self, view: &BoardView) -> DirectionName {
+        let closeness = |direction: DirectionName| {
+            let cell = view.step(view.head(), direction).unwrap();
+            view.food().iter().map(|&food| view.distance(cell, food)).min().unwrap_or(0)
+        };
+
+        view.safe_moves()
+            .into_iter()
+            .min_by_key(|&direction| closeness(direction))
+            .unwrap_or_else(|| view.heading())
+    }
+}
+
+// Shortest path to the nearest food around walls and bodies. Without a path
+// it stalls for time by moving towards the most open space.
+pub struct AStar;
+
+impl Controller for AStar {
+    fn next_direction(&mut self, view: &BoardView) -> DirectionName {
+        let targets = view.food();
+        if targets.is_empty() {
+            return view.roomiest_move();
+        }
+
+        shortest_path(view, view.head(), targets, Some(view.direction))
+            .and_then(|path| path.first().copied())
+            .unwrap_or_else(|| view.roomiest_move())
+    }
+}
+
+// A* over open cells; the start cell itself may be occupied (it is usually
+// a head). With a `heading`, the first step may not reverse it.
+fn shortest_path(
+    view: &BoardView,
+    start: (u32, u32),
+    targets: &[(u32, u32)],
+    heading: Option<Direction>,
+) -> Option<Vec<DirectionName>> {
+    let heuristic = |cell: (u32, u32)| targets.iter().map(|&target| view.distance(cell, target)).min().unwrap_or(0);
+    let len = (view.width * view.height) as usize;
+    let mut cost = vec![u32::MAX; len];
+    let mut came_from: Vec<Option<((u32, u32), DirectionName)>> = vec![None; len];
+    let mut open = BinaryHeap::new();
+
+    cost[view.index(start)] = 0;
+    open.push(Reverse((heuristic(start), 0, start)));
+
+    while let Some(Reverse((_, g, cell))) = open.pop() {
+        if targets.contains(&cell) {
+            let mut path = Vec::new();
+            let mut at = cell;
+            while let Some((previous, direction)) = came_from[view.index(at)] {
+                path.push(direction);
+                at = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if g > cost[view.index(cell)] {
+            continue;
+        }
+
+        for &direction in &DIRECTIONS {
+            let next = match view.step(cell, direction) {
+                Some(next) if !view.is_blocked(next.0, next.1) => next,
+                _ => continue,
+            };
+            let reverses = heading.is_some_and(|heading| Direction::from_name(direction).is_opposite(&heading));
+            if cell == start && reverses {
+                continue;
+            }
+
+            let idx = view.index(next);
+            if g + 1 < cost[idx] {
+                cost[idx] = g + 1;
+                came_from[idx] = Some((cell, direction));
+                open.push(Reverse((g + 1 + heuristic(next), g + 1, next)));
+            }
+        }
+    }
+    None
+}
+
+// Directions (as `DirectionName` values) leading from one cell to another
+// around walls and snakes, or undefined when the target can't be reached.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn find_path(universe: &Universe, from_x: u32, from_y: u32, to_x: u32, to_y: u32) -> Option<Vec<u32>> {
+    let view = universe.board_view(0)?;
+    let inside = |x: u32, y: u32| x < view.width && y < view.height;
+    if !inside(from_x, from_y) || !inside(to_x, to_y) {
+        return None;
+    }
+
+    let path = shortest_path(&view, (from_x, from_y), &[(to_x, to_y)], None)?;
+    Some(path.into_iter().map(|direction| direction as u32).collect())
+}
+
+// Follows a cycle through every cell so the snake can never trap itself.
+// Boards with an odd number of cells on both sides have no such cycle, and
+// a blocked cycle cell (walls, other snakes) falls back to `AStar`.
+#[derive(Default)]
+pub struct Hamiltonian {
+    size: (u32, u32),
+    next: Vec<(u32, u32)>,
+}
+
+impl Hamiltonian {
+    fn build(&mut self, width: u32, height: u32) {
+        self.size = (width, height);
+        self.next.clear();
+
+        let cycle = if height.is_multiple_of(2) {
+            cycle_for_even_rows(width, height)
+        } else if width.is_multiple_of(2) {
+            let transposed = cycle_for_even_rows(height, width);
+            transposed.into_iter().map(|(x, y)| (y, x)).collect()
+        } else {
+            return;
+        };
+
+        self.next = vec![(0, 0); (width * height) as usize];
+        for (i, &(x, y)) in cycle.iter().enumerate() {
+            self.next[(y * width + x) as usize] = cycle[(i + 1) % cycle.len()];
+        }
+    }
+}
+
+// Along the top row, zigzag back through columns 1.. and up column 0.
+fn cycle_for_even_rows(width: u32, height: u32) -> Vec<(u32, u32)> {
+    if width < 2 {
+        return Vec::new();
+    }
+
+    let mut cycle: Vec<(u32, u32)> = (0..width).map(|x| (x, 0)).collect();
+    for y in 1..height {
+        if y % 2 == 1 {
+            cycle.extend((1..width).rev().map(|x| (x, y)));
+        } else {
+            cycle.extend((1..width).map(|x| (x, y)));
+        }
+    }
+    cycle.extend((1..height).rev().map(|y| (0, y)));
+    cycle
+}
+
+impl Controller for Hamiltonian {
+    fn next_direction(&mut self, view: &BoardView) -> DirectionName {
+        if self.size != (view.width, view.height) {
+            self.build(view.width, view.height);
+        }
+
+        let head = view.head();
+        if let Some(&next) = self.next.get(view.index(head)) {
+            let along = view
+                .safe_moves()
+                .into_iter()
+                .find(|&direction| view.step(head, direction) == Some(next));
+            if let Some(direction) = along {
+                return direction;
+            }
+        }
+
+        AStar.next_direction(view)
+    }
+}
+
+// === Universe integration ===
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn attach_ai(&mut self, player_id: u32, strategy: AiStrategy) -> bool {
+        self.attach_controller(player_id, strategy.controller())
+    }
+
+    pub fn detach_ai(&mut self, player_id: u32) {
+        if let Some(player) = self.players.get_mut(player_id as usize) {
+            player.controller = None;
+        }
+    }
+
+    // Hands the player snake to the A* controller, e.g. for a self-playing
+    // title screen. Turning it off gives control straight back.
+    pub fn set_autopilot(&mut self, enabled: bool) {
+        if enabled {
+            self.attach_ai(0, AiStrategy::AStar);
+        } else {
+            self.detach_ai(0);
+        }
+    }
+
+    pub fn autopilot(&self) -> bool {
+        self.has_ai(0)
+    }
+
+    pub fn has_ai(&self, player_id: u32) -> bool {
+        self.players
+            .get(player_id as usize)
+            .is_some_and(|player| player.controller.is_some())
+    }
+}
+
+impl Universe {
+    pub fn attach_controller(&mut self, player_id: u32, controller: Box<dyn Controller>) -> bool {
+        match self.players.get_mut(player_id as usize) {
+            Some(player) => {
+                player.controller = Some(controller);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn board_view(&self, player_id: u32) -> Option<BoardView<'_>> {
+        let player = self.players.get(player_id as usize)?;
+        let food = self
+            .foods
+            .iter()
+            .filter(|food| food.kind != FoodKind::Poison)
+            .map(|food| (food.position.x, food.position.y))
+            .collect();
+
+        Some(BoardView {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+            cells: &self.cells,
+            body: &player.snake.body,
+            direction: player.snake.direction,
+            food,
+        })
+    }
+
+    // Controllers are asked before anyone moves, so they all see the same
+    // board. A controlled snake ignores any turns queued by hand.
+    pub(crate) fn steer_controllers(&mut self) {
+        for id in 0..self.players.len() {
+            if !self.players[id].alive {
+                continue;
+            }
+            let mut controller = match self.players[id].controller.take() {
+                Some(controller) => controller,
+                None => continue,
+            };
+
+            let direction = controller.next_direction(&self.board_view(id as u32).unwrap());
+            self.players[id].snake.pending.clear();
+            self.players[id].snake.set_direction_name(direction);
+            self.players[id].controller = Some(controller);
+        }
+    }
+}
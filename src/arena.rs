@@ -0,0 +1,164 @@
+use crate::hazards::Hazard;
+use crate::sounds::{Sound, SHRINK_WARNING_TICKS};
+use crate::{Cell, GameEvent, GameOverReason, Position, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// The arena stops shrinking once it is this many cells across.
+pub const MIN_ARENA: u32 = 3;
+
+// Battle royale: every `interval` ticks the outermost open ring of cells
+// turns into wall. Snakes caught in the ring die; portals and hazards in it
+// are put away and come back, with the open arena, on `reset`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Shrink {
+    pub(crate) interval: u32,
+    pub(crate) countdown: u32,
+    pub(crate) rings: u32,
+    pub(crate) closed: Vec<u32>,
+    pub(crate) portals: Vec<(u32, u32)>,
+    pub(crate) hazards: Vec<Hazard>,
+}
+
+impl Shrink {
+    pub(crate) fn new(interval: u32) -> Shrink {
+        Shrink {
+            interval,
+            countdown: interval,
+            ..Shrink::default()
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Closes a ring every `interval` ticks, counting from now; 0 stops the
+    // shrinking. Rings already closed stay until `reset`.
+    pub fn set_shrinking_arena(&mut self, interval: u32) {
+        self.shrink.interval = interval;
+        self.shrink.countdown = interval;
+    }
+
+    pub fn shrink_interval(&self) -> u32 {
+        self.shrink.interval
+    }
+
+    pub fn arena_rings(&self) -> u32 {
+        self.shrink.rings
+    }
+
+    // For a HUD countdown; None when the arena won't shrink any further.
+    pub fn ticks_until_shrink(&self) -> Option<u32> {
+        if self.shrink.interval > 0 && self.can_shrink(self.shrink.rings) {
+            Some(self.shrink.countdown)
+        } else {
+            None
+        }
+    }
+
+    pub fn seconds_until_shrink(&self) -> Option<f64> {
+        self.ticks_until_shrink().map(|ticks| ticks as f64 / self.ticks_per_second())
+    }
+}
+
+impl Universe {
+    // Counts down and closes the next ring when it's due. Returns the snakes
+    // caught in it, already killed.
+    pub(crate) fn shrink_arena(&mut self) -> Vec<(usize, GameOverReason)> {
+        let shrink = &mut self.shrink;
+        if shrink.interval == 0 {
+            return Vec::new();
+        }
+        if shrink.countdown > 1 {
+            shrink.countdown -= 1;
+            let countdown = shrink.countdown;
+            if countdown <= SHRINK_WARNING_TICKS && self.can_shrink(self.shrink.rings) {
+                self.cue(Sound::Countdown, 0, 1.0, countdown);
+            }
+            return Vec::new();
+        }
+        shrink.countdown = shrink.interval;
+        let ring = shrink.rings;
+        if !self.can_shrink(ring) {
+            return Vec::new();
+        }
+
+        let cells: Vec<usize> = (0..self.cells.len()).filter(|&idx| self.ring_of(idx) == ring).collect();
+        let caught: Vec<_> = (0..self.players.len())
+            .filter(|&id| self.players[id].alive)
+            .filter(|&id| {
+                let body = &self.players[id].snake.body;
+                body.iter().any(|p| self.ring_of(self.get_index(p.y, p.x)) == ring)
+            })
+            .map(|id| (id, GameOverReason::Crushed))
+            .collect();
+        self.kill(&caught);
+
+        let (portals, kept) = self
+            .portals
+            .iter()
+            .partition(|&&(a, b)| self.ring_of(a as usize) == ring || self.ring_of(b as usize) == ring);
+        self.portals = kept;
+        let (hazards, kept) = std::mem::take(&mut self.hazards)
+            .into_iter()
+            .partition(|hazard| self.ring_at(&hazard.position) == ring);
+        self.hazards = kept;
+        for &(a, b) in &portals {
+            self.set_cell(a as usize, self.background(a as usize));
+            self.set_cell(b as usize, self.background(b as usize));
+        }
+        for hazard in &hazards {
+            let idx = self.get_index(hazard.position.y, hazard.position.x);
+            self.set_cell(idx, self.background(idx));
+        }
+
+        let mut closed = Vec::new();
+        for idx in cells {
+            if self.walls.contains(idx) || self.occupied.contains(idx) {
+                continue;
+            }
+            self.walls.insert(idx);
+            self.set_cell(idx, Cell::Wall);
+            self.cover_food(idx);
+            closed.push(idx as u32);
+        }
+
+        self.shrink.rings += 1;
+        self.shrink.closed.extend(closed);
+        self.shrink.portals.extend(portals);
+        self.shrink.hazards.extend(hazards);
+        self.emit(GameEvent::ArenaShrunk { rings: ring + 1 });
+        caught
+    }
+
+    // Takes the closed rings down again and brings back what they held.
+    // Only the bookkeeping: `reset` repaints the board afterwards.
+    pub(crate) fn reopen_arena(&mut self) {
+        let interval = self.shrink.interval;
+        let shrink = std::mem::replace(&mut self.shrink, Shrink::new(interval));
+        for idx in shrink.closed {
+            self.walls.remove(idx as usize);
+        }
+        self.portals.extend(shrink.portals);
+        self.hazards.extend(shrink.hazards);
+    }
+
+    fn can_shrink(&self, rings: u32) -> bool {
+        let inner = |size: u32| size.saturating_sub(2 * (rings + 1));
+        inner(self.width) >= MIN_ARENA && inner(self.height) >= MIN_ARENA
+    }
+
+    // How many rings in from the edge of the board `idx` lies.
+    fn ring_of(&self, idx: usize) -> u32 {
+        let x = idx as u32 % self.width;
+        let y = idx as u32 / self.width;
+        self.ring_at(&Position { x, y })
+    }
+
+    fn ring_at(&self, p: &Position) -> u32 {
+        p.x.min(p.y).min(self.width - 1 - p.x).min(self.height - 1 - p.y)
+    }
+}
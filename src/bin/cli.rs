@@ -0,0 +1,95 @@
+//! Plays the game in a terminal: arrows, WASD or hjkl to steer, p or space
+//! to pause, r to restart and q or Esc to quit.
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::style::Print;
+use crossterm::terminal::{self, ClearType, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute, queue};
+use rust_snake_wasm::{GameState, Snake, Universe};
+use std::io::{self, Write};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const WIDTH: u32 = 32;
+const HEIGHT: u32 = 20;
+
+fn main() -> io::Result<()> {
+    let seed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    let mut universe = Universe::new_with_seed(Snake::new(), 60.0, WIDTH, HEIGHT, seed)
+        .map_err(io::Error::other)?;
+
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, EnterAlternateScreen, cursor::Hide)?;
+
+    let result = run(&mut universe, &mut stdout);
+
+    execute!(stdout, cursor::Show, LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    result?;
+
+    println!("Score: {}", universe.score());
+    Ok(())
+}
+
+fn run(universe: &mut Universe, stdout: &mut impl Write) -> io::Result<()> {
+    let mut last_tick = Instant::now();
+
+    loop {
+        draw(universe, stdout)?;
+
+        let delay = Duration::from_secs_f64(universe.recommended_frame_delay_ms() / 1000.0);
+        if event::poll(delay.saturating_sub(last_tick.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match key.code {
+                    KeyCode::Esc | KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Char('p') | KeyCode::Char(' ') if universe.state() == GameState::Paused => {
+                        universe.resume()
+                    }
+                    KeyCode::Char('p') | KeyCode::Char(' ') => universe.pause(),
+                    KeyCode::Char('r') => universe.reset(),
+                    code => {
+                        let key = match code {
+                            KeyCode::Up => "ArrowUp".to_string(),
+                            KeyCode::Down => "ArrowDown".to_string(),
+                            KeyCode::Left => "ArrowLeft".to_string(),
+                            KeyCode::Right => "ArrowRight".to_string(),
+                            KeyCode::Char(c) => c.to_string(),
+                            _ => continue,
+                        };
+                        universe.press_key(&key);
+                    }
+                }
+            }
+            continue;
+        }
+
+        universe.tick(0);
+        last_tick = Instant::now();
+    }
+}
+
+fn draw(universe: &Universe, stdout: &mut impl Write) -> io::Result<()> {
+    queue!(stdout, cursor::MoveTo(0, 0))?;
+    for line in universe.render().lines() {
+        queue!(stdout, Print(line), cursor::MoveToNextLine(1))?;
+    }
+
+    let status = match universe.state() {
+        GameState::GameOver => "game over - r to restart, q to quit",
+        GameState::Victory => "you win - r to play again, q to quit",
+        GameState::Finished => "time's up - r to play again, q to quit",
+        GameState::Paused => "paused - p to resume",
+        _ => "",
+    };
+    queue!(
+        stdout,
+        Print(format!("score {}  {}", universe.score(), status)),
+        terminal::Clear(ClearType::UntilNewLine)
+    )?;
+    stdout.flush()
+}
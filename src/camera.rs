@@ -0,0 +1,87 @@
+use crate::Universe;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// The part of the board on screen, in cells: `w` x `h` from the top-left
+// corner (`x`, `y`). Along an edge that wraps it may run off the right or
+// bottom and carry on from the other side.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Camera {
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+    pub(crate) w: u32,
+    pub(crate) h: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Camera {
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn w(&self) -> u32 {
+        self.w
+    }
+
+    pub fn h(&self) -> u32 {
+        self.h
+    }
+}
+
+// Centers a `view` cells long span on `head` along one axis of `size`.
+// Solid edges stop the camera so it never shows past them.
+fn follow(head: u32, view: u32, size: u32, wraps: bool) -> u32 {
+    let start = head as i64 - (view / 2) as i64;
+    if view == size {
+        0
+    } else if wraps {
+        start.rem_euclid(size as i64) as u32
+    } else {
+        start.clamp(0, (size - view) as i64) as u32
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Shows only a `width` x `height` window of the board that follows
+    // player 0's head, for boards far bigger than the screen. The window is
+    // trimmed to the board; 0 for either shows the whole board again.
+    pub fn set_viewport(&mut self, width: u32, height: u32) {
+        self.viewport = if width == 0 || height == 0 {
+            None
+        } else {
+            Some((width, height))
+        };
+    }
+
+    pub fn camera(&self) -> Camera {
+        let (w, h) = self.viewport.unwrap_or((self.width, self.height));
+        let (w, h) = (w.min(self.width), h.min(self.height));
+        let head = self.head();
+        Camera {
+            x: follow(head.x, w, self.width, self.topology.wraps_x()),
+            y: follow(head.y, h, self.height, self.topology.wraps_y()),
+            w,
+            h,
+        }
+    }
+
+    // The cells under the camera, one `Cell` a byte, row by row.
+    pub fn visible_cells(&self) -> Vec<u8> {
+        let Camera { x, y, w, h } = self.camera();
+        let mut cells = Vec::with_capacity((w * h) as usize);
+        for row in 0..h {
+            let row = (y + row) % self.height;
+            for column in 0..w {
+                let column = (x + column) % self.width;
+                cells.push(self.cells.get(self.get_index(row, column)) as u8);
+            }
+        }
+        cells
+    }
+}
@@ -0,0 +1,115 @@
+use crate::snapshot::Variants;
+use crate::theme::Theme;
+use crate::{Cell, Universe};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+pub const DEFAULT_CELL_SIZE: u32 = 8;
+
+// Draws the board onto a canvas with its 2D context, one `cell_size` square
+// per cell. The canvas is sized to the board, and only cells that changed
+// since the last frame are repainted.
+#[wasm_bindgen]
+pub struct CanvasRenderer {
+    canvas: HtmlCanvasElement,
+    context: CanvasRenderingContext2d,
+    cell_size: u32,
+    // CSS colors indexed by `Cell as usize`.
+    colors: Vec<String>,
+    grid: Option<String>,
+    drawn: Vec<Cell>,
+}
+
+#[wasm_bindgen]
+impl CanvasRenderer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement, cell_size: u32) -> Result<CanvasRenderer, JsValue> {
+        let context = canvas
+            .get_context("2d")?
+            .ok_or_else(|| JsValue::from_str("canvas has no 2d context"))?
+            .dyn_into::<CanvasRenderingContext2d>()?;
+
+        let mut renderer = CanvasRenderer {
+            canvas,
+            context,
+            cell_size: cell_size.max(1),
+            colors: Vec::new(),
+            grid: None,
+            drawn: Vec::new(),
+        };
+        renderer.set_theme(&Theme::default());
+        Ok(renderer)
+    }
+
+    pub fn cell_size(&self) -> u32 {
+        self.cell_size
+    }
+
+    pub fn set_cell_size(&mut self, cell_size: u32) {
+        self.cell_size = cell_size.max(1);
+        self.invalidate();
+    }
+
+    // Any CSS color, e.g. "#222" or "rgb(0 128 0)".
+    pub fn set_color(&mut self, cell: Cell, color: String) {
+        self.colors[cell as usize] = color;
+        self.invalidate();
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.colors = Cell::VARIANTS.iter().map(|&cell| theme.css(cell)).collect();
+        self.grid = theme.grid_css();
+        self.invalidate();
+    }
+
+    // Repaints every cell on the next frame, e.g. after something else drew
+    // over the canvas.
+    pub fn invalidate(&mut self) {
+        self.drawn.clear();
+    }
+
+    pub fn render_to_canvas(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        let width = universe.width * self.cell_size;
+        let height = universe.height * self.cell_size;
+        if self.canvas.width() != width || self.canvas.height() != height {
+            self.canvas.set_width(width);
+            self.canvas.set_height(height);
+            self.invalidate();
+        }
+        if self.drawn.len() != universe.cells.len() {
+            self.drawn.clear();
+        }
+        // Grid lines are the background showing through a one pixel gap at
+        // the right and bottom of every cell.
+        let gap = if self.grid.is_some() && self.cell_size > 1 { 1.0 } else { 0.0 };
+        if self.drawn.is_empty() {
+            if let Some(grid) = &self.grid {
+                self.context.set_fill_style_str(grid);
+                self.context.fill_rect(0.0, 0.0, width as f64, height as f64);
+            }
+        }
+
+        let size = self.cell_size as f64;
+        let mut fill = None;
+        for (idx, cell) in universe.cells.iter().enumerate() {
+            if self.drawn.get(idx) == Some(&cell) {
+                continue;
+            }
+
+            // Runs of one color are common, so the fill style is only set
+            // when it changes.
+            if fill != Some(cell) {
+                self.context.set_fill_style_str(&self.colors[cell as usize]);
+                fill = Some(cell);
+            }
+            let x = (idx as u32 % universe.width) as f64 * size;
+            let y = (idx as u32 / universe.width) as f64 * size;
+            self.context.fill_rect(x, y, size - gap, size - gap);
+        }
+
+        self.drawn.clear();
+        self.drawn.extend(universe.cells.iter());
+        Ok(())
+    }
+}
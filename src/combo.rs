@@ -0,0 +1,113 @@
+use crate::{GameEvent, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Apples eaten at most this many ticks apart chain into a combo.
+pub const COMBO_WINDOW_TICKS: u32 = 20;
+pub const MAX_MULTIPLIER: u32 = 5;
+
+// Every apple chained onto a combo raises the score multiplier by one, up to
+// MAX_MULTIPLIER. Once the combo breaks the multiplier doesn't drop at once
+// but loses one step per COMBO_WINDOW_TICKS, so a new combo started soon
+// after picks up where the last one left off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Combo {
+    pub(crate) chain: u32,
+    pub(crate) multiplier: u32,
+    pub(crate) idle_ticks: u32,
+}
+
+impl Default for Combo {
+    fn default() -> Combo {
+        Combo {
+            chain: 0,
+            multiplier: 1,
+            idle_ticks: 0,
+        }
+    }
+}
+
+impl Combo {
+    fn eat(&mut self) {
+        if self.chain > 0 {
+            self.multiplier = (self.multiplier + 1).min(MAX_MULTIPLIER);
+        }
+        self.chain += 1;
+        self.idle_ticks = 0;
+    }
+
+    // Returns the length of a combo that just broke.
+    fn tick(&mut self) -> Option<u32> {
+        // Nothing left to run out; staying put keeps snapshots short.
+        if *self == Combo::default() {
+            return None;
+        }
+
+        self.idle_ticks += 1;
+        if self.idle_ticks <= COMBO_WINDOW_TICKS {
+            return None;
+        }
+
+        self.idle_ticks = 0;
+        let broken = std::mem::take(&mut self.chain);
+        if broken == 0 {
+            self.multiplier = (self.multiplier - 1).max(1);
+        }
+        Some(broken).filter(|&chain| chain > 1)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Off by default, leaving every apple worth its usual points.
+    pub fn set_combos(&mut self, enabled: bool) {
+        self.combos = enabled;
+        if !enabled {
+            for player in &mut self.players {
+                player.combo = Combo::default();
+            }
+        }
+    }
+
+    pub fn combos(&self) -> bool {
+        self.combos
+    }
+
+    // Apples in player 0's running combo; 1 after a lone apple, 0 once the
+    // window has passed.
+    pub fn combo(&self) -> u32 {
+        self.players[0].combo.chain
+    }
+
+    // What player 0's apples score times their usual points, raised by each
+    // chained apple.
+    pub fn multiplier(&self) -> u32 {
+        self.players[0].combo.multiplier
+    }
+}
+
+impl Universe {
+    // Returns the multiplier the apple scores with, 1 with combos off.
+    pub(crate) fn chain_apple(&mut self, player: usize) -> u32 {
+        if !self.combos {
+            return 1;
+        }
+        let combo = &mut self.players[player].combo;
+        combo.eat();
+        combo.multiplier
+    }
+
+    pub(crate) fn decay_combos(&mut self) {
+        for id in 0..self.players.len() {
+            if let Some(combo) = self.players[id].combo.tick() {
+                self.emit(GameEvent::ComboBroken {
+                    player: id as u32,
+                    combo,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use crate::modes::GameMode;
+use crate::scoring::Scoring;
+use crate::speed::SpeedModel;
+use crate::{random_seed, Snake, Universe, UniverseTopology, DEFAULT_FPS_TARGET, DEFAULT_HEIGHT, DEFAULT_WIDTH};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "config")]
+use wasm_bindgen::prelude::*;
+
+// Everything a new game can be set up with in one go. Missing fields take
+// their defaults, so `{}` is a classic 64x64 game:
+//
+//     { "width": 20, "height": 20, "topology": "Flat", "seed": 7,
+//       "speed": { "curve": "Linear", "base": 8, "step": 0.5, "max": 20 },
+//       "apples": 2, "mode": { "Timed": { "seconds": 60 } },
+//       "walls": [[0, 0], [1, 0]], "scoring": { "apple": 10, "tick": 1 } }
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct Config {
+    pub width: u32,
+    pub height: u32,
+    pub topology: UniverseTopology,
+    // A random seed when left out.
+    pub seed: Option<u64>,
+    pub speed: SpeedModel,
+    pub apples: u32,
+    pub mode: GameMode,
+    pub walls: Vec<(u32, u32)>,
+    pub scoring: Scoring,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            width: DEFAULT_WIDTH,
+            height: DEFAULT_HEIGHT,
+            topology: UniverseTopology::Toroidal,
+            seed: None,
+            speed: SpeedModel::default(),
+            apples: 1,
+            mode: GameMode::Classic,
+            walls: Vec::new(),
+            scoring: Scoring::default(),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+#[wasm_bindgen]
+impl Universe {
+    pub fn new_with_config(config: JsValue) -> Result<Universe, String> {
+        let config: Config = serde_wasm_bindgen::from_value(config).map_err(|err| err.to_string())?;
+        Universe::from_config(&config)
+    }
+}
+
+impl Universe {
+    pub fn from_config(config: &Config) -> Result<Universe, String> {
+        let seed = config.seed.unwrap_or_else(random_seed);
+        let mut universe =
+            Universe::new_with_seed(Snake::default(), DEFAULT_FPS_TARGET, config.width, config.height, seed)?;
+
+        universe.set_topology(config.topology);
+        let SpeedModel { curve, base, step, max } = config.speed;
+        universe.set_speed_curve(curve, base, step, max)?;
+        universe.set_game_mode(config.mode);
+        for &(x, y) in &config.walls {
+            if !universe.set_wall(x, y) {
+                return Err(format!("can't put a wall at ({}, {})", x, y));
+            }
+        }
+        universe.set_apple_count(config.apples);
+        universe.set_scoring(config.scoring);
+        Ok(universe)
+    }
+
+    // The settings this game runs with, minus the seed. Rings closed by a
+    // shrinking arena aren't walls of the board.
+    pub fn config(&self) -> Config {
+        Config {
+            width: self.width,
+            height: self.height,
+            topology: self.topology,
+            seed: None,
+            speed: self.speed,
+            apples: self.apple_count,
+            mode: self.mode,
+            walls: self
+                .walls
+                .iter()
+                .filter(|&idx| !self.shrink.closed.contains(&(idx as u32)))
+                .map(|idx| (idx as u32 % self.width, idx as u32 / self.width))
+                .collect(),
+            scoring: self.scoring,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use crate::hash::checksum;
+use crate::levels::generate_level_with_size;
+use crate::modes::GameMode;
+use crate::rng::Rng;
+use crate::speed::{SpeedCurve, DEFAULT_MAX_TICKS_PER_SECOND, DEFAULT_TICKS_PER_SECOND};
+use crate::{Snake, Universe, DEFAULT_FPS_TARGET};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+const DAILY_SECONDS: u32 = 120;
+const DAILY_SIZES: [u32; 3] = [20, 24, 32];
+const DAILY_MAX_DIFFICULTY: u32 = 6;
+const DAILY_MAX_APPLES: u32 = 3;
+
+// The seed for a `YYYY-MM-DD` date; every build and platform agrees on it.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn daily_seed(date: &str) -> u64 {
+    Rng::new(checksum(date.trim().as_bytes())).next_u64()
+}
+
+// The same game for everyone on a given UTC day: the seed, the generated
+// board and the rules all follow from the date, and the match is timed so
+// scores compare fairly.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DailyChallenge {
+    date: String,
+    seed: u64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl DailyChallenge {
+    pub fn new(date: &str) -> Result<DailyChallenge, String> {
+        let date = date.trim();
+        let parts: Vec<_> = date.split('-').collect();
+        let valid = match parts.as_slice() {
+            [year, month, day] => {
+                year.len() == 4
+                    && month.len() == 2
+                    && day.len() == 2
+                    && parts.iter().all(|part| part.bytes().all(|b| b.is_ascii_digit()))
+                    && (1..=12).contains(&month.parse::<u32>().unwrap_or(0))
+                    && (1..=31).contains(&day.parse::<u32>().unwrap_or(0))
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(format!("'{}' is not a YYYY-MM-DD date", date));
+        }
+
+        Ok(DailyChallenge {
+            date: date.to_string(),
+            seed: daily_seed(date),
+        })
+    }
+
+    pub fn today() -> DailyChallenge {
+        DailyChallenge::new(&today()).expect("today is a valid date")
+    }
+
+    pub fn date(&self) -> String {
+        self.date.clone()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // For tagging high scores, e.g. "daily-2024-05-01".
+    pub fn tag(&self) -> String {
+        format!("daily-{}", self.date)
+    }
+
+    pub fn start(&self) -> Result<Universe, String> {
+        let mut rng = Rng::new(self.seed);
+        let size = DAILY_SIZES[rng.below(DAILY_SIZES.len() as u32) as usize];
+        let level = generate_level_with_size(rng.next_u64(), 1 + rng.below(DAILY_MAX_DIFFICULTY), size, size);
+        let (curve, step) = match rng.below(3) {
+            0 => (SpeedCurve::Constant, 0.0),
+            1 => (SpeedCurve::Linear, 0.25),
+            _ => (SpeedCurve::Exponential, 0.02),
+        };
+        let apples = 1 + rng.below(DAILY_MAX_APPLES);
+
+        let mut universe = Universe::new_with_seed(Snake::default(), DEFAULT_FPS_TARGET, size, size, self.seed)?;
+        universe.load_level(&level)?;
+        universe.set_speed_curve(curve, DEFAULT_TICKS_PER_SECOND, step, DEFAULT_MAX_TICKS_PER_SECOND)?;
+        universe.set_apple_count(apples);
+        universe.set_game_mode(GameMode::Timed {
+            seconds: DAILY_SECONDS,
+        });
+        Ok(universe)
+    }
+}
+
+// Today's date in UTC, so the challenge flips at the same moment everywhere.
+#[cfg(feature = "wasm")]
+fn today() -> String {
+    let iso = String::from(js_sys::Date::new_0().to_iso_string());
+    iso[..10].to_string()
+}
+
+#[cfg(not(feature = "wasm"))]
+fn today() -> String {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+
+    // Days since 1970-01-01 to a civil date, after Howard Hinnant's algorithm.
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
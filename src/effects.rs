@@ -0,0 +1,104 @@
+use crate::food::FoodKind;
+use crate::{GameOverReason, Position, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Effects are dropped oldest first past this, so a game nobody drains
+// doesn't grow without bound.
+pub const MAX_QUEUED_EFFECTS: usize = 256;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectKind {
+    // A snake ate the food at the cell.
+    AppleBurst,
+    // A snake died with its head at the cell.
+    DeathFlash,
+    // A snake ran into the wall at the cell, or off the board from it.
+    WallHit,
+}
+
+// Something worth animating that happened on a tick, where it happened.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effect {
+    kind: EffectKind,
+    x: u32,
+    y: u32,
+    player: u32,
+    tick: u32,
+    food: Option<FoodKind>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Effect {
+    pub fn kind(&self) -> EffectKind {
+        self.kind
+    }
+
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    pub fn player(&self) -> u32 {
+        self.player
+    }
+
+    // The tick it happened on, counting from 1 like `Stats::ticks`.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    // What was eaten, for an `AppleBurst`.
+    pub fn food(&self) -> Option<FoodKind> {
+        self.food
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Effects since the last call, oldest first.
+    pub fn take_effects(&mut self) -> Vec<Effect> {
+        std::mem::take(&mut self.effects)
+    }
+}
+
+impl Universe {
+    fn push_effect(&mut self, kind: EffectKind, at: Position, player: usize, food: Option<FoodKind>) {
+        if self.silent {
+            return;
+        }
+        if self.effects.len() == MAX_QUEUED_EFFECTS {
+            self.effects.remove(0);
+        }
+        self.effects.push(Effect {
+            kind,
+            x: at.x,
+            y: at.y,
+            player: player as u32,
+            tick: self.stats.ticks + 1,
+            food,
+        });
+    }
+
+    pub(crate) fn burst(&mut self, player: usize, food: FoodKind, at: Position) {
+        self.push_effect(EffectKind::AppleBurst, at, player, Some(food));
+    }
+
+    // Called before the body is cleared away.
+    pub(crate) fn flash_death(&mut self, player: usize, reason: GameOverReason) {
+        let snake = &self.players[player].snake;
+        let head = *snake.body.front().unwrap();
+        if reason == GameOverReason::HitWall {
+            let ahead = self.step_from(&head, snake.direction, true).unwrap_or(head);
+            let idx = self.get_index(ahead.y, ahead.x);
+            let wall = if self.walls.contains(idx) { ahead } else { head };
+            self.push_effect(EffectKind::WallHit, wall, player, None);
+        }
+        self.push_effect(EffectKind::DeathFlash, head, player, None);
+    }
+}
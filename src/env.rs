@@ -0,0 +1,153 @@
+use crate::{Cell, DirectionName, GameState, Snake, Universe, UniverseTopology};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Planes of the observation, each `width * height` floats in row-major order.
+pub const PLANES: usize = 5;
+pub const PLANE_HEAD: usize = 0;
+pub const PLANE_BODY: usize = 1;
+pub const PLANE_FOOD: usize = 2;
+pub const PLANE_POISON: usize = 3;
+pub const PLANE_WALL: usize = 4;
+
+// Actions 0-3 follow `DirectionName` (Up, Down, Left, Right); anything else
+// keeps the current heading.
+pub const ACTIONS: u32 = 4;
+
+pub const DEATH_REWARD: f32 = -1.0;
+
+// An episode is cut off after this many board areas' worth of steps
+// without food, so a circling agent can't run forever.
+const STALL_FACTOR: u32 = 2;
+
+pub type Observation = Vec<f32>;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug)]
+pub struct Step {
+    observation: Observation,
+    reward: f32,
+    done: bool,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Step {
+    pub fn observation(&self) -> Vec<f32> {
+        self.observation.clone()
+    }
+
+    pub fn reward(&self) -> f32 {
+        self.reward
+    }
+
+    pub fn done(&self) -> bool {
+        self.done
+    }
+}
+
+impl Step {
+    pub fn into_parts(self) -> (Observation, f32, bool) {
+        (self.observation, self.reward, self.done)
+    }
+}
+
+// A gym-style wrapper: flat board, one snake, reward equal to the points
+// scored in the step and DEATH_REWARD for crashing.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Env {
+    universe: Universe,
+    stalled: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Env {
+    pub fn new(width: u32, height: u32, seed: u64) -> Result<Env, String> {
+        let length = (width / 2).clamp(1, 4);
+        let snake = Snake::new_at(width / 2, height / 2, DirectionName::Right, length);
+        let mut universe = Universe::new_with_seed(snake, 60.0, width, height, seed)?;
+        universe.set_topology(UniverseTopology::Flat);
+
+        Ok(Env { universe, stalled: 0 })
+    }
+
+    pub fn reset(&mut self) -> Vec<f32> {
+        self.universe.reset();
+        self.stalled = 0;
+        self.observe()
+    }
+
+    pub fn step(&mut self, action: u32) -> Step {
+        let direction = match action {
+            0 => Some(DirectionName::Up),
+            1 => Some(DirectionName::Down),
+            2 => Some(DirectionName::Left),
+            3 => Some(DirectionName::Right),
+            _ => None,
+        };
+        if let Some(direction) = direction {
+            self.universe.on_click(direction);
+        }
+
+        let score = self.universe.score();
+        let result = self.universe.tick(0);
+        let mut reward = (self.universe.score() - score) as f32;
+
+        self.stalled = if result.ate_apple() { 0 } else { self.stalled + 1 };
+        let limit = STALL_FACTOR * self.universe.width() * self.universe.height();
+
+        let done = match self.universe.state() {
+            GameState::GameOver => {
+                reward = DEATH_REWARD;
+                true
+            }
+            GameState::Victory | GameState::Finished => true,
+            _ => self.stalled >= limit,
+        };
+
+        Step {
+            observation: self.observe(),
+            reward,
+            done,
+        }
+    }
+
+    pub fn observation_len(&self) -> usize {
+        self.universe.cells.len() * PLANES
+    }
+
+    pub fn action_count(&self) -> u32 {
+        ACTIONS
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.universe.seed()
+    }
+
+    pub fn render(&self) -> String {
+        self.universe.render()
+    }
+
+    fn observe(&self) -> Observation {
+        let cells = &self.universe.cells;
+        let mut observation = vec![0.0; cells.len() * PLANES];
+
+        for (idx, cell) in cells.iter().enumerate() {
+            let plane = match cell {
+                Cell::Empty | Cell::Ghost | Cell::Portal => continue,
+                Cell::SnakeHead => PLANE_HEAD,
+                Cell::SnakeBody => PLANE_BODY,
+                Cell::Apple | Cell::Golden | Cell::Speed | Cell::PowerUp => PLANE_FOOD,
+                Cell::Poison => PLANE_POISON,
+                Cell::Wall | Cell::Hazard => PLANE_WALL,
+            };
+            observation[plane * cells.len() + idx] = 1.0;
+        }
+        observation
+    }
+}
+
+impl Env {
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+}
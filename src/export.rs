@@ -0,0 +1,306 @@
+#[cfg(feature = "gif")]
+use crate::replay::Replay;
+#[cfg(feature = "gif")]
+use crate::snapshot::Variants;
+use crate::theme::Color;
+use crate::{Cell, Player, Position, Universe};
+use std::fmt::Write;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Side of a cell in SVG user units; the image scales cleanly either way.
+const SVG_CELL: u32 = 20;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // The current frame as a standalone SVG document in the theme's colors:
+    // snakes as rounded segments joined along the body with eyes on the
+    // head, food as round fruit and walls as blocks.
+    pub fn render_svg(&self) -> String {
+        let (width, height) = (self.width * SVG_CELL, self.height * SVG_CELL);
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+            w = width,
+            h = height
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="{}" height="{}" {}/>"#,
+            width,
+            height,
+            fill(self.theme.color(Cell::Empty))
+        );
+
+        if let Some(grid) = self.theme.grid() {
+            let _ = write!(svg, r#"<path stroke-width="1" {} d=""#, stroke(grid));
+            for x in 1..self.width {
+                let _ = write!(svg, "M{} 0V{}", x * SVG_CELL, height);
+            }
+            for y in 1..self.height {
+                let _ = write!(svg, "M0 {}H{}", y * SVG_CELL, width);
+            }
+            svg.push_str("\"/>\n");
+        }
+
+        for (idx, cell) in self.cells.iter().enumerate() {
+            let x = idx as u32 % self.width * SVG_CELL;
+            let y = idx as u32 / self.width * SVG_CELL;
+            let color = self.theme.color(cell);
+            let (cx, cy, r) = (x + SVG_CELL / 2, y + SVG_CELL / 2, SVG_CELL * 2 / 5);
+            match cell {
+                Cell::Empty | Cell::SnakeHead | Cell::SnakeBody => {}
+                Cell::Apple => {
+                    let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, cx, cy + 1, r, fill(color));
+                    let _ = writeln!(
+                        svg,
+                        r#"<path d="M{} {}q2 -4 5 -5" fill="none" stroke-width="2" {}/>"#,
+                        cx,
+                        cy - r + 2,
+                        stroke(self.theme.color(Cell::SnakeBody))
+                    );
+                }
+                Cell::Poison | Cell::Golden | Cell::Speed | Cell::PowerUp => {
+                    let _ = writeln!(svg, r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, cx, cy, r, fill(color));
+                }
+                Cell::Portal => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<circle cx="{}" cy="{}" r="{}" fill="none" stroke-width="3" {}/>"#,
+                        cx,
+                        cy,
+                        r - 1,
+                        stroke(color)
+                    );
+                }
+                Cell::Hazard => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<path d="M{} {}L{} {}L{} {}Z" {}/>"#,
+                        cx,
+                        y + 2,
+                        x + SVG_CELL - 2,
+                        y + SVG_CELL - 2,
+                        x + 2,
+                        y + SVG_CELL - 2,
+                        fill(color)
+                    );
+                }
+                Cell::Wall | Cell::Ghost => {
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{s}" height="{s}" {}/>"#,
+                        x,
+                        y,
+                        fill(color),
+                        s = SVG_CELL
+                    );
+                }
+            }
+        }
+
+        for player in &self.players {
+            self.svg_snake(&mut svg, player);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+#[cfg(feature = "screenshot")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // The board as PNG file bytes, `scale` pixels to a cell like
+    // `render_rgba`, e.g. for a "download screenshot" button.
+    pub fn screenshot_png(&self, scale: u32) -> Vec<u8> {
+        let scale = scale.max(1);
+        let mut rgba = Vec::new();
+        self.paint_rgba(scale, &mut rgba);
+
+        let mut png = Vec::new();
+        let mut encoder = png::Encoder::new(&mut png, self.width * scale, self.height * scale);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().expect("board dimensions fit a PNG");
+        writer.write_image_data(&rgba).expect("the image matches its header");
+        writer.finish().expect("PNG encodes into memory");
+        png
+    }
+}
+
+#[cfg(feature = "gif")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Replay {
+    // Plays the run back from the start and encodes every tick as a frame
+    // of a looping animated GIF in the default theme, `scale` pixels to a
+    // cell, shown `fps` frames a second. GIF timing counts hundredths of a second and most
+    // viewers won't go faster than 50 frames a second.
+    pub fn to_gif(&self, scale: u32, fps: f64) -> Result<Vec<u8>, String> {
+        let mut replay = self.clone();
+        let mut universe = replay.restart()?;
+        let scale = scale.max(1);
+        let (width, height) = (universe.width * scale, universe.height * scale);
+        if width > u16::MAX as u32 || height > u16::MAX as u32 {
+            return Err(format!("a {}x{} image is too big for a GIF", width, height));
+        }
+        let delay = (100.0 / fps.max(f64::MIN_POSITIVE)).round().clamp(2.0, u16::MAX as f64) as u16;
+
+        // Cells are drawn by their index in the palette, grid lines last.
+        let theme = &universe.theme;
+        let grid_index = Cell::VARIANTS.len() as u8;
+        let palette: Vec<u8> = Cell::VARIANTS
+            .iter()
+            .map(|&cell| theme.color(cell))
+            .chain(theme.grid())
+            .flat_map(|[r, g, b, _]| [r, g, b])
+            .collect();
+
+        let mut gif = Vec::new();
+        {
+            let map_err = |err: gif::EncodingError| err.to_string();
+            let mut encoder = gif::Encoder::new(&mut gif, width as u16, height as u16, &palette).map_err(map_err)?;
+            encoder.set_repeat(gif::Repeat::Infinite).map_err(map_err)?;
+
+            let mut pixels = Vec::new();
+            loop {
+                let grid = universe.theme.grid().map(|_| [grid_index]);
+                universe.paint(scale, &mut pixels, |cell| [cell as u8], grid);
+                let mut frame = gif::Frame::from_indexed_pixels(width as u16, height as u16, pixels.as_slice(), None);
+                frame.delay = delay;
+                encoder.write_frame(&frame).map_err(map_err)?;
+
+                if !replay.step(&mut universe) {
+                    break;
+                }
+            }
+        }
+        Ok(gif)
+    }
+}
+
+impl Universe {
+    // Fills `rgba` with one `scale`×`scale` block of pixels per cell. With
+    // grid lines in the theme, the last column and row of each block take
+    // the grid color.
+    pub(crate) fn paint_rgba(&self, scale: u32, rgba: &mut Vec<u8>) {
+        self.paint(scale, rgba, |cell| self.theme.color(cell), self.theme.grid());
+    }
+
+    // Like `paint_rgba` with `N` bytes a pixel.
+    fn paint<const N: usize>(
+        &self,
+        scale: u32,
+        pixels: &mut Vec<u8>,
+        color: impl Fn(Cell) -> [u8; N],
+        grid: Option<[u8; N]>,
+    ) {
+        let row_pixels = (self.width * scale) as usize;
+        pixels.resize(row_pixels * (self.height * scale) as usize * N, 0);
+        let grid = grid.filter(|_| scale > 1);
+
+        for (idx, cell) in self.cells.iter().enumerate() {
+            let color = color(cell);
+            let x0 = (idx as u32 % self.width * scale) as usize;
+            let y0 = (idx as u32 / self.width * scale) as usize;
+
+            for (dy, y) in (y0..y0 + scale as usize).enumerate() {
+                let start = (y * row_pixels + x0) * N;
+                let row = pixels[start..start + scale as usize * N].chunks_exact_mut(N);
+                for (dx, pixel) in row.enumerate() {
+                    let edge = dx + 1 == scale as usize || dy + 1 == scale as usize;
+                    pixel.copy_from_slice(&grid.filter(|_| edge).unwrap_or(color));
+                }
+            }
+        }
+    }
+
+    // Only the parts of the body still on the board are drawn, so a dead
+    // snake that was cleared away leaves nothing behind. Tail first, so the
+    // head ends up on top.
+    fn svg_snake(&self, svg: &mut String, player: &Player) {
+        let on_board = |p: &Position, cell: Cell| self.cells.get((p.y * self.width + p.x) as usize) == cell;
+        let body_color = self.theme.color(Cell::SnakeBody);
+        let inset = SVG_CELL / 10;
+        let segment = SVG_CELL - 2 * inset;
+
+        let body: Vec<_> = player.snake.body.iter().collect();
+        for (i, p) in body.iter().enumerate().rev() {
+            let cell = if i == 0 { Cell::SnakeHead } else { Cell::SnakeBody };
+            if !on_board(p, cell) {
+                continue;
+            }
+            // Bridge the gap to the next segment unless the body wraps
+            // around the board there.
+            if let Some(next) = body.get(i + 1).filter(|next| on_board(next, Cell::SnakeBody)) {
+                let (dx, dy) = (next.x as i64 - p.x as i64, next.y as i64 - p.y as i64);
+                if dx.abs() + dy.abs() == 1 {
+                    let (bx, by) = (p.x.min(next.x) * SVG_CELL + inset, p.y.min(next.y) * SVG_CELL + inset);
+                    let (bw, bh) = if dx != 0 {
+                        (SVG_CELL + segment, segment)
+                    } else {
+                        (segment, SVG_CELL + segment)
+                    };
+                    let _ = writeln!(
+                        svg,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" {}/>"#,
+                        bx,
+                        by,
+                        bw,
+                        bh,
+                        fill(body_color)
+                    );
+                }
+            }
+
+            let (x, y) = (p.x * SVG_CELL + inset, p.y * SVG_CELL + inset);
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{}" y="{}" width="{s}" height="{s}" rx="{}" {}/>"#,
+                x,
+                y,
+                inset * 2,
+                fill(self.theme.color(cell)),
+                s = segment
+            );
+        }
+
+        // Eyes sit towards the heading, side by side across it.
+        let head = body[0];
+        if on_board(head, Cell::SnakeHead) {
+            let (vx, vy) = (player.snake.direction.vx as f64, player.snake.direction.vy as f64);
+            let (cx, cy) = ((head.x as f64 + 0.5) * SVG_CELL as f64, (head.y as f64 + 0.5) * SVG_CELL as f64);
+            let (ahead, apart) = (SVG_CELL as f64 * 0.2, SVG_CELL as f64 * 0.2);
+            for side in [-1.0, 1.0].iter() {
+                let _ = writeln!(
+                    svg,
+                    r#"<circle cx="{}" cy="{}" r="{}" {}/>"#,
+                    cx + vx * ahead - vy * apart * side,
+                    cy + vy * ahead + vx * apart * side,
+                    SVG_CELL as f64 * 0.1,
+                    fill(self.theme.color(Cell::Empty))
+                );
+            }
+        }
+    }
+}
+
+fn hex([r, g, b, _]: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn fill(color: Color) -> String {
+    paint("fill", color)
+}
+
+fn stroke(color: Color) -> String {
+    paint("stroke", color)
+}
+
+fn paint(property: &str, color: Color) -> String {
+    if color[3] == 0xFF {
+        format!(r#"{}="{}""#, property, hex(color))
+    } else {
+        format!(r#"{p}="{}" {p}-opacity="{}""#, hex(color), color[3] as f64 / 255.0, p = property)
+    }
+}
@@ -0,0 +1,78 @@
+use crate::Universe;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// What the visibility buffer holds for cells out of sight; past every `Cell`.
+pub const UNKNOWN: u8 = u8::MAX;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn fog_radius(&self) -> u32 {
+        self.fog_radius
+    }
+
+    // Fog of war: only cells within `radius` of player 0's head, as the crow
+    // flies and across wrapping edges, are revealed in `visibility`. 0 lifts
+    // the fog. The game itself plays the same either way.
+    pub fn set_fog_radius(&mut self, radius: u32) {
+        self.fog_radius = radius;
+        self.refresh_fog();
+    }
+
+    // One byte per cell alongside `cells`: the cell as `cells` has it where
+    // player 0 can see, UNKNOWN elsewhere. Updated every tick; empty while
+    // there is no fog.
+    pub fn visibility(&self) -> *const u8 {
+        self.fog.as_ptr()
+    }
+
+    pub fn visibility_u8(&self) -> Vec<u8> {
+        self.fog.clone()
+    }
+
+    pub fn is_visible(&self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        self.fog_radius == 0 || self.fog[self.get_index(y, x)] != UNKNOWN
+    }
+}
+
+impl Universe {
+    pub(crate) fn refresh_fog(&mut self) {
+        self.fog.clear();
+        if self.fog_radius == 0 {
+            return;
+        }
+
+        self.fog.resize(self.cells.len(), UNKNOWN);
+        let head = *self.head();
+        // Past the board's diagonal every cell is in sight anyway, and a
+        // smaller radius squares without overflowing.
+        let radius = (self.fog_radius as i64).min(self.width as i64 + self.height as i64);
+        let reach = radius.min(self.width.max(self.height) as i64);
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if dx * dx + dy * dy > radius * radius {
+                    continue;
+                }
+                let x = sight_line(head.x, dx, self.width, self.topology.wraps_x());
+                let y = sight_line(head.y, dy, self.height, self.topology.wraps_y());
+                if let (Some(x), Some(y)) = (x, y) {
+                    let idx = self.get_index(y, x);
+                    self.fog[idx] = self.cells.get(idx) as u8;
+                }
+            }
+        }
+    }
+}
+
+// `offset` cells from `from` along an axis of `size`; None past a solid edge.
+fn sight_line(from: u32, offset: i64, size: u32, wraps: bool) -> Option<u32> {
+    let to = from as i64 + offset;
+    if wraps {
+        Some(to.rem_euclid(size as i64) as u32)
+    } else {
+        (0..size as i64).contains(&to).then_some(to as u32)
+    }
+}
@@ -0,0 +1,117 @@
+use crate::rng::Rng;
+use crate::{Cell, Position};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const GOLDEN_POINTS: u32 = 5;
+pub const GOLDEN_TTL: u32 = 50;
+pub const POISON_SHRINK: usize = 2;
+pub const SPEED_BOOST_TICKS: u32 = 40;
+pub const SPEED_BOOST_MULTIPLIER: f64 = 1.5;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum FoodKind {
+    Normal,
+    Poison,
+    Golden,
+    Speed,
+    PowerUp,
+}
+
+impl FoodKind {
+    pub fn cell(self) -> Cell {
+        match self {
+            FoodKind::Normal => Cell::Apple,
+            FoodKind::Poison => Cell::Poison,
+            FoodKind::Golden => Cell::Golden,
+            FoodKind::Speed => Cell::Speed,
+            FoodKind::PowerUp => Cell::PowerUp,
+        }
+    }
+
+    pub fn points(self) -> u32 {
+        match self {
+            FoodKind::Normal | FoodKind::Speed | FoodKind::PowerUp => 1,
+            FoodKind::Golden => GOLDEN_POINTS,
+            FoodKind::Poison => 0,
+        }
+    }
+
+    pub fn grows(self) -> bool {
+        self != FoodKind::Poison
+    }
+
+    // 10% golden, 10% poison, 8% speed, 5% power-up, the rest normal apples.
+    pub(crate) fn roll(rng: &mut Rng) -> FoodKind {
+        match rng.below(100) {
+            0..=9 => FoodKind::Golden,
+            10..=19 => FoodKind::Poison,
+            20..=27 => FoodKind::Speed,
+            28..=32 => FoodKind::PowerUp,
+            _ => FoodKind::Normal,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Food {
+    pub(crate) position: Position,
+    pub(crate) kind: FoodKind,
+    pub(crate) ticks_left: Option<u32>,
+}
+
+impl Food {
+    pub fn new(position: Position, kind: FoodKind) -> Food {
+        let ticks_left = match kind {
+            FoodKind::Golden => Some(GOLDEN_TTL),
+            _ => None,
+        };
+
+        Food {
+            position,
+            kind,
+            ticks_left,
+        }
+    }
+
+    // Counts down perishable food; returns true once it has expired.
+    pub(crate) fn age(&mut self) -> bool {
+        match &mut self.ticks_left {
+            Some(0) => true,
+            Some(ticks) => {
+                *ticks -= 1;
+                *ticks == 0
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Food {
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    pub fn x(&self) -> u32 {
+        self.position.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.position.y
+    }
+
+    pub fn kind(&self) -> FoodKind {
+        self.kind
+    }
+
+    pub fn ticks_left(&self) -> Option<u32> {
+        self.ticks_left
+    }
+}
@@ -0,0 +1,156 @@
+use crate::{Cell, DirectionName, GameState, Instant, Universe};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+struct LoopState {
+    universe: Universe,
+    render: js_sys::Function,
+    running: bool,
+    last_timestamp: Option<f64>,
+    handle: Option<i32>,
+    frame: Option<Closure<dyn FnMut(f64)>>,
+}
+
+// Drives `Universe::advance` from requestAnimationFrame and calls `render`
+// with the number of simulation steps after every frame, timing it for
+// `Universe::perf_stats`.
+#[wasm_bindgen]
+pub struct GameLoop {
+    inner: Rc<RefCell<LoopState>>,
+}
+
+#[wasm_bindgen]
+impl GameLoop {
+    #[wasm_bindgen(constructor)]
+    pub fn new(universe: Universe, render: js_sys::Function) -> GameLoop {
+        GameLoop {
+            inner: Rc::new(RefCell::new(LoopState {
+                universe,
+                render,
+                running: false,
+                last_timestamp: None,
+                handle: None,
+                frame: None,
+            })),
+        }
+    }
+
+    pub fn start(&mut self) -> Result<(), JsValue> {
+        if self.inner.borrow().running {
+            return Ok(());
+        }
+
+        if self.inner.borrow().frame.is_none() {
+            let weak = Rc::downgrade(&self.inner);
+            let frame = Closure::wrap(Box::new(move |timestamp: f64| GameLoop::frame(&weak, timestamp))
+                as Box<dyn FnMut(f64)>);
+            self.inner.borrow_mut().frame = Some(frame);
+        }
+
+        {
+            let mut state = self.inner.borrow_mut();
+            state.running = true;
+            state.last_timestamp = None;
+        }
+        GameLoop::schedule(&self.inner)
+    }
+
+    pub fn stop(&mut self) {
+        let mut state = self.inner.borrow_mut();
+        state.running = false;
+        if let (Some(handle), Some(window)) = (state.handle.take(), web_sys::window()) {
+            let _ = window.cancel_animation_frame(handle);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.inner.borrow().running
+    }
+
+    pub fn on_click(&self, direction: DirectionName) {
+        self.inner.borrow_mut().universe.on_click(direction);
+    }
+
+    pub fn pause(&self) {
+        self.inner.borrow_mut().universe.pause();
+    }
+
+    pub fn resume(&self) {
+        self.inner.borrow_mut().universe.resume();
+    }
+
+    pub fn reset(&self) {
+        self.inner.borrow_mut().universe.reset();
+    }
+
+    pub fn state(&self) -> GameState {
+        self.inner.borrow().universe.state()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.inner.borrow().universe.score()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.inner.borrow().universe.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.inner.borrow().universe.height()
+    }
+
+    pub fn cells(&self) -> *const Cell {
+        self.inner.borrow().universe.cells()
+    }
+}
+
+impl GameLoop {
+    pub fn universe(&self) -> std::cell::Ref<'_, Universe> {
+        std::cell::Ref::map(self.inner.borrow(), |state| &state.universe)
+    }
+
+    pub fn universe_mut(&self) -> std::cell::RefMut<'_, Universe> {
+        std::cell::RefMut::map(self.inner.borrow_mut(), |state| &mut state.universe)
+    }
+
+    fn schedule(inner: &Rc<RefCell<LoopState>>) -> Result<(), JsValue> {
+        let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window available"))?;
+        let handle = {
+            let state = inner.borrow();
+            let frame = state.frame.as_ref().expect("frame closure is created in start()");
+            window.request_animation_frame(frame.as_ref().unchecked_ref())?
+        };
+        inner.borrow_mut().handle = Some(handle);
+        Ok(())
+    }
+
+    // The RefCell borrow is released before `render` runs, so the callback
+    // is free to call back into this GameLoop.
+    fn frame(weak: &Weak<RefCell<LoopState>>, timestamp: f64) {
+        let inner = match weak.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let (steps, render) = {
+            let mut state = inner.borrow_mut();
+            if !state.running {
+                return;
+            }
+            let elapsed = state.last_timestamp.map_or(0.0, |last| timestamp - last);
+            state.last_timestamp = Some(timestamp);
+            (state.universe.advance(elapsed), state.render.clone())
+        };
+
+        let started = Instant::now();
+        let _ = render.call1(&JsValue::NULL, &JsValue::from(steps));
+        let render_ms = started.elapsed().as_secs_f64() * 1000.0;
+        inner.borrow_mut().universe.record_render_ms(render_ms);
+
+        if inner.borrow().running {
+            let _ = GameLoop::schedule(&inner);
+        }
+    }
+}
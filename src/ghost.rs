@@ -0,0 +1,138 @@
+use crate::replay::Replay;
+use crate::{Cell, Universe};
+use std::mem;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// A recorded run raced alongside the live game. It plays in a universe of
+// its own and only shows up in the live cells as `Cell::Ghost` between
+// ticks, so it never blocks, eats or collides with anything.
+pub(crate) struct Ghost {
+    replay: Replay,
+    universe: Universe,
+    painted: Vec<usize>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // The ghost restarts along with the live game on `reset` and vanishes
+    // once its recording runs out.
+    pub fn set_ghost(&mut self, mut replay: Replay) -> Result<(), String> {
+        let universe = replay.restart()?;
+        if (universe.width, universe.height) != (self.width, self.height) {
+            return Err(format!(
+                "ghost was recorded on a {}x{} board, this one is {}x{}",
+                universe.width, universe.height, self.width, self.height
+            ));
+        }
+
+        self.clear_ghost();
+        self.ghost = Some(Box::new(Ghost {
+            replay,
+            universe,
+            painted: Vec::new(),
+        }));
+        self.paint_ghost();
+        Ok(())
+    }
+
+    pub fn clear_ghost(&mut self) {
+        self.erase_ghost();
+        self.ghost = None;
+    }
+
+    pub fn has_ghost(&self) -> bool {
+        self.ghost.is_some()
+    }
+}
+
+impl Universe {
+    pub(crate) fn erase_ghost(&mut self) {
+        let painted = match &mut self.ghost {
+            Some(ghost) => mem::take(&mut ghost.painted),
+            None => return,
+        };
+
+        for idx in painted {
+            if idx < self.cells.len() && self.cells.get(idx) == Cell::Ghost {
+                self.set_cell(idx, Cell::Empty);
+            }
+        }
+    }
+
+    pub(crate) fn step_ghost(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let running = match &mut self.ghost {
+            Some(ghost) => {
+                (ghost.universe.width, ghost.universe.height) == (width, height)
+                    && ghost.replay.step(&mut ghost.universe)
+            }
+            None => return,
+        };
+
+        if running {
+            self.paint_ghost();
+        } else {
+            self.clear_ghost();
+        }
+    }
+
+    pub(crate) fn restart_ghost(&mut self) {
+        if let Some(mut ghost) = self.ghost.take() {
+            if let Ok(universe) = ghost.replay.restart() {
+                ghost.universe = universe;
+                ghost.painted.clear();
+                self.ghost = Some(ghost);
+                self.paint_ghost();
+            }
+        }
+    }
+
+    // Brings the ghost to as far into its run as the live game is, after the
+    // live game jumped to a saved state.
+    pub(crate) fn seek_ghost(&mut self) {
+        let ticks = self.stats.ticks;
+        let caught_up = match &mut self.ghost {
+            Some(ghost) => {
+                ghost.painted.clear();
+                match ghost.replay.restart() {
+                    Ok(universe) => {
+                        ghost.universe = universe;
+                        (0..ticks).all(|_| ghost.replay.step(&mut ghost.universe))
+                    }
+                    Err(_) => false,
+                }
+            }
+            None => return,
+        };
+
+        if caught_up {
+            self.paint_ghost();
+        } else {
+            self.clear_ghost();
+        }
+    }
+
+    // The ghost only takes cells that are empty on the live board.
+    fn paint_ghost(&mut self) {
+        let body: Vec<usize> = match &self.ghost {
+            Some(ghost) if (ghost.universe.width, ghost.universe.height) == (self.width, self.height) => ghost
+                .universe
+                .players[0]
+                .snake
+                .body
+                .iter()
+                .map(|p| self.get_index(p.y, p.x))
+                .filter(|&idx| idx < self.cells.len() && self.cells.get(idx) == Cell::Empty)
+                .collect(),
+            _ => return,
+        };
+
+        for &idx in &body {
+            self.set_cell(idx, Cell::Ghost);
+        }
+        if let Some(ghost) = &mut self.ghost {
+            ghost.painted = body;
+        }
+    }
+}
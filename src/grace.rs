@@ -0,0 +1,62 @@
+use crate::sounds::Sound;
+use crate::{GameState, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Ticks after spawning during which a snake that would crash waits in place
+// instead, on boards with solid edges.
+pub const SPAWN_GRACE_TICKS: u32 = 3;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn countdown_seconds(&self) -> u32 {
+        self.countdown_seconds
+    }
+
+    // From the next `reset` on, games open with a countdown of `seconds`
+    // in `GameState::Countdown`: ticks beep and count it down in game time
+    // but nothing moves. 0 starts games right away; `start` skips it.
+    pub fn set_countdown_seconds(&mut self, seconds: u32) {
+        self.countdown_seconds = seconds;
+    }
+
+    // Whole seconds left to show, 3-2-1; 0 once the game is under way.
+    pub fn countdown_left(&self) -> u32 {
+        if self.state == GameState::Countdown {
+            (self.countdown_ms / 1000.0).ceil() as u32
+        } else {
+            0
+        }
+    }
+
+    // Whether a crash would stop `player_id` rather than kill it this tick.
+    pub fn in_grace(&self, player_id: u32) -> bool {
+        let solid_edges = !self.topology.wraps_x() || !self.topology.wraps_y();
+        let player = self.players.get(player_id as usize);
+        solid_edges && player.is_some_and(|player| self.stats.ticks < player.spawned_at + SPAWN_GRACE_TICKS)
+    }
+}
+
+impl Universe {
+    pub(crate) fn begin_countdown(&mut self) {
+        if self.countdown_seconds == 0 {
+            return;
+        }
+
+        self.state = GameState::Countdown;
+        self.countdown_ms = self.countdown_seconds as f64 * 1000.0;
+        self.cue(Sound::Countdown, 0, 1.0, self.countdown_seconds);
+    }
+
+    // A tick spent counting down rather than playing.
+    pub(crate) fn count_down(&mut self, tick_ms: f64) {
+        let before = self.countdown_left();
+        self.countdown_ms = (self.countdown_ms - tick_ms).max(0.0);
+        let after = self.countdown_left();
+        if after == 0 {
+            self.state = GameState::Running;
+        } else if after < before {
+            self.cue(Sound::Countdown, 0, 1.0, after);
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use crate::snapshot::Variants;
+use crate::Cell;
+use std::cell::RefCell;
+
+// `Cell` has twelve variants, so each takes half a byte.
+const BITS: usize = 4;
+const PER_WORD: usize = 64 / BITS;
+const MASK: u64 = (1 << BITS) - 1;
+// Cells are stored in CHUNK x CHUNK tiles.
+pub const CHUNK: u32 = 16;
+const CHUNK_WORDS: usize = (CHUNK * CHUNK) as usize / PER_WORD;
+
+// The board's cells packed sixteen to a word, half the memory of a byte each.
+// They are kept in square tiles that are only allocated once something other
+// than `Cell::Empty` is written to them, so a huge board that is mostly open
+// space costs little and cells near each other share cache lines.
+pub(crate) struct CellGrid {
+    width: u32,
+    len: usize,
+    chunks_across: u32,
+    chunks: Vec<Option<Box<[u64; CHUNK_WORDS]>>>,
+    // A byte-per-cell copy for `as_ptr`, only built once JS asks for it and
+    // written through by `set` from then on. Headless games never pay for it.
+    expanded: RefCell<Vec<Cell>>,
+}
+
+impl CellGrid {
+    pub fn new(width: u32, height: u32) -> CellGrid {
+        let chunks_across = width.div_ceil(CHUNK);
+        let chunks_down = height.div_ceil(CHUNK);
+        CellGrid {
+            width,
+            len: (width * height) as usize,
+            chunks_across,
+            chunks: (0..chunks_across * chunks_down).map(|_| None).collect(),
+            expanded: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // The tile `idx` lies in, and the word and bit offset inside it.
+    fn locate(&self, idx: usize) -> (usize, usize, usize) {
+        assert!(idx < self.len, "cell {} out of {}", idx, self.len);
+        let (x, y) = (idx as u32 % self.width, idx as u32 / self.width);
+        let chunk = (y / CHUNK * self.chunks_across + x / CHUNK) as usize;
+        let inner = ((y % CHUNK) * CHUNK + x % CHUNK) as usize;
+        (chunk, inner / PER_WORD, inner % PER_WORD * BITS)
+    }
+
+    pub fn get(&self, idx: usize) -> Cell {
+        let (chunk, word, shift) = self.locate(idx);
+        match &self.chunks[chunk] {
+            Some(words) => Cell::VARIANTS[((words[word] >> shift) & MASK) as usize],
+            None => Cell::Empty,
+        }
+    }
+
+    pub fn set(&mut self, idx: usize, cell: Cell) {
+        let (chunk, word, shift) = self.locate(idx);
+        let words = match &mut self.chunks[chunk] {
+            Some(words) => words,
+            None if cell == Cell::Empty => return,
+            chunk => chunk.insert(Box::new([0; CHUNK_WORDS])),
+        };
+        words[word] = (words[word] & !(MASK << shift)) | ((cell as u64) << shift);
+        if let Some(byte) = self.expanded.get_mut().get_mut(idx) {
+            *byte = cell;
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Cell> + '_ {
+        (0..self.len).map(move |idx| self.get(idx))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.iter().map(|cell| cell as u8).collect()
+    }
+
+    // One `Cell` a byte, the layout JS reads out of wasm memory. The pointer
+    // stays put and its cells current while the board keeps its size.
+    pub fn as_ptr(&self) -> *const Cell {
+        let mut expanded = self.expanded.borrow_mut();
+        if expanded.is_empty() {
+            expanded.extend(self.iter());
+        }
+        expanded.as_ptr()
+    }
+}
@@ -0,0 +1,66 @@
+use crate::sounds::Sound;
+use crate::Universe;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const EAT_VIBRATION_MS: u32 = 20;
+pub const DIE_VIBRATION_MS: u32 = 200;
+
+// How long the device vibrates for each sound cue of the local player,
+// indexed by `Sound as usize`; 0 stays still. Only web builds vibrate, through
+// `navigator.vibrate`, which does nothing on devices without a motor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Haptics {
+    enabled: bool,
+    durations: [u32; 5],
+}
+
+impl Default for Haptics {
+    fn default() -> Haptics {
+        let mut durations = [0; 5];
+        durations[Sound::Eat as usize] = EAT_VIBRATION_MS;
+        durations[Sound::Die as usize] = DIE_VIBRATION_MS;
+        Haptics {
+            enabled: true,
+            durations,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn haptics(&self) -> bool {
+        self.haptics.enabled
+    }
+
+    pub fn set_haptics(&mut self, enabled: bool) {
+        self.haptics.enabled = enabled;
+    }
+
+    pub fn vibration_ms(&self, sound: Sound) -> u32 {
+        self.haptics.durations[sound as usize]
+    }
+
+    pub fn set_vibration_ms(&mut self, sound: Sound, ms: u32) {
+        self.haptics.durations[sound as usize] = ms;
+    }
+}
+
+impl Universe {
+    pub(crate) fn buzz(&self, sound: Sound, player: usize) {
+        let ms = self.vibration_ms(sound);
+        if self.haptics.enabled && player == 0 && ms > 0 {
+            vibrate(ms);
+        }
+    }
+}
+
+#[cfg(feature = "web")]
+fn vibrate(ms: u32) {
+    if let Some(window) = web_sys::window() {
+        window.navigator().vibrate_with_duration(ms);
+    }
+}
+
+#[cfg(not(feature = "web"))]
+fn vibrate(_ms: u32) {}
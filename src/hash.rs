@@ -0,0 +1,109 @@
+use crate::{Cell, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+const FNV_OFFSET: u64 = 0xCBF2_9CE4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01B3;
+
+// 64-bit FNV-1a over little-endian values, so wasm and native builds agree.
+struct Fnv(u64);
+
+impl Fnv {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u64).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+pub(crate) fn checksum(bytes: &[u8]) -> u64 {
+    let mut h = Fnv(FNV_OFFSET);
+    h.write(bytes);
+    h.0
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Fingerprint of everything that decides how the game plays on: board,
+    // snakes, food timers, effects, RNG and score. Two simulations with the
+    // same hash are in sync; the ghost overlay and wall-clock time are left out.
+    pub fn state_hash(&self) -> u64 {
+        let mut h = Fnv(FNV_OFFSET);
+        h.write_u32(self.width);
+        h.write_u32(self.height);
+        for cell in self.cells.iter() {
+            h.write_u8(if cell == Cell::Ghost { Cell::Empty } else { cell } as u8);
+        }
+        for (&idx, &terrain) in &self.terrain {
+            h.write_u32(idx);
+            h.write_u8(terrain as u8);
+        }
+
+        for player in &self.players {
+            h.write_u8(player.alive as u8);
+            h.write_u32(player.score);
+            h.write_u32(player.hunger_ticks);
+            h.write_u32(player.respawn_in);
+            h.write_u32(player.combo.chain);
+            h.write_u32(player.combo.multiplier);
+            h.write_u32(player.combo.idle_ticks);
+            h.write_u32(player.lives);
+            h.write_u32(player.spawned_at);
+            h.write_u8(player.mired as u8);
+            h.write_u32(player.snake.body.len() as u32);
+            for p in &player.snake.body {
+                h.write_u32(p.x);
+                h.write_u32(p.y);
+            }
+            h.write_u32(player.snake.pending.len() as u32);
+            for direction in Some(&player.snake.direction).into_iter().chain(&player.snake.pending) {
+                h.write_i32(direction.vx);
+                h.write_i32(direction.vy);
+            }
+        }
+
+        h.write_u32(self.foods.len() as u32);
+        for food in &self.foods {
+            h.write_u32(food.ticks_left.unwrap_or(u32::MAX));
+        }
+        for effect in self.power_ups.effects() {
+            h.write_u8(effect.kind() as u8);
+            h.write_u32(effect.ticks_left());
+        }
+        for hazard in &self.hazards {
+            h.write_u8(hazard.heading as u8);
+            h.write_u32(hazard.wait);
+        }
+        h.write_u32(self.shrink.countdown);
+        h.write_u32(self.shrink.rings);
+        h.write_u64(self.time_left_ms.to_bits());
+        h.write_u64(self.countdown_ms.to_bits());
+        h.write_u32(self.speed_boost);
+        h.write_u32(self.overlaps);
+        h.write_u8(self.state as u8);
+
+        for word in self.rng.state().iter() {
+            h.write_u64(*word);
+        }
+        h.write_u32(self.stats.score);
+        h.write_u32(self.stats.apples_eaten);
+        h.write_u32(self.stats.ticks);
+        h.0
+    }
+}
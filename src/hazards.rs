@@ -0,0 +1,152 @@
+use crate::ai::DIRECTIONS;
+use crate::{Cell, Direction, DirectionName, GameState, Position, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// A patrol walks straight and turns back when blocked; a rogue wanders in a
+// random direction on every move.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum HazardKind {
+    Patrol,
+    Rogue,
+}
+
+// A moving block that kills any snake running into it. It moves once every
+// `period` ticks, before the snakes do, and only onto empty cells, so it
+// never eats food or pushes into a snake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Hazard {
+    pub(crate) kind: HazardKind,
+    pub(crate) start: Position,
+    pub(crate) start_heading: DirectionName,
+    pub(crate) position: Position,
+    pub(crate) heading: DirectionName,
+    pub(crate) period: u32,
+    pub(crate) wait: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Returns the hazard's id. Hazards go back to where they started on `reset`.
+    pub fn add_hazard(
+        &mut self,
+        kind: HazardKind,
+        x: u32,
+        y: u32,
+        heading: DirectionName,
+        period: u32,
+    ) -> Result<u32, String> {
+        if x >= self.width || y >= self.height {
+            return Err(format!("hazard ({}, {}) lies outside the {}x{} universe", x, y, self.width, self.height));
+        }
+
+        let position = Position { x, y };
+        let idx = self.get_index(y, x);
+        let spawn = self.players.iter().any(|player| player.spawn.body.contains(&position));
+        let food = self.foods.iter().any(|food| food.position == position);
+        if spawn || (self.cells.get(idx) != Cell::Empty && !food) {
+            return Err(format!("hazard ({}, {}) is already taken", x, y));
+        }
+
+        self.set_cell(idx, Cell::Hazard);
+        self.cover_food(idx);
+        let period = period.max(1);
+        self.hazards.push(Hazard {
+            kind,
+            start: position,
+            start_heading: heading,
+            position,
+            heading,
+            period,
+            wait: period,
+        });
+        Ok(self.hazards.len() as u32 - 1)
+    }
+
+    pub fn clear_hazards(&mut self) {
+        for hazard in std::mem::take(&mut self.hazards) {
+            let idx = self.get_index(hazard.position.y, hazard.position.x);
+            self.set_cell(idx, Cell::Empty);
+        }
+
+        if self.state == GameState::Running {
+            self.spawn_food();
+        }
+    }
+
+    pub fn hazard_count(&self) -> u32 {
+        self.hazards.len() as u32
+    }
+
+    // Flat `[x, y, x, y, ...]` positions, in hazard id order.
+    pub fn hazard_positions(&self) -> Vec<u32> {
+        self.hazards.iter().flat_map(|hazard| [hazard.position.x, hazard.position.y]).collect()
+    }
+}
+
+impl Universe {
+    pub(crate) fn move_hazards(&mut self) {
+        for id in 0..self.hazards.len() {
+            let hazard = &mut self.hazards[id];
+            hazard.wait -= 1;
+            if hazard.wait > 0 {
+                continue;
+            }
+            hazard.wait = hazard.period;
+
+            let options = match hazard.kind {
+                HazardKind::Patrol => vec![hazard.heading, Direction::from_name(hazard.heading).opposite()],
+                HazardKind::Rogue => {
+                    let first = self.rng.below(DIRECTIONS.len() as u32) as usize;
+                    (0..DIRECTIONS.len()).map(|i| DIRECTIONS[(first + i) % DIRECTIONS.len()]).collect()
+                }
+            };
+
+            let from = self.hazards[id].position;
+            let step = options
+                .into_iter()
+                .find_map(|heading| self.hazard_step(&from, heading).map(|to| (heading, to)));
+            if let Some((heading, to)) = step {
+                let from_idx = self.get_index(from.y, from.x);
+                let background = self.background(from_idx);
+                self.set_cell(from_idx, background);
+                let to_idx = self.get_index(to.y, to.x);
+                self.set_cell(to_idx, Cell::Hazard);
+
+                let hazard = &mut self.hazards[id];
+                hazard.position = to;
+                hazard.heading = heading;
+            }
+        }
+    }
+
+    // Puts every hazard back at its start; the board is expected to have
+    // been cleared around the snakes' spawns.
+    pub(crate) fn restart_hazards(&mut self) {
+        for id in 0..self.hazards.len() {
+            let hazard = &mut self.hazards[id];
+            hazard.position = hazard.start;
+            hazard.heading = hazard.start_heading;
+            hazard.wait = hazard.period;
+
+            let start = hazard.start;
+            let idx = self.get_index(start.y, start.x);
+            self.set_cell(idx, Cell::Hazard);
+        }
+    }
+
+    fn hazard_step(&self, from: &Position, heading: DirectionName) -> Option<Position> {
+        let to = self.step_from(from, Direction::from_name(heading), false).ok()?;
+        let idx = self.get_index(to.y, to.x);
+        if self.cells.get(idx) == Cell::Empty {
+            Some(to)
+        } else {
+            None
+        }
+    }
+}
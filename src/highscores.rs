@@ -0,0 +1,143 @@
+#[cfg(feature = "storage")]
+use crate::storage;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const DEFAULT_HIGH_SCORES: u32 = 10;
+#[cfg(feature = "storage")]
+const STORAGE_KEY: &str = "rust-snake-wasm/high-scores";
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HighScore {
+    score: u32,
+    // Milliseconds since the Unix epoch, as `Date.now()` gives them.
+    timestamp_ms: f64,
+    mode: String,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl HighScore {
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn timestamp_ms(&self) -> f64 {
+        self.timestamp_ms
+    }
+
+    pub fn mode(&self) -> String {
+        self.mode.clone()
+    }
+}
+
+// The best `capacity` scores, highest first; among equal scores the older
+// one ranks higher. Each entry is tagged with the mode it was played in,
+// e.g. `Universe::mode_tag`, so one table can serve several modes.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HighScores {
+    capacity: u32,
+    entries: Vec<HighScore>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl HighScores {
+    pub fn new(capacity: u32) -> HighScores {
+        HighScores {
+            capacity,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    // Whether `score` would make it into the table.
+    pub fn is_high_score(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < self.capacity as usize
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+    // Adds `score` as of now and returns its rank from 0, or None when it
+    // didn't make the table.
+    pub fn submit(&mut self, score: u32, mode: &str) -> Option<u32> {
+        self.submit_at(score, mode, now_ms())
+    }
+
+    pub fn list(&self) -> Vec<HighScore> {
+        self.entries.clone()
+    }
+
+    // Entries for one mode only, in rank order.
+    pub fn list_mode(&self, mode: &str) -> Vec<HighScore> {
+        self.entries.iter().filter(|entry| entry.mode == mode).cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+impl HighScores {
+    pub fn submit_at(&mut self, score: u32, mode: &str, timestamp_ms: f64) -> Option<u32> {
+        if !self.is_high_score(score) {
+            return None;
+        }
+
+        let rank = self.entries.iter().take_while(|entry| entry.score >= score).count();
+        self.entries.insert(
+            rank,
+            HighScore {
+                score,
+                timestamp_ms,
+                mode: mode.to_string(),
+            },
+        );
+        self.entries.truncate(self.capacity as usize);
+        Some(rank as u32)
+    }
+}
+
+impl Default for HighScores {
+    fn default() -> HighScores {
+        HighScores::new(DEFAULT_HIGH_SCORES)
+    }
+}
+
+#[cfg(feature = "storage")]
+#[wasm_bindgen]
+impl HighScores {
+    // The saved table, or an empty one of `capacity` when there is none or
+    // it can't be read anymore. A saved table keeps its own capacity.
+    pub fn load(capacity: u32) -> HighScores {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_else(|| HighScores::new(capacity))
+    }
+
+    pub fn save(&self) -> Result<(), JsValue> {
+        let json = serde_json::to_string(self).expect("high scores serialize to JSON");
+        storage::save(STORAGE_KEY, &json)
+    }
+}
+
+#[cfg(feature = "wasm")]
+fn now_ms() -> f64 {
+    js_sys::Date::now()
+}
+
+#[cfg(not(feature = "wasm"))]
+fn now_ms() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64() * 1000.0)
+}
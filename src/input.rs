@@ -0,0 +1,430 @@
+use crate::{DirectionName, GameState, Universe};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::rc::Rc;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "web")]
+use wasm_bindgen::JsCast;
+
+const INPUT_BUFFER_LEN: usize = 8;
+
+pub const DEFAULT_SWIPE_DEAD_ZONE: f64 = 24.0;
+pub const GAMEPAD_DEAD_ZONE: f64 = 0.5;
+
+// Buttons 12-15 are the d-pad in the standard gamepad mapping.
+#[cfg(feature = "web")]
+const DPAD_BUTTONS: [(u32, DirectionName); 4] = [
+    (12, DirectionName::Up),
+    (13, DirectionName::Down),
+    (14, DirectionName::Left),
+    (15, DirectionName::Right),
+];
+
+// Directions collected by event listeners between ticks.
+#[derive(Clone, Default)]
+pub(crate) struct InputBuffer(Rc<RefCell<VecDeque<DirectionName>>>);
+
+impl InputBuffer {
+    pub(crate) fn push(&self, direction: DirectionName) {
+        let mut queue = self.0.borrow_mut();
+        if queue.len() < INPUT_BUFFER_LEN {
+            queue.push_back(direction);
+        }
+    }
+
+    pub(crate) fn drain(&self) -> Vec<DirectionName> {
+        self.0.borrow_mut().drain(..).collect()
+    }
+}
+
+// Arrow keys, WASD and vim's hjkl, matched on `KeyboardEvent.key`.
+pub fn direction_for_key(key: &str) -> Option<DirectionName> {
+    match key {
+        "ArrowUp" | "w" | "W" | "k" => Some(DirectionName::Up),
+        "ArrowDown" | "s" | "S" | "j" => Some(DirectionName::Down),
+        "ArrowLeft" | "a" | "A" | "h" => Some(DirectionName::Left),
+        "ArrowRight" | "d" | "D" | "l" => Some(DirectionName::Right),
+        _ => None,
+    }
+}
+
+// Stick axes run from -1 to 1 with y pointing down; a push has to clear the
+// dead zone so a resting stick does not drift the snake.
+pub fn direction_for_stick(x: f64, y: f64) -> Option<DirectionName> {
+    dominant_direction(x, y, GAMEPAD_DEAD_ZONE)
+}
+
+fn dominant_direction(dx: f64, dy: f64, dead_zone: f64) -> Option<DirectionName> {
+    if dx.abs().max(dy.abs()) < dead_zone {
+        None
+    } else if dx.abs() > dy.abs() {
+        Some(if dx > 0.0 { DirectionName::Right } else { DirectionName::Left })
+    } else {
+        Some(if dy > 0.0 { DirectionName::Down } else { DirectionName::Up })
+    }
+}
+
+// === Key bindings ===
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Restart,
+    ToggleTopology,
+}
+
+impl KeyAction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyAction::Up => "up",
+            KeyAction::Down => "down",
+            KeyAction::Left => "left",
+            KeyAction::Right => "right",
+            KeyAction::Pause => "pause",
+            KeyAction::Restart => "restart",
+            KeyAction::ToggleTopology => "toggle_topology",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<KeyAction> {
+        match name {
+            "up" => Some(KeyAction::Up),
+            "down" => Some(KeyAction::Down),
+            "left" => Some(KeyAction::Left),
+            "right" => Some(KeyAction::Right),
+            "pause" => Some(KeyAction::Pause),
+            "restart" => Some(KeyAction::Restart),
+            "toggle_topology" => Some(KeyAction::ToggleTopology),
+            _ => None,
+        }
+    }
+}
+
+// Key codes (`KeyboardEvent.keyCode`) to actions. The JSON form is a flat
+// object, e.g. {"38":"up","80":"pause"}.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: BTreeMap<u32, KeyAction>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl KeyMap {
+    pub fn new() -> KeyMap {
+        KeyMap {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    pub fn bind(&mut self, code: u32, action: KeyAction) {
+        self.bindings.insert(code, action);
+    }
+
+    pub fn unbind(&mut self, code: u32) {
+        self.bindings.remove(&code);
+    }
+
+    pub fn action(&self, code: u32) -> Option<KeyAction> {
+        self.bindings.get(&code).copied()
+    }
+
+    pub fn to_json(&self) -> String {
+        let entries = self
+            .bindings
+            .iter()
+            .map(|(code, action)| format!("\"{}\":\"{}\"", code, action.name()))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", entries.join(","))
+    }
+
+    pub fn from_json(json: &str) -> Result<KeyMap, String> {
+        let body = json
+            .trim()
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+            .ok_or_else(|| "key map must be a JSON object".to_string())?;
+
+        let mut map = KeyMap::new();
+        for entry in body.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (code, action) = entry
+                .split_once(':')
+                .ok_or_else(|| format!("malformed key binding '{}'", entry))?;
+            let code = unquote(code)?
+                .parse::<u32>()
+                .map_err(|_| format!("invalid key code in '{}'", entry))?;
+            let action = unquote(action)?;
+            let action = KeyAction::from_name(action).ok_or_else(|| format!("unknown action '{}'", action))?;
+            map.bind(code, action);
+        }
+        Ok(map)
+    }
+}
+
+fn unquote(value: &str) -> Result<&str, String> {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a JSON string, found '{}'", value))
+}
+
+// Arrows, WASD and hjkl steer; P and Space pause, R restarts, T toggles walls.
+impl Default for KeyMap {
+    fn default() -> KeyMap {
+        let mut map = KeyMap::new();
+        let bindings = [
+            ([38, 87, 75], KeyAction::Up),
+            ([40, 83, 74], KeyAction::Down),
+            ([37, 65, 72], KeyAction::Left),
+            ([39, 68, 76], KeyAction::Right),
+        ];
+        for (codes, action) in bindings.iter() {
+            for &code in codes {
+                map.bind(code, *action);
+            }
+        }
+        map.bind(80, KeyAction::Pause);
+        map.bind(32, KeyAction::Pause);
+        map.bind(82, KeyAction::Restart);
+        map.bind(84, KeyAction::ToggleTopology);
+        map
+    }
+}
+
+// === Touch ===
+// Turns a swipe into a direction along its dominant axis. Swipes shorter than
+// the dead zone (in the same units as the coordinates) are treated as taps.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchController {
+    dead_zone: f64,
+    start: Option<(f64, f64)>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TouchController {
+    pub fn new(dead_zone: f64) -> TouchController {
+        TouchController {
+            dead_zone: dead_zone.max(0.0),
+            start: None,
+        }
+    }
+
+    pub fn dead_zone(&self) -> f64 {
+        self.dead_zone
+    }
+
+    pub fn set_dead_zone(&mut self, dead_zone: f64) {
+        self.dead_zone = dead_zone.max(0.0);
+    }
+
+    pub fn touch_start(&mut self, x: f64, y: f64) {
+        self.start = Some((x, y));
+    }
+
+    pub fn touch_end(&mut self, x: f64, y: f64) -> Option<DirectionName> {
+        let (x0, y0) = self.start.take()?;
+        self.swipe(x0, y0, x, y)
+    }
+
+    pub fn swipe(&self, x0: f64, y0: f64, x1: f64, y1: f64) -> Option<DirectionName> {
+        dominant_direction(x1 - x0, y1 - y0, self.dead_zone)
+    }
+}
+
+impl Default for TouchController {
+    fn default() -> TouchController {
+        TouchController::new(DEFAULT_SWIPE_DEAD_ZONE)
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Buffers the direction bound to `key`; it is applied on the next tick.
+    pub fn press_key(&mut self, key: &str) -> bool {
+        match direction_for_key(key) {
+            Some(direction) => {
+                self.input.push(direction);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn key_map(&self) -> KeyMap {
+        self.key_map.clone()
+    }
+
+    pub fn set_key_map(&mut self, key_map: KeyMap) {
+        self.key_map = key_map;
+    }
+
+    // Directions go through the input buffer like any other key press; the
+    // remaining actions take effect immediately.
+    pub fn handle_key(&mut self, code: u32) -> bool {
+        let action = match self.key_map.action(code) {
+            Some(action) => action,
+            None => return false,
+        };
+
+        match action {
+            KeyAction::Up => self.input.push(DirectionName::Up),
+            KeyAction::Down => self.input.push(DirectionName::Down),
+            KeyAction::Left => self.input.push(DirectionName::Left),
+            KeyAction::Right => self.input.push(DirectionName::Right),
+            KeyAction::Pause if self.state == GameState::Paused => self.resume(),
+            KeyAction::Pause => self.pause(),
+            KeyAction::Restart => self.reset(),
+            KeyAction::ToggleTopology => self.toggle_topology(),
+        }
+        true
+    }
+
+    pub fn feed_touch(&mut self, x0: f64, y0: f64, x1: f64, y1: f64) -> bool {
+        match self.touch.swipe(x0, y0, x1, y1) {
+            Some(direction) => {
+                self.input.push(direction);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_swipe_dead_zone(&mut self, dead_zone: f64) {
+        self.touch.set_dead_zone(dead_zone);
+    }
+}
+
+// === Browser listeners ===
+#[cfg(feature = "web")]
+pub(crate) struct Listener {
+    target: web_sys::EventTarget,
+    event: &'static str,
+    callback: Closure<dyn FnMut(web_sys::Event)>,
+}
+
+#[cfg(feature = "web")]
+impl Listener {
+    fn attach(
+        target: web_sys::EventTarget,
+        event: &'static str,
+        callback: impl FnMut(web_sys::Event) + 'static,
+    ) -> Result<Listener, JsValue> {
+        let callback = Closure::wrap(Box::new(callback) as Box<dyn FnMut(web_sys::Event)>);
+        target.add_event_listener_with_callback(event, callback.as_ref().unchecked_ref())?;
+        Ok(Listener { target, event, callback })
+    }
+}
+
+#[cfg(feature = "web")]
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self
+            .target
+            .remove_event_listener_with_callback(self.event, self.callback.as_ref().unchecked_ref());
+    }
+}
+
+#[cfg(feature = "web")]
+fn first_touch(event: &web_sys::Event) -> Option<(f64, f64)> {
+    let event = event.dyn_ref::<web_sys::TouchEvent>()?;
+    let touch = event.changed_touches().get(0)?;
+    Some((touch.client_x() as f64, touch.client_y() as f64))
+}
+
+#[cfg(feature = "web")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn attach_keyboard(&mut self, document: web_sys::Document) -> Result<(), JsValue> {
+        let buffer = self.input.clone();
+        let listener = Listener::attach(document.into(), "keydown", move |event| {
+            let key = match event.dyn_ref::<web_sys::KeyboardEvent>() {
+                Some(event) => event.key(),
+                None => return,
+            };
+            if let Some(direction) = direction_for_key(&key) {
+                event.prevent_default();
+                buffer.push(direction);
+            }
+        })?;
+
+        self.keyboard = Some(listener);
+        Ok(())
+    }
+
+    pub fn detach_keyboard(&mut self) {
+        self.keyboard = None;
+    }
+
+    // Both listeners share one controller so a swipe is measured from its own
+    // touchstart; the dead zone is captured when attaching.
+    pub fn attach_touch(&mut self, target: web_sys::EventTarget) -> Result<(), JsValue> {
+        let controller = Rc::new(RefCell::new(self.touch));
+
+        let start = controller.clone();
+        let on_start = Listener::attach(target.clone(), "touchstart", move |event| {
+            if let Some((x, y)) = first_touch(&event) {
+                start.borrow_mut().touch_start(x, y);
+            }
+        })?;
+
+        let buffer = self.input.clone();
+        let on_end = Listener::attach(target, "touchend", move |event| {
+            let swipe = first_touch(&event).and_then(|(x, y)| controller.borrow_mut().touch_end(x, y));
+            if let Some(direction) = swipe {
+                event.prevent_default();
+                buffer.push(direction);
+            }
+        })?;
+
+        self.touch_listeners = vec![on_start, on_end];
+        Ok(())
+    }
+
+    pub fn detach_touch(&mut self) {
+        self.touch_listeners.clear();
+    }
+
+    // Meant to be called once per frame. The d-pad wins over the left stick,
+    // and a direction is only buffered when it differs from the last poll.
+    pub fn poll_gamepad(&mut self) -> Result<bool, JsValue> {
+        let window = web_sys::window().ok_or("no window available")?;
+        let pads = window.navigator().get_gamepads()?;
+        let direction = pads
+            .iter()
+            .filter_map(|pad| pad.dyn_into::<web_sys::Gamepad>().ok())
+            .find(|pad| pad.connected())
+            .and_then(|pad| gamepad_direction(&pad));
+
+        let changed = direction.is_some() && direction != self.gamepad_direction;
+        self.gamepad_direction = direction;
+        if let (true, Some(direction)) = (changed, direction) {
+            self.input.push(direction);
+        }
+        Ok(changed)
+    }
+}
+
+#[cfg(feature = "web")]
+fn gamepad_direction(pad: &web_sys::Gamepad) -> Option<DirectionName> {
+    let buttons = pad.buttons();
+    let pressed = DPAD_BUTTONS.iter().find(|(button, _)| {
+        buttons
+            .get(*button)
+            .dyn_into::<web_sys::GamepadButton>()
+            .is_ok_and(|button| button.pressed())
+    });
+    if let Some(&(_, direction)) = pressed {
+        return Some(direction);
+    }
+
+    let axes = pad.axes();
+    let x = axes.get(0).as_f64().unwrap_or(0.0);
+    let y = axes.get(1).as_f64().unwrap_or(0.0);
+    direction_for_stick(x, y)
+}
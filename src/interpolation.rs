@@ -0,0 +1,67 @@
+use crate::{GameState, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Where a snake's segments were before the last tick and where they are now,
+// plus how far the game is into the next tick, so a renderer can draw each
+// segment `fraction` of the way from its previous cell to its current one.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Interpolation {
+    fraction: f64,
+    segments: Vec<u32>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Interpolation {
+    // From 0 right after a tick to 1 when the next one is due.
+    pub fn fraction(&self) -> f64 {
+        self.fraction
+    }
+
+    // Head first, as flat [prev_x, prev_y, x, y, ...] quadruples. A segment
+    // that just grew starts where it is. Segments that moved further than
+    // one cell wrapped around the board, went through a portal or respawned,
+    // and are better drawn where they are than slid across.
+    pub fn segments(&self) -> Vec<u32> {
+        self.segments.clone()
+    }
+
+    pub fn len(&self) -> u32 {
+        self.segments.len() as u32 / 4
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn interpolation_state(&self) -> Interpolation {
+        self.player_interpolation(0)
+    }
+
+    // Like `interpolation_state` for any player; no segments for an unknown id.
+    pub fn player_interpolation(&self, player_id: u32) -> Interpolation {
+        let fraction = if self.state == GameState::Running {
+            (self.accumulator_ms / self.recommended_frame_delay_ms()).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        let segments = self.players.get(player_id as usize).map_or_else(Vec::new, |player| {
+            player
+                .snake
+                .body
+                .iter()
+                .enumerate()
+                .flat_map(|(i, p)| {
+                    let from = player.previous.get(i).unwrap_or(p);
+                    [from.x, from.y, p.x, p.y]
+                })
+                .collect()
+        });
+
+        Interpolation { fraction, segments }
+    }
+}
@@ -0,0 +1,149 @@
+use crate::replay::Replay;
+use js_sys::{Array, Object, Promise, Reflect, Uint8Array};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::{CryptoKey, Headers, Request, RequestInit, Response};
+
+// How many entries `Leaderboard::top` asks for.
+pub const TOP_SCORES: u32 = 100;
+
+#[wasm_bindgen]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    name: String,
+    score: u32,
+    mode: String,
+    timestamp_ms: f64,
+}
+
+#[wasm_bindgen]
+impl LeaderboardEntry {
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn mode(&self) -> String {
+        self.mode.clone()
+    }
+
+    pub fn timestamp_ms(&self) -> f64 {
+        self.timestamp_ms
+    }
+}
+
+// A submission is POSTed to the endpoint as JSON with the replay in hex, so
+// the server can re-simulate the run from its seed and check the score
+// before listing it. With a signing key the body's HMAC-SHA256 goes along
+// in the `X-Signature` header, also in hex.
+#[derive(Serialize)]
+struct Submission<'a> {
+    name: &'a str,
+    score: u32,
+    mode: &'a str,
+    replay: String,
+}
+
+// Client for an online leaderboard. `top` GETs the endpoint with
+// `?limit=100` and expects a JSON array of entries, best first.
+#[wasm_bindgen]
+pub struct Leaderboard {
+    endpoint: String,
+    key: Option<Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl Leaderboard {
+    // Scores only go out over HTTPS, or plain HTTP to localhost for testing.
+    #[wasm_bindgen(constructor)]
+    pub fn new(endpoint: String) -> Result<Leaderboard, String> {
+        let local = ["http://localhost", "http://127.0.0.1"]
+            .iter()
+            .any(|prefix| endpoint.starts_with(prefix));
+        if !endpoint.starts_with("https://") && !local {
+            return Err(format!("leaderboard endpoint {} is not HTTPS", endpoint));
+        }
+
+        Ok(Leaderboard { endpoint, key: None })
+    }
+
+    pub fn set_signing_key(&mut self, key: Vec<u8>) {
+        self.key = Some(key);
+    }
+
+    // Resolves once the server has taken the score.
+    pub fn submit(&self, name: &str, score: u32, mode: &str, replay: &Replay) -> Promise {
+        let body = serde_json::to_string(&Submission {
+            name,
+            score,
+            mode,
+            replay: hex(&replay.to_bytes()),
+        })
+        .expect("submission serializes to JSON");
+        let endpoint = self.endpoint.clone();
+        let key = self.key.clone();
+
+        future_to_promise(async move {
+            let headers = Headers::new()?;
+            headers.set("Content-Type", "application/json")?;
+            if let Some(key) = key {
+                headers.set("X-Signature", &sign(&key, body.as_bytes()).await?)?;
+            }
+
+            let init = RequestInit::new();
+            init.set_method("POST");
+            init.set_headers(&headers);
+            init.set_body(&JsValue::from_str(&body));
+            fetch(&Request::new_with_str_and_init(&endpoint, &init)?).await?;
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    // Resolves to an array of `LeaderboardEntry`.
+    pub fn top(&self) -> Promise {
+        let separator = if self.endpoint.contains('?') { '&' } else { '?' };
+        let url = format!("{}{}limit={}", self.endpoint, separator, TOP_SCORES);
+
+        future_to_promise(async move {
+            let response = fetch(&Request::new_with_str(&url)?).await?;
+            let text = JsFuture::from(response.text()?).await?.as_string().unwrap_or_default();
+            let entries: Vec<LeaderboardEntry> =
+                serde_json::from_str(&text).map_err(|err| JsValue::from_str(&err.to_string()))?;
+            Ok(entries.into_iter().map(JsValue::from).collect::<Array>().into())
+        })
+    }
+}
+
+async fn fetch(request: &Request) -> Result<Response, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window available"))?;
+    let response: Response = JsFuture::from(window.fetch_with_request(request)).await?.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("leaderboard answered {}", response.status())));
+    }
+    Ok(response)
+}
+
+// HMAC-SHA256 through WebCrypto.
+async fn sign(key: &[u8], data: &[u8]) -> Result<String, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window available"))?;
+    let subtle = window.crypto()?.subtle();
+
+    let algorithm = Object::new();
+    Reflect::set(&algorithm, &"name".into(), &"HMAC".into())?;
+    Reflect::set(&algorithm, &"hash".into(), &"SHA-256".into())?;
+    let usages = Array::of1(&"sign".into());
+    let import = subtle.import_key_with_object("raw", &Uint8Array::from(key), &algorithm, false, &usages)?;
+    let key: CryptoKey = JsFuture::from(import).await?.dyn_into()?;
+
+    let signature = JsFuture::from(subtle.sign_with_str_and_u8_array("HMAC", &key, data)?).await?;
+    Ok(hex(&Uint8Array::new(&signature).to_vec()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,452 @@
+use crate::rng::Rng;
+use crate::{Direction, Position, Snake};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const SPAWN_LENGTH: u32 = 4;
+pub const MAX_DIFFICULTY: u32 = 10;
+// Maps bigger than this are rejected before they're ever laid out.
+pub const MAX_LEVEL_CELLS: u32 = 1 << 20;
+
+const GENERATED_SIZE: u32 = 32;
+const MIN_GENERATED_SIZE: u32 = 12;
+const GENERATOR_ATTEMPTS: u32 = 16;
+
+// A board layout: '#' wall, '.' empty, 'S' snake head, 'A' apple.
+// A decimal count before a symbol repeats it, so "3#2." reads as "###..".
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Level {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) walls: Vec<u32>,
+    pub(crate) spawn: Position,
+    pub(crate) apple: Option<Position>,
+}
+
+impl Level {
+    pub fn parse(map: &str) -> Result<Level, String> {
+        let mut width = None;
+        let mut height = 0;
+        let mut walls = Vec::new();
+        let mut spawn = None;
+        let mut apple = None;
+        let mut cells: u32 = 0;
+
+        for line in map.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let mut x = 0;
+            let mut count = String::new();
+
+            for symbol in line.chars() {
+                if symbol.is_ascii_digit() {
+                    count.push(symbol);
+                    continue;
+                }
+
+                let repeat = if count.is_empty() {
+                    1
+                } else {
+                    count.parse::<u32>().map_err(|_| format!("invalid run length on row {}", height))?
+                };
+                count.clear();
+                cells = cells
+                    .checked_add(repeat)
+                    .filter(|&cells| cells <= MAX_LEVEL_CELLS)
+                    .ok_or_else(|| format!("map is larger than {} cells", MAX_LEVEL_CELLS))?;
+
+                for _ in 0..repeat {
+                    let position = Position { x, y: height };
+                    match symbol {
+                        '#' => walls.push(position),
+                        '.' => {}
+                        'S' if spawn.is_none() => spawn = Some(position),
+                        'A' if apple.is_none() => apple = Some(position),
+                        'S' | 'A' => return Err(format!("duplicate '{}' at ({}, {})", symbol, x, height)),
+                        other => return Err(format!("unknown map symbol '{}' at ({}, {})", other, x, height)),
+                    }
+                    x += 1;
+                }
+            }
+
+            if !count.is_empty() {
+                return Err(format!("dangling run length on row {}", height));
+            }
+
+            match width {
+                None => width = Some(x),
+                Some(w) if w != x => return Err(format!("row {} is {} cells wide, expected {}", height, x, w)),
+                Some(_) => {}
+            }
+            height += 1;
+        }
+
+        let width = width.filter(|&width| width > 0).ok_or_else(|| "map is empty".to_string())?;
+        let spawn = spawn.ok_or_else(|| "map has no snake spawn 'S'".to_string())?;
+        let walls = walls
+            .iter()
+            .map(|p| p.y.checked_mul(width).and_then(|row| row.checked_add(p.x)))
+            .collect::<Option<_>>()
+            .ok_or_else(|| format!("map is larger than {} cells", MAX_LEVEL_CELLS))?;
+
+        Ok(Level {
+            width,
+            height,
+            walls,
+            spawn,
+            apple,
+        })
+    }
+
+    // What `parse` guarantees, checked again for levels that arrive some
+    // other way, like a snapshot. Returns the number of cells on the board.
+    pub(crate) fn validate(&self) -> Result<usize, String> {
+        let len = self.width.checked_mul(self.height).filter(|&len| len > 0);
+        let len = len.ok_or_else(|| format!("invalid level size {}x{}", self.width, self.height))?;
+
+        if let Some(idx) = self.walls.iter().find(|&&idx| idx >= len) {
+            return Err(format!("wall {} lies outside the {}x{} level", idx, self.width, self.height));
+        }
+        if let Some(apple) = self.apple.as_ref().filter(|p| p.x >= self.width || p.y >= self.height) {
+            return Err(format!("apple ({}, {}) lies outside the {}x{} level", apple.x, apple.y, self.width, self.height));
+        }
+
+        self.snake()?;
+        Ok(len as usize)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn to_map(&self) -> String {
+        let mut rows = vec![vec!['.'; self.width as usize]; self.height as usize];
+        for &idx in &self.walls {
+            rows[(idx / self.width) as usize][(idx % self.width) as usize] = '#';
+        }
+        rows[self.spawn.y as usize][self.spawn.x as usize] = 'S';
+        if let Some(apple) = &self.apple {
+            rows[apple.y as usize][apple.x as usize] = 'A';
+        }
+
+        rows.into_iter()
+            .map(|row| row.into_iter().collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // The spawn marks the head; the body trails behind it along the first
+    // heading that leaves room for a full-length snake.
+    pub(crate) fn snake(&self) -> Result<Snake, String> {
+        let headings = [
+            Direction { vx: 1, vy: 0 },
+            Direction { vx: -1, vy: 0 },
+            Direction { vx: 0, vy: 1 },
+            Direction { vx: 0, vy: -1 },
+        ];
+
+        headings
+            .iter()
+            .find(|direction| (0..SPAWN_LENGTH as i32).all(|i| self.is_open(&self.spawn, direction, -i)))
+            .map(|&direction| Snake::spawn(&self.spawn, direction, SPAWN_LENGTH))
+            .ok_or_else(|| format!("no room for a snake at ({}, {})", self.spawn.x, self.spawn.y))
+    }
+
+    fn is_open(&self, from: &Position, direction: &Direction, steps: i32) -> bool {
+        let x = from.x as i32 + direction.vx * steps;
+        let y = from.y as i32 + direction.vy * steps;
+
+        x >= 0
+            && y >= 0
+            && x < self.width as i32
+            && y < self.height as i32
+            && !self.walls.contains(&(y as u32 * self.width + x as u32))
+    }
+}
+
+// === Generator ===
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn generate_level_map(seed: u64, difficulty: u32) -> String {
+    generate_level(seed, difficulty).to_map()
+}
+
+pub fn generate_level(seed: u64, difficulty: u32) -> Level {
+    generate_level_with_size(seed, difficulty, GENERATED_SIZE, GENERATED_SIZE)
+}
+
+// Every open cell of the result is reachable from the spawn: pockets cut off
+// by the layout are sealed, and layouts that end up too cramped are retried.
+pub fn generate_level_with_size(seed: u64, difficulty: u32, width: u32, height: u32) -> Level {
+    let width = width.max(MIN_GENERATED_SIZE);
+    let height = height.max(MIN_GENERATED_SIZE);
+    let difficulty = difficulty.min(MAX_DIFFICULTY);
+    let spawn = Position {
+        x: width / 2,
+        y: height / 2,
+    };
+    let mut rng = Rng::new(seed);
+
+    for attempt in 0..GENERATOR_ATTEMPTS {
+        let mut grid = Grid::bordered(width, height);
+        if attempt + 1 < GENERATOR_ATTEMPTS {
+            match rng.below(3) {
+                0 => grid.rooms(&mut rng, difficulty),
+                1 => grid.corridors(&mut rng, difficulty),
+                _ => grid.arena(&mut rng, difficulty),
+            }
+        }
+
+        grid.clear_spawn(&spawn);
+        grid.seal_unreachable(&spawn);
+
+        if grid.open_cells() * 2 >= (width * height) as usize {
+            return Level {
+                width,
+                height,
+                walls: grid.wall_indices(),
+                spawn,
+                apple: None,
+            };
+        }
+    }
+
+    unreachable!("an empty bordered arena always has enough room")
+}
+
+struct Grid {
+    width: u32,
+    height: u32,
+    walls: Vec<bool>,
+}
+
+impl Grid {
+    fn bordered(width: u32, height: u32) -> Grid {
+        let mut grid = Grid {
+            width,
+            height,
+            walls: vec![false; (width * height) as usize],
+        };
+        for x in 0..width {
+            grid.set(x, 0, true);
+            grid.set(x, height - 1, true);
+        }
+        for y in 0..height {
+            grid.set(0, y, true);
+            grid.set(width - 1, y, true);
+        }
+        grid
+    }
+
+    fn set(&mut self, x: u32, y: u32, wall: bool) {
+        if x < self.width && y < self.height {
+            self.walls[(y * self.width + x) as usize] = wall;
+        }
+    }
+
+    // Clears a wall without ever breaking the outer border.
+    fn open(&mut self, x: u32, y: u32) {
+        if x > 0 && y > 0 && x + 1 < self.width && y + 1 < self.height {
+            self.set(x, y, false);
+        }
+    }
+
+    // Divider walls split the board into rooms joined by two-cell doorways.
+    fn rooms(&mut self, rng: &mut Rng, difficulty: u32) {
+        let room = (12 - difficulty / 2).max(6);
+
+        for x in (room..self.width - 1).step_by(room as usize) {
+            for y in 1..self.height - 1 {
+                self.set(x, y, true);
+            }
+            for start in (1..self.height - 1).step_by(room as usize) {
+                let door = start + rng.below(room.saturating_sub(2).max(1));
+                self.open(x, door);
+                self.open(x, door + 1);
+            }
+        }
+
+        for y in (room..self.height - 1).step_by(room as usize) {
+            for x in 1..self.width - 1 {
+                self.set(x, y, true);
+            }
+            for start in (1..self.width - 1).step_by(room as usize) {
+                let door = start + rng.below(room.saturating_sub(2).max(1));
+                self.open(door, y);
+                self.open(door + 1, y);
+            }
+        }
+    }
+
+    fn corridors(&mut self, rng: &mut Rng, difficulty: u32) {
+        for _ in 0..4 + difficulty * 2 {
+            let horizontal = rng.below(2) == 0;
+            let x = 2 + rng.below(self.width - 4);
+            let y = 2 + rng.below(self.height - 4);
+            let length = 3 + rng.below(self.width.min(self.height) / 3);
+
+            for i in 0..length {
+                if horizontal {
+                    self.set(x + i, y, true);
+                } else {
+                    self.set(x, y + i, true);
+                }
+            }
+        }
+    }
+
+    // Blocks placed in one quadrant and mirrored into the other three.
+    fn arena(&mut self, rng: &mut Rng, difficulty: u32) {
+        for _ in 0..2 + difficulty {
+            let x = 2 + rng.below(self.width / 2 - 2);
+            let y = 2 + rng.below(self.height / 2 - 2);
+            let w = 1 + rng.below(3);
+            let h = 1 + rng.below(3);
+
+            for bx in x..x + w {
+                for by in y..y + h {
+                    let mx = self.width - 1 - bx;
+                    let my = self.height - 1 - by;
+                    self.set(bx, by, true);
+                    self.set(mx, by, true);
+                    self.set(bx, my, true);
+                    self.set(mx, my, true);
+                }
+            }
+        }
+    }
+
+    fn clear_spawn(&mut self, spawn: &Position) {
+        for x in spawn.x - SPAWN_LENGTH..=spawn.x + 2 {
+            self.open(x, spawn.y);
+        }
+    }
+
+    fn seal_unreachable(&mut self, spawn: &Position) {
+        let mut reached = vec![false; self.walls.len()];
+        let mut stack = vec![spawn.y * self.width + spawn.x];
+        reached[stack[0] as usize] = true;
+
+        while let Some(idx) = stack.pop() {
+            let (x, y) = (idx % self.width, idx / self.width);
+            let neighbours = [
+                (x > 0).then(|| idx - 1),
+                (x + 1 < self.width).then(|| idx + 1),
+                (y > 0).then(|| idx - self.width),
+                (y + 1 < self.height).then(|| idx + self.width),
+            ];
+
+            for next in neighbours.iter().flatten() {
+                let next = *next as usize;
+                if !self.walls[next] && !reached[next] {
+                    reached[next] = true;
+                    stack.push(next as u32);
+                }
+            }
+        }
+
+        for (wall, reached) in self.walls.iter_mut().zip(reached) {
+            *wall |= !reached;
+        }
+    }
+
+    fn open_cells(&self) -> usize {
+        self.walls.iter().filter(|&&wall| !wall).count()
+    }
+
+    fn wall_indices(&self) -> Vec<u32> {
+        (0..self.walls.len() as u32).filter(|&idx| self.walls[idx as usize]).collect()
+    }
+}
+
+// === Campaign ===
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Goal {
+    Length(u32),
+    Apples(u32),
+}
+
+impl Goal {
+    pub fn is_met(&self, length: u32, apples: u32) -> bool {
+        match *self {
+            Goal::Length(target) => length >= target,
+            Goal::Apples(target) => apples >= target,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Campaign {
+    // Shared, so copying a game in a campaign doesn't copy every level.
+    pub(crate) stages: Rc<Vec<(Level, Goal)>>,
+    pub(crate) current: usize,
+    pub(crate) apples: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Campaign {
+    pub fn new() -> Campaign {
+        Campaign::default()
+    }
+
+    pub fn add_level(&mut self, map: &str, target_length: u32) -> Result<(), String> {
+        self.push(Level::parse(map)?, Goal::Length(target_length));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    pub fn current_level(&self) -> usize {
+        self.current
+    }
+
+    pub fn advance(&mut self) -> bool {
+        if self.current + 1 >= self.stages.len() {
+            return false;
+        }
+
+        self.current += 1;
+        self.apples = 0;
+        true
+    }
+}
+
+impl Campaign {
+    pub fn push(&mut self, level: Level, goal: Goal) {
+        Rc::make_mut(&mut self.stages).push((level, goal));
+    }
+
+    pub fn level(&self) -> &Level {
+        &self.stages[self.current].0
+    }
+
+    pub(crate) fn next_level(&self) -> Option<&Level> {
+        self.stages.get(self.current + 1).map(|(level, _)| level)
+    }
+
+    pub fn goal(&self) -> Goal {
+        self.stages[self.current].1
+    }
+
+    pub(crate) fn record_apple(&mut self) {
+        self.apples += 1;
+    }
+
+    pub(crate) fn apples(&self) -> u32 {
+        self.apples
+    }
+}
@@ -1,13 +1,105 @@
 ﻿// === Modules and Imports ===
+pub mod ai;
+mod arena;
+mod bitset;
+pub mod camera;
+#[cfg(feature = "canvas")]
+pub mod canvas;
+mod combo;
+pub mod config;
+pub mod daily;
+pub mod effects;
+pub mod env;
+pub mod fog;
+mod export;
+pub mod food;
+#[cfg(feature = "web")]
+pub mod game_loop;
+mod ghost;
+mod grid;
+mod grace;
+mod haptics;
+mod hash;
+pub mod hazards;
+pub mod highscores;
+pub mod input;
+pub mod interpolation;
+#[cfg(feature = "leaderboard")]
+pub mod leaderboard;
+pub mod levels;
+mod lives;
+mod minimap;
+pub mod modes;
+pub mod narration;
+pub mod netcode;
+pub mod perf;
+mod portals;
+#[cfg(feature = "net-ws")]
+pub mod net_ws;
+pub mod powerups;
+pub mod presets;
+mod profile;
+pub mod replay;
+mod resize;
+mod rewind;
+pub mod rules;
+pub mod scoring;
+#[cfg(feature = "wasm")]
+mod script;
+mod rng;
+#[cfg(feature = "storage")]
+pub mod settings;
+pub mod sim;
+pub mod snapshot;
+pub mod sounds;
+pub mod spectator;
+#[cfg(feature = "storage")]
+mod storage;
+pub mod speed;
+pub mod terrain;
+pub mod theme;
 mod utils;
+#[cfg(feature = "webgl")]
+pub mod webgl;
 
+use bitset::BitSet;
+use combo::Combo;
+use effects::Effect;
+use food::{Food, FoodKind};
+use grid::CellGrid;
+use haptics::Haptics;
+use input::{InputBuffer, KeyMap, TouchController};
+use sounds::{Sound, SoundCue};
+use levels::{Campaign, Level};
+use modes::GameMode;
+use perf::PerfStats;
+use powerups::{ActiveEffect, PowerUp, PowerUps};
+use profile::{Profile, Section};
+use arena::Shrink;
+use replay::Replay;
+use rewind::History;
+use rules::{Collision, Rules};
+use rng::Rng;
+use scoring::Scoring;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use speed::{SpeedCurve, SpeedModel};
+use terrain::Terrain;
+use theme::Theme;
+use std::cell::RefCell;
 use std::cmp::PartialEq;
-use std::convert::TryInto;
+use std::collections::{BTreeMap, VecDeque};
 use std::fmt;
+use std::rc::Rc;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
 use wasm_timer::Instant;
 
 // === External JS Bindings ===
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = Math)]
@@ -15,16 +107,28 @@ extern "C" {
 }
 
 // === Shared Enums and Structs ===
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Cell {
-    Alive = 1,
-    Dead = 0,
+    Empty = 0,
+    SnakeHead = 1,
+    SnakeBody = 2,
+    Apple = 3,
+    Wall = 4,
+    Poison = 5,
+    Golden = 6,
+    Speed = 7,
+    PowerUp = 8,
+    Ghost = 9,
+    Portal = 10,
+    Hazard = 11,
 }
 
-#[wasm_bindgen]
-#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum DirectionName {
     Up,
     Down,
@@ -32,15 +136,113 @@ pub enum DirectionName {
     Right,
 }
 
-#[wasm_bindgen]
-#[derive(Debug, Clone, Copy)]
+// Relative mode reads Left/Right as turns from the current heading and
+// ignores Up/Down, for two-button play.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InputMode {
+    Absolute,
+    Relative,
+}
+
+// How a crash between two snakes is settled. The rammer is the snake whose
+// head moves into the other; in a head-on meeting both are rammers.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CollisionPolicy {
+    BodyKillsRammer,
+    HeadOnKillsBoth,
+    LongerWins,
+    PassThrough,
+}
+
+// Versus matches end with one survivor. Co-op snakes share the score and
+// respawn after a delay; the match ends once every snake is down at once.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum MatchMode {
+    Versus,
+    Coop,
+}
+
+// Which board edges wrap around to the opposite side; the rest are walls.
+// JS sees this through `wraps_x`, `wraps_y` and `set_wrap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum UniverseTopology {
     Flat,
     Toroidal,
+    // Wraps left-right, walls top and bottom.
+    CylinderX,
+    // Wraps top-bottom, walls left and right.
+    CylinderY,
+    Custom { wrap_x: bool, wrap_y: bool },
 }
 
-#[wasm_bindgen]
-#[derive(Clone)]
+impl UniverseTopology {
+    // The named topology with this wrapping.
+    pub fn from_wrap(wrap_x: bool, wrap_y: bool) -> UniverseTopology {
+        match (wrap_x, wrap_y) {
+            (false, false) => UniverseTopology::Flat,
+            (true, true) => UniverseTopology::Toroidal,
+            (true, false) => UniverseTopology::CylinderX,
+            (false, true) => UniverseTopology::CylinderY,
+        }
+    }
+
+    pub fn wraps_x(self) -> bool {
+        match self {
+            UniverseTopology::Flat | UniverseTopology::CylinderY => false,
+            UniverseTopology::Toroidal | UniverseTopology::CylinderX => true,
+            UniverseTopology::Custom { wrap_x, .. } => wrap_x,
+        }
+    }
+
+    pub fn wraps_y(self) -> bool {
+        match self {
+            UniverseTopology::Flat | UniverseTopology::CylinderX => false,
+            UniverseTopology::Toroidal | UniverseTopology::CylinderY => true,
+            UniverseTopology::Custom { wrap_y, .. } => wrap_y,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameState {
+    Menu,
+    Countdown,
+    Running,
+    Paused,
+    GameOver,
+    Victory,
+    // A timed match ran out of time.
+    Finished,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameOverReason {
+    HitWall,
+    HitSelf,
+    HitObstacle,
+    BoardFull,
+    CampaignComplete,
+    Starved,
+    HitSnake,
+    HitHazard,
+    Crushed,
+    TimeUp,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Position {
     x: u32,
     y: u32,
@@ -52,44 +254,167 @@ impl PartialEq for Position {
     }
 }
 
+impl Eq for Position {}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Position {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new(x: u32, y: u32) -> Position {
+        Position { x, y }
+    }
+
+    pub fn x(&self) -> u32 {
+        self.x
+    }
+
+    pub fn y(&self) -> u32 {
+        self.y
+    }
+
+    // The cell index on a board `width` cells wide, as `cells()` is laid out.
+    pub fn index(&self, width: u32) -> u32 {
+        self.y * width + self.x
+    }
+}
+
 // === Snake ===
-#[wasm_bindgen]
+const INPUT_QUEUE_LEN: usize = 2;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Direction {
     vx: i32,
     vy: i32,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Direction {
+    pub fn vx(&self) -> i32 {
+        self.vx
+    }
+
+    // Grows downwards, as on screen.
+    pub fn vy(&self) -> i32 {
+        self.vy
+    }
+
+    pub fn name(&self) -> DirectionName {
+        match (self.vx, self.vy) {
+            (0, -1) => DirectionName::Up,
+            (0, 1) => DirectionName::Down,
+            (-1, 0) => DirectionName::Left,
+            _ => DirectionName::Right,
+        }
+    }
+}
+
+impl Direction {
+    fn from_name(direction: DirectionName) -> Direction {
+        match direction {
+            DirectionName::Up => Direction { vx: 0, vy: -1 },
+            DirectionName::Down => Direction { vx: 0, vy: 1 },
+            DirectionName::Left => Direction { vx: -1, vy: 0 },
+            DirectionName::Right => Direction { vx: 1, vy: 0 },
+        }
+    }
+
+    fn opposite(&self) -> DirectionName {
+        Direction {
+            vx: -self.vx,
+            vy: -self.vy,
+        }
+        .name()
+    }
+
+    fn is_opposite(&self, other: &Direction) -> bool {
+        self.vx == -other.vx && self.vy == -other.vy
+    }
+
+    // Screen coordinates: y grows downwards, so a left turn is clockwise in math terms.
+    fn rotated_left(&self) -> Direction {
+        Direction { vx: self.vy, vy: -self.vx }
+    }
+
+    fn rotated_right(&self) -> Direction {
+        Direction { vx: -self.vy, vy: self.vx }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Snake {
-    body: Vec<Position>,
+    body: VecDeque<Position>,
     direction: Direction,
+    pending: VecDeque<Direction>,
 }
 
-#[wasm_bindgen]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
 impl Snake {
     pub fn new() -> Snake {
         Snake {
-            body: vec![
+            body: VecDeque::from(vec![
                 Position { x: 5, y: 6 },
                 Position { x: 4, y: 6 },
                 Position { x: 3, y: 6 },
                 Position { x: 2, y: 6 },
-            ],
+            ]),
             direction: Direction { vx: 1, vy: 0 },
+            pending: VecDeque::with_capacity(INPUT_QUEUE_LEN),
         }
     }
 
-    fn set_direction(&mut self, vx: i32, vy: i32) {
-        self.direction = Direction { vx, vy };
+    // The body trails behind the head, opposite to `direction`.
+    pub fn new_at(x: u32, y: u32, direction: DirectionName, length: u32) -> Snake {
+        Snake::spawn(&Position { x, y }, Direction::from_name(direction), length.max(1))
+    }
+
+    fn spawn(head: &Position, direction: Direction, length: u32) -> Snake {
+        let body = (0..length as i32)
+            .map(|i| Position {
+                x: (head.x as i32 - direction.vx * i) as u32,
+                y: (head.y as i32 - direction.vy * i) as u32,
+            })
+            .collect();
+
+        Snake {
+            body,
+            direction,
+            pending: VecDeque::with_capacity(INPUT_QUEUE_LEN),
+        }
     }
 
+    // Turns are queued and validated against the heading they will follow,
+    // so a quick Up+Left while moving right registers both instead of reversing.
     pub fn set_direction_name(&mut self, direction: DirectionName) {
-        match direction {
-            DirectionName::Up => self.set_direction(0, -1),
-            DirectionName::Down => self.set_direction(0, 1),
-            DirectionName::Left => self.set_direction(-1, 0),
-            DirectionName::Right => self.set_direction(1, 0),
+        self.queue_direction(Direction::from_name(direction));
+    }
+
+    pub fn turn_left(&mut self) {
+        self.queue_direction(self.last_direction().rotated_left());
+    }
+
+    pub fn turn_right(&mut self) {
+        self.queue_direction(self.last_direction().rotated_right());
+    }
+
+    fn last_direction(&self) -> Direction {
+        self.pending.back().copied().unwrap_or(self.direction)
+    }
+
+    fn queue_direction(&mut self, next: Direction) {
+        let last = self.last_direction();
+        if next == last || next.is_opposite(&last) || self.pending.len() >= INPUT_QUEUE_LEN {
+            return;
         }
+
+        self.pending.push_back(next);
+    }
+
+    // The way the snake is moving now; queued turns haven't happened yet.
+    pub fn direction_name(&self) -> DirectionName {
+        self.direction.name()
     }
 
     pub fn has_index(&self, index: u32, universe_width: u32) -> bool {
@@ -97,11 +422,21 @@ impl Snake {
     }
 }
 
+impl Default for Snake {
+    fn default() -> Self {
+        Snake::new()
+    }
+}
+
 // === FPS Counter ===
 pub struct FpsCounter {
     last_frame: Instant,
     frames: u32,
     fps: f64,
+    fps_target: f64,
+    frame_times: perf::Window,
+    tick_times: perf::Window,
+    render_times: perf::Window,
 }
 
 impl FpsCounter {
@@ -110,9 +445,17 @@ impl FpsCounter {
             last_frame: Instant::now(),
             frames: 0,
             fps: fps_target,
+            fps_target,
+            frame_times: perf::Window::default(),
+            tick_times: perf::Window::default(),
+            render_times: perf::Window::default(),
         }
     }
 
+    pub fn reset(&mut self) {
+        *self = FpsCounter::new(self.fps_target);
+    }
+
     pub fn tick(&mut self, fps_measurements: u32) {
         const AVG_LEARNING_RATE: f64 = 0.001;
 
@@ -123,120 +466,249 @@ impl FpsCounter {
         if self.frames != 0 && elapsed > 0.0 {
             self.fps = self.fps * (1.0 - AVG_LEARNING_RATE)
                 + ((fps_measurements as f64) / elapsed) * AVG_LEARNING_RATE;
+            self.frame_times.push(elapsed * 1000.0);
         }
 
         self.frames += 1;
     }
-}
 
-// === Universe ===
-#[wasm_bindgen]
-pub struct Universe {
-    width: u32,
-    height: u32,
-    cells: Vec<Cell>,
-    snake: Snake,
-    apple: Option<Position>,
-    game_over: bool,
-    topology: UniverseTopology,
-    counter: FpsCounter,
-}
+    pub fn record_tick(&mut self, ms: f64) {
+        self.tick_times.push(ms);
+    }
 
-#[wasm_bindgen]
-impl Universe {
-    pub fn new(snake: Snake, fps_target: f64) -> Universe {
-        utils::set_panic_hook();
+    pub fn record_render(&mut self, ms: f64) {
+        self.render_times.push(ms);
+    }
 
-        let width: u32 = 64;
-        let height: u32 = 64;
+    pub fn perf_stats(&self) -> PerfStats {
+        PerfStats {
+            fps: self.fps,
+            frame_min_ms: self.frame_times.min(),
+            frame_max_ms: self.frame_times.max(),
+            frame_p95_ms: self.frame_times.percentile(0.95),
+            tick_mean_ms: self.tick_times.mean(),
+            tick_max_ms: self.tick_times.max(),
+            render_mean_ms: self.render_times.mean(),
+            render_max_ms: self.render_times.max(),
+        }
+    }
+}
 
-        let cells = (0..width * height)
-            .map(|i| if snake.has_index(i, width) { Cell::Alive } else { Cell::Dead })
-            .collect();
+// === Stats ===
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Stats {
+    score: u32,
+    apples_eaten: u32,
+    ticks: u32,
+    length: u32,
+    max_length: u32,
+    elapsed_ms: f64,
+    // Game time: the sum of every tick's fixed step, so it stands still
+    // while the game is paused or behind.
+    game_ms: f64,
+    // Game time at which the longest snake first grew to each milestone.
+    splits: Vec<f64>,
+}
 
-        Universe {
-            width,
-            height,
-            cells,
-            snake,
-            apple: None,
-            game_over: false,
-            topology: UniverseTopology::Toroidal,
-            counter: FpsCounter::new(fps_target),
+impl Stats {
+    pub fn new(length: u32) -> Stats {
+        Stats {
+            score: 0,
+            apples_eaten: 0,
+            ticks: 0,
+            length,
+            max_length: length,
+            elapsed_ms: 0.0,
+            game_ms: 0.0,
+            splits: Vec::new(),
         }
     }
 
-    pub fn tick(&mut self, fps_measurements: u32) {
-        if self.game_over {
-            return;
+    fn record_tick(&mut self, length: u32, elapsed_ms: f64, tick_ms: f64, milestone: u32) {
+        self.ticks += 1;
+        self.game_ms += tick_ms;
+        let reached = |len: u32| milestone > 0 && len.is_multiple_of(milestone);
+        if length > self.max_length && (self.max_length + 1..=length).any(reached) {
+            self.splits.push(self.game_ms);
         }
+        self.length = length;
+        self.max_length = self.max_length.max(length);
+        self.elapsed_ms = elapsed_ms;
+    }
 
-        let new_head = match self.topology {
-            UniverseTopology::Flat => {
-                let head = self.snake.body.first().unwrap();
-                let new_x = head.x as i32 + self.snake.direction.vx;
-                let new_y = head.y as i32 + self.snake.direction.vy;
+    fn record_food(&mut self, kind: FoodKind, points: u32) {
+        self.record_points(points);
+        if kind.grows() {
+            self.apples_eaten += 1;
+        }
+    }
 
-                if new_x < 0 || new_y < 0 || new_x >= self.width as i32 || new_y >= self.height as i32 {
-                    self.game_over = true;
-                    return;
-                }
+    fn record_points(&mut self, points: u32) {
+        self.score = self.score.saturating_add(points);
+    }
 
-                Position {
-                    x: new_x as u32,
-                    y: new_y as u32,
-                }
-            }
-            UniverseTopology::Toroidal => Position {
-                x: self.add_u32_i32(self.snake.body.first().unwrap().x, self.snake.direction.vx, self.width),
-                y: self.add_u32_i32(self.snake.body.first().unwrap().y, self.snake.direction.vy, self.height),
-            },
-        };
+    pub fn to_json(&self) -> String {
+        let splits: Vec<_> = self.splits.iter().map(f64::to_string).collect();
+        format!(
+            "{{\"score\":{},\"apples_eaten\":{},\"ticks\":{},\"length\":{},\"max_length\":{},\"elapsed_ms\":{},\"game_ms\":{},\"splits\":[{}]}}",
+            self.score,
+            self.apples_eaten,
+            self.ticks,
+            self.length,
+            self.max_length,
+            self.elapsed_ms,
+            self.game_ms,
+            splits.join(",")
+        )
+    }
+}
 
-        if self.snake.body.contains(&new_head) {
-            self.game_over = true;
-            return;
+// === Tick Result ===
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct TickResult {
+    moved: bool,
+    ate_apple: bool,
+    died: bool,
+    died_reason: Option<GameOverReason>,
+    won: bool,
+    new_head_x: u32,
+    new_head_y: u32,
+}
+
+impl TickResult {
+    fn idle(head: &Position) -> TickResult {
+        TickResult {
+            moved: false,
+            ate_apple: false,
+            died: false,
+            died_reason: None,
+            won: false,
+            new_head_x: head.x,
+            new_head_y: head.y,
         }
+    }
 
-        let mut next = self.cells.clone();
+    fn moved_to(head: &Position) -> TickResult {
+        TickResult {
+            moved: true,
+            ..TickResult::idle(head)
+        }
+    }
 
-        if let Some(apple) = &self.apple {
-            if new_head.eq(apple) {
-                self.randomize_apple();
-                let apple = self.apple.clone().unwrap();
-                let apple_idx = self.get_index(apple.y, apple.x);
-                next[apple_idx] = Cell::Alive;
-            } else {
-                let last = self.snake.body.pop().unwrap();
-                let old_idx = self.get_index(last.y, last.x);
-                next[old_idx] = Cell::Dead;
-            }
+    fn fatal(head: &Position, reason: GameOverReason) -> TickResult {
+        TickResult {
+            died: true,
+            died_reason: Some(reason),
+            ..TickResult::idle(head)
         }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl TickResult {
+    pub fn moved(&self) -> bool {
+        self.moved
+    }
+
+    pub fn ate_apple(&self) -> bool {
+        self.ate_apple
+    }
+
+    pub fn died(&self) -> bool {
+        self.died
+    }
+
+    pub fn died_reason(&self) -> Option<GameOverReason> {
+        self.died_reason
+    }
+
+    pub fn won(&self) -> bool {
+        self.won
+    }
 
-        self.snake.body.insert(0, new_head);
-        let new_idx = self.get_index(
-            self.snake.body.first().unwrap().y,
-            self.snake.body.first().unwrap().x
-        );
-        next[new_idx] = Cell::Alive;
+    pub fn new_head_x(&self) -> u32 {
+        self.new_head_x
+    }
+
+    pub fn new_head_y(&self) -> u32 {
+        self.new_head_y
+    }
+}
 
-        self.cells = next;
+// === Events ===
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GameEvent {
+    AppleEaten { score: u32 },
+    GameOver { reason: GameOverReason },
+    SpeedChanged { ticks_per_second: f64 },
+    LevelComplete { level: u32 },
+    ArenaShrunk { rings: u32 },
+    ComboBroken { player: u32, combo: u32 },
+}
 
-        if self.apple.is_none() {
-            self.randomize_apple();
+impl GameEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameEvent::AppleEaten { .. } => "AppleEaten",
+            GameEvent::GameOver { .. } => "GameOver",
+            GameEvent::SpeedChanged { .. } => "SpeedChanged",
+            GameEvent::LevelComplete { .. } => "LevelComplete",
+            GameEvent::ArenaShrunk { .. } => "ArenaShrunk",
+            GameEvent::ComboBroken { .. } => "ComboBroken",
         }
+    }
 
-        if fps_measurements > 0 {
-            self.counter.tick(fps_measurements);
+    #[cfg(feature = "wasm")]
+    fn to_js(self) -> JsValue {
+        let event = js_sys::Object::new();
+        let set = |key: &str, value: JsValue| {
+            js_sys::Reflect::set(&event, &JsValue::from_str(key), &value).expect("event object is extensible");
+        };
+
+        set("type", JsValue::from_str(self.name()));
+        match self {
+            GameEvent::AppleEaten { score } => set("score", score.into()),
+            GameEvent::GameOver { reason } => set("reason", reason.into()),
+            GameEvent::SpeedChanged { ticks_per_second } => set("ticksPerSecond", ticks_per_second.into()),
+            GameEvent::LevelComplete { level } => set("level", level.into()),
+            GameEvent::ArenaShrunk { rings } => set("rings", rings.into()),
+            GameEvent::ComboBroken { player, combo } => {
+                set("player", player.into());
+                set("combo", combo.into());
+            }
         }
+
+        event.into()
     }
+}
 
-    pub fn on_click(&mut self, direction: DirectionName) {
-        self.snake.set_direction_name(direction);
+// === Cells View ===
+// Where the board lives in wasm memory and how it is laid out, for a
+// `Uint8Array` view over `memory.buffer`: one byte per cell, row-major. The
+// view goes stale when the board is resized or a level is loaded.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug)]
+pub struct CellsView {
+    ptr: *const Cell,
+    len: u32,
+    width: u32,
+    height: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl CellsView {
+    pub fn ptr(&self) -> *const Cell {
+        self.ptr
     }
 
-    pub fn render(&self) -> String {
-        self.to_string()
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     pub fn width(&self) -> u32 {
@@ -246,73 +718,1783 @@ impl Universe {
     pub fn height(&self) -> u32 {
         self.height
     }
+}
 
-    pub fn cells(&self) -> *const Cell {
-        self.cells.as_ptr()
+// === Free Cells ===
+const NOT_FREE: u32 = u32::MAX;
+const SAMPLE_PROBES: u32 = 16;
+
+// Indices of empty cells kept in a dense list with a reverse lookup table,
+// so inserts, removals and uniform sampling are all O(1).
+#[derive(Clone, Debug)]
+struct FreeCells {
+    cells: Vec<u32>,
+    slots: Vec<u32>,
+}
+
+impl FreeCells {
+    fn full(len: usize) -> FreeCells {
+        FreeCells {
+            cells: (0..len as u32).collect(),
+            slots: (0..len as u32).collect(),
+        }
     }
 
-    pub fn snake_mut(&mut self) -> *mut Snake {
-        &mut self.snake
+    fn insert(&mut self, idx: u32) {
+        if self.slots[idx as usize] == NOT_FREE {
+            self.slots[idx as usize] = self.cells.len() as u32;
+            self.cells.push(idx);
+        }
     }
 
-    pub fn is_game_over(&self) -> bool {
-        self.game_over
+    fn remove(&mut self, idx: u32) {
+        let slot = self.slots[idx as usize];
+        if slot == NOT_FREE {
+            return;
+        }
+
+        let last = *self.cells.last().unwrap();
+        self.cells.swap_remove(slot as usize);
+        if last != idx {
+            self.slots[last as usize] = slot;
+        }
+        self.slots[idx as usize] = NOT_FREE;
     }
 
-    pub fn fps(&self) -> f64 {
-        self.counter.fps
+    // Draws depend only on which cells are free, never on the order of the
+    // list, so a board rebuilt from a snapshot picks the same cells. Random
+    // probes find a free cell quickly unless the board is nearly full.
+    fn sample(&self, rng: &mut Rng) -> Option<u32> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        for _ in 0..SAMPLE_PROBES {
+            let idx = rng.below(self.slots.len() as u32);
+            if self.slots[idx as usize] != NOT_FREE {
+                return Some(idx);
+            }
+        }
+
+        let nth = rng.below(self.cells.len() as u32) as usize;
+        (0..self.slots.len() as u32).filter(|&idx| self.slots[idx as usize] != NOT_FREE).nth(nth)
     }
+}
 
-    pub fn topology(&self) -> UniverseTopology {
-        self.topology
+// === Universe ===
+const DEFAULT_WIDTH: u32 = 64;
+const DEFAULT_HEIGHT: u32 = 64;
+const DEFAULT_FPS_TARGET: f64 = 60.0;
+const MAX_STEPS_PER_ADVANCE: u32 = 8;
+const RESPAWN_DELAY_TICKS: u32 = 20;
+const SPAWN_CLEARANCE: i32 = 3;
+
+// One snake on the board along with what it has earned this match.
+struct Player {
+    snake: Snake,
+    // The body before the last tick moved it, for `interpolation_state`.
+    previous: Vec<Position>,
+    spawn: Snake,
+    score: u32,
+    alive: bool,
+    death: Option<GameOverReason>,
+    hunger_ticks: u32,
+    respawn_in: u32,
+    combo: Combo,
+    lives: u32,
+    // The tick count from which `SPAWN_GRACE_TICKS` run.
+    spawned_at: u32,
+    // Sitting out this tick in mud.
+    mired: bool,
+    controller: Option<Box<dyn ai::Controller>>,
+}
+
+impl Player {
+    fn new(snake: Snake, lives: u32) -> Player {
+        Player {
+            spawn: snake.clone(),
+            previous: Vec::new(),
+            snake,
+            score: 0,
+            alive: true,
+            death: None,
+            hunger_ticks: 0,
+            respawn_in: 0,
+            combo: Combo::default(),
+            lives,
+            spawned_at: 0,
+            mired: false,
+            controller: None,
+        }
     }
 
-    pub fn toggle_topology(&mut self) {
-        self.topology = match self.topology {
-            UniverseTopology::Flat => UniverseTopology::Toroidal,
-            UniverseTopology::Toroidal => UniverseTopology::Flat,
+    // Back to the spawn for a new match; an attached controller stays.
+    fn reset(&mut self, lives: u32) {
+        self.snake = self.spawn.clone();
+        self.previous.clear();
+        self.score = 0;
+        self.alive = true;
+        self.death = None;
+        self.hunger_ticks = 0;
+        self.respawn_in = 0;
+        self.combo = Combo::default();
+        self.lives = lives;
+        self.spawned_at = 0;
+        self.mired = false;
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Universe {
+    width: u32,
+    height: u32,
+    cells: CellGrid,
+    // The running tick, counted by `age_clock`, each cell last changed on.
+    changed_at: Vec<u32>,
+    age_clock: u32,
+    // Ticks since each cell last changed, saturating at 255; worked out from
+    // `changed_at` when asked for.
+    ages: RefCell<Vec<u8>>,
+    free: FreeCells,
+    occupied: BitSet,
+    walls: BitSet,
+    // Linked cell index pairs.
+    portals: Vec<(u32, u32)>,
+    hazards: Vec<hazards::Hazard>,
+    shrink: Shrink,
+    mode: GameMode,
+    time_left_ms: f64,
+    rules: Option<Rc<dyn Rules>>,
+    #[cfg(feature = "wasm")]
+    script: Option<Rc<script::ScriptRules>>,
+    dirty: Vec<u32>,
+    dirty_mark: BitSet,
+    rgba: Vec<u8>,
+    rgba_scale: u32,
+    theme: Theme,
+    viewport: Option<(u32, u32)>,
+    fog_radius: u32,
+    fog: Vec<u8>,
+    terrain: BTreeMap<u32, Terrain>,
+    // Counts edits to walls, portals and terrain, so rewind history knows
+    // when it can keep sharing the last layout it copied.
+    board_edits: u32,
+    effects: Vec<Effect>,
+    sounds: Vec<SoundCue>,
+    narration: Option<Vec<String>>,
+    haptics: Haptics,
+    // Set while ticks that already played out once are run again: they
+    // make no sounds, effects, narration or events the second time.
+    silent: bool,
+    players: Vec<Player>,
+    // Each snake's lives at the start of a game.
+    lives: u32,
+    countdown_seconds: u32,
+    countdown_ms: f64,
+    collision_policy: CollisionPolicy,
+    match_mode: MatchMode,
+    winner: Option<u32>,
+    foods: Vec<Food>,
+    apple_count: u32,
+    special_food: bool,
+    speed_boost: u32,
+    power_ups: PowerUps,
+    overlaps: u32,
+    hunger_interval: u32,
+    speed: SpeedModel,
+    scoring: Scoring,
+    last_tps: f64,
+    accumulator_ms: f64,
+    state: GameState,
+    game_over_reason: Option<GameOverReason>,
+    topology: UniverseTopology,
+    counter: FpsCounter,
+    profile: Profile,
+    seed: u64,
+    rng: Rng,
+    stats: Stats,
+    started_at: Instant,
+    #[cfg(feature = "wasm")]
+    listeners: Vec<js_sys::Function>,
+    campaign: Option<Campaign>,
+    recording: Option<Replay>,
+    ghost: Option<Box<ghost::Ghost>>,
+    history: History,
+    input: InputBuffer,
+    input_mode: InputMode,
+    assist: bool,
+    combos: bool,
+    key_map: KeyMap,
+    touch: TouchController,
+    #[cfg(feature = "web")]
+    keyboard: Option<input::Listener>,
+    #[cfg(feature = "web")]
+    touch_listeners: Vec<input::Listener>,
+    #[cfg(feature = "web")]
+    gamepad_direction: Option<DirectionName>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn new(snake: Snake, fps_target: f64) -> Universe {
+        Universe::new_with_size(snake, fps_target, DEFAULT_WIDTH, DEFAULT_HEIGHT)
+            .expect("default snake must fit the default universe")
+    }
+
+    pub fn new_with_size(snake: Snake, fps_target: f64, width: u32, height: u32) -> Result<Universe, String> {
+        Universe::new_with_seed(snake, fps_target, width, height, random_seed())
+    }
+
+    pub fn new_with_seed(
+        snake: Snake,
+        fps_target: f64,
+        width: u32,
+        height: u32,
+        seed: u64,
+    ) -> Result<Universe, String> {
+        utils::set_panic_hook();
+
+        if width == 0 || height == 0 || width.checked_mul(height).is_none() {
+            return Err(format!("invalid universe size {}x{}", width, height));
+        }
+
+        if let Some(p) = snake.body.iter().find(|p| p.x >= width || p.y >= height) {
+            return Err(format!(
+                "snake segment ({}, {}) lies outside the {}x{} universe",
+                p.x, p.y, width, height
+            ));
+        }
+
+        let snake_length = snake.body.len() as u32;
+        let cells = CellGrid::new(width, height);
+        let free = FreeCells::full(cells.len());
+        let occupied = BitSet::new(cells.len());
+
+        let mut universe = Universe {
+            width,
+            height,
+            changed_at: vec![0u32.wrapping_sub(u8::MAX as u32); cells.len()],
+            age_clock: 0,
+            ages: RefCell::new(Vec::new()),
+            cells,
+            free,
+            occupied,
+            walls: BitSet::new((width * height) as usize),
+            portals: Vec::new(),
+            hazards: Vec::new(),
+            shrink: Shrink::default(),
+            mode: GameMode::Classic,
+            time_left_ms: 0.0,
+            rules: None,
+            #[cfg(feature = "wasm")]
+            script: None,
+            dirty: Vec::new(),
+            dirty_mark: BitSet::new((width * height) as usize),
+            rgba: Vec::new(),
+            rgba_scale: 1,
+            theme: Theme::default(),
+            viewport: None,
+            fog_radius: 0,
+            fog: Vec::new(),
+            terrain: BTreeMap::new(),
+            board_edits: 0,
+            effects: Vec::new(),
+            sounds: Vec::new(),
+            narration: None,
+            haptics: Haptics::default(),
+            silent: false,
+            players: vec![Player::new(snake, 1)],
+            lives: 1,
+            countdown_seconds: 0,
+            countdown_ms: 0.0,
+            collision_policy: CollisionPolicy::BodyKillsRammer,
+            match_mode: MatchMode::Versus,
+            winner: None,
+            foods: Vec::new(),
+            apple_count: 1,
+            special_food: false,
+            speed_boost: 0,
+            power_ups: PowerUps::default(),
+            overlaps: 0,
+            hunger_interval: 0,
+            speed: SpeedModel::default(),
+            scoring: Scoring::default(),
+            last_tps: speed::DEFAULT_TICKS_PER_SECOND,
+            accumulator_ms: 0.0,
+            state: GameState::Running,
+            game_over_reason: None,
+            topology: UniverseTopology::Toroidal,
+            counter: FpsCounter::new(fps_target),
+            profile: Profile::default(),
+            seed,
+            rng: Rng::new(seed),
+            stats: Stats::new(snake_length),
+            started_at: Instant::now(),
+            #[cfg(feature = "wasm")]
+            listeners: Vec::new(),
+            campaign: None,
+            recording: None,
+            ghost: None,
+            history: History::default(),
+            input: InputBuffer::default(),
+            input_mode: InputMode::Absolute,
+            assist: false,
+            combos: false,
+            key_map: KeyMap::default(),
+            touch: TouchController::default(),
+            #[cfg(feature = "web")]
+            keyboard: None,
+            #[cfg(feature = "web")]
+            touch_listeners: Vec::new(),
+            #[cfg(feature = "web")]
+            gamepad_direction: None,
         };
+
+        universe.paint_snakes();
+        universe.spawn_food();
+
+        Ok(universe)
     }
 
-    fn get_index(&self, row: u32, column: u32) -> usize {
-        (row * self.width + column) as usize
+    pub fn from_map(map: &str) -> Result<Universe, String> {
+        Universe::from_map_with_seed(map, random_seed())
     }
 
-    fn add_u32_i32(&self, u: u32, i: i32, modulo: u32) -> u32 {
-        (u as i64 + i as i64).rem_euclid(modulo as i64) as u32
+    pub fn from_map_with_seed(map: &str, seed: u64) -> Result<Universe, String> {
+        Universe::from_level(&Level::parse(map)?, seed)
     }
 
-    fn randomize_apple(&mut self) {
-        let apple_x = random_position(self.width.try_into().unwrap()) as u32;
-        let apple_y = random_position(self.height.try_into().unwrap()) as u32;
-        let apple_index = self.get_index(apple_y, apple_x);
+    pub fn start_campaign(campaign: Campaign, seed: u64) -> Result<Universe, String> {
+        if campaign.is_empty() {
+            return Err("campaign has no levels".to_string());
+        }
 
-        if self.cells[apple_index] == Cell::Dead {
-            self.cells[apple_index] = Cell::Alive;
-        } else {
-            self.randomize_apple();
+        let mut universe = Universe::from_level(campaign.level(), seed)?;
+        universe.campaign = Some(campaign);
+        Ok(universe)
+    }
+
+    pub fn current_level(&self) -> Option<u32> {
+        self.campaign.as_ref().map(|campaign| campaign.current_level() as u32)
+    }
+
+    pub fn advance_level(&mut self) -> bool {
+        let next = match self.campaign.as_ref().and_then(Campaign::next_level) {
+            Some(level) => level.clone(),
+            None => return false,
+        };
+
+        // A level that won't load leaves the campaign where it was.
+        if self.load_level(&next).is_err() {
+            return false;
         }
+        self.campaign.as_mut().is_some_and(Campaign::advance)
+    }
 
-        self.apple = Some(Position { x: apple_x, y: apple_y });
+    pub fn reset(&mut self) {
+        let seed = self.rng.next_u64();
+        self.reset_with_seed(seed);
     }
-}
 
-// === Traits ===
-impl fmt::Display for Universe {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for line in self.cells.as_slice().chunks(self.width as usize) {
-            for &cell in line {
-                let symbol = if cell == Cell::Dead { '◻' } else { '◼' };
-                write!(f, "{}", symbol)?;
-            }
-            writeln!(f)?;
+    // A new game with the current settings, drawn from `seed`.
+    pub(crate) fn reset_with_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::new(seed);
+
+        self.clear_dirty();
+        self.reopen_arena();
+        for idx in 0..self.cells.len() {
+            let background = self.background(idx);
+            self.set_cell(idx, background);
         }
-        Ok(())
+        self.occupied.clear();
+        for player in &mut self.players {
+            player.reset(self.lives);
+        }
+        self.paint_snakes();
+        self.restart_hazards();
+        self.foods.clear();
+        self.effects.clear();
+        self.sounds.clear();
+        if let Some(lines) = &mut self.narration {
+            lines.clear();
+        }
+        self.speed_boost = 0;
+        self.power_ups.clear();
+        self.overlaps = 0;
+        self.spawn_food();
+        self.last_tps = self.ticks_per_second();
+        self.accumulator_ms = 0.0;
+        self.restart_clock();
+
+        self.state = GameState::Running;
+        self.game_over_reason = None;
+        self.winner = None;
+        self.stats = Stats::new(self.longest_snake());
+        self.started_at = Instant::now();
+        self.counter.reset();
+        self.profile = Profile::default();
+        self.begin_countdown();
+
+        if self.recording.is_some() {
+            self.start_recording();
+        }
+        self.restart_ghost();
+        self.history.clear();
+        self.refresh_fog();
     }
-}
 
-// === Utility ===
+    pub fn tick(&mut self, fps_measurements: u32) -> TickResult {
+        let started = Instant::now();
+        let running = self.state == GameState::Running;
+        let result = self.run_tick(fps_measurements);
+        self.refresh_fog();
+        if running {
+            self.profile.record_tick();
+            self.counter.record_tick(started.elapsed().as_secs_f64() * 1000.0);
+        }
+        result
+    }
+
+    fn run_tick(&mut self, fps_measurements: u32) -> TickResult {
+        self.clear_dirty();
+
+        if self.state == GameState::Countdown {
+            self.count_down(self.recommended_frame_delay_ms());
+            return TickResult::idle(self.head());
+        }
+        if self.state != GameState::Running {
+            return TickResult::idle(self.head());
+        }
+
+        let tick_ms = self.recommended_frame_delay_ms();
+        // Counted down before anything is eaten, so a fresh boost covers the
+        // next `SPEED_BOOST_TICKS` ticks in full.
+        self.speed_boost = self.speed_boost.saturating_sub(1);
+        self.age_clock = self.age_clock.wrapping_add(1);
+        self.erase_ghost();
+        if self.history.is_enabled() {
+            let state = self.save_state_sharing(self.history.layout(self.board_edits));
+            self.history.push(state, self.board_edits);
+        }
+        if let Some(recording) = &mut self.recording {
+            recording.record_tick();
+        }
+        for direction in self.input.drain() {
+            self.on_click(direction);
+        }
+        self.steer_controllers();
+        for id in 0..self.players.len() {
+            if self.players[id].alive && !self.on_ice(id) {
+                self.apply_turn(id);
+            }
+        }
+        for player in &mut self.players {
+            player.previous.clear();
+            player.previous.extend(player.snake.body.iter().copied());
+        }
+        self.move_hazards();
+
+        // Every snake picks its next cell against the board as it was before
+        // anyone moved, then crashes between snakes are settled by the policy.
+        let rules = self.rules();
+        let targets: Vec<_> = self.profiled(Section::Collisions, |universe| {
+            let mut planned = Vec::with_capacity(universe.players.len());
+            for id in 0..universe.players.len() {
+                if universe.stuck_in_mud(id) {
+                    planned.push(None);
+                    continue;
+                }
+                let mut target = universe.next_head(id);
+                if let Some(head) = target {
+                    if let Some(direction) = rules.on_head_computed(universe, id as u32, head) {
+                        universe.players[id].snake.direction = Direction::from_name(direction);
+                        target = universe.next_head(id);
+                    }
+                }
+                planned.push(target);
+            }
+            let mut doomed = vec![false; universe.players.len()];
+            for (rammer, victim) in universe.snake_contacts(&planned) {
+                let rammer_len = universe.players[rammer].snake.body.len();
+                let victim_len = universe.players[victim].snake.body.len();
+                match universe.collision_policy {
+                    CollisionPolicy::BodyKillsRammer => doomed[rammer] = true,
+                    CollisionPolicy::HeadOnKillsBoth => {
+                        doomed[rammer] = true;
+                        doomed[victim] = true;
+                    }
+                    CollisionPolicy::LongerWins => {
+                        doomed[rammer] |= rammer_len <= victim_len;
+                        doomed[victim] |= victim_len <= rammer_len;
+                    }
+                    CollisionPolicy::PassThrough => {}
+                }
+            }
+            planned
+                .into_iter()
+                .zip(doomed)
+                .enumerate()
+                .map(|(id, (target, doomed))| {
+                    let crash = match target {
+                        Some(Ok(_)) if doomed => GameOverReason::HitSnake,
+                        Some(Err(reason)) => reason,
+                        target => return target,
+                    };
+                    if universe.in_grace(id as u32) {
+                        return None;
+                    }
+                    match rules.on_collision(universe, id as u32, crash) {
+                        Collision::Die => Some(Err(crash)),
+                        Collision::Stop => None,
+                    }
+                })
+                .collect()
+        });
+
+        let mut result = TickResult::idle(self.head());
+        let mut fed = vec![false; self.players.len()];
+        let mut deaths = Vec::new();
+        let mut moved = Vec::new();
+
+        self.profiled(Section::CellUpdates, |universe| {
+            for (id, target) in targets.into_iter().enumerate() {
+                match target {
+                    None => {}
+                    Some(Err(reason)) => deaths.push((id, reason)),
+                    Some(Ok(head)) => {
+                        let eaten = universe.move_snake(id, head);
+                        fed[id] = eaten.is_some_and(|food| food.kind.grows());
+                        moved.push(id);
+                        if id == 0 {
+                            result = TickResult::moved_to(&head);
+                            result.ate_apple = eaten.is_some();
+                        }
+                    }
+                }
+            }
+        });
+        for (id, eaten) in self.ride_conveyors(&moved) {
+            fed[id] |= eaten.is_some_and(|food| food.kind.grows());
+            if id == 0 {
+                let ate_apple = result.ate_apple || eaten.is_some();
+                result = TickResult::moved_to(self.head());
+                result.ate_apple = ate_apple;
+            }
+        }
+        result.won = self.state == GameState::Victory;
+
+        if let Some(&(_, reason)) = deaths.iter().find(|(id, _)| *id == 0) {
+            result = TickResult::fatal(self.head(), reason);
+        }
+        if let Some(&(_, reason)) = deaths.last() {
+            self.kill(&deaths);
+            if self.state == GameState::Running && self.match_over() {
+                self.end_game(reason);
+                return result;
+            }
+        }
+
+        self.score_tick(&fed);
+        self.age_food();
+        self.power_ups.tick();
+        self.decay_combos();
+
+        if self.state == GameState::Running {
+            let crushed = self.shrink_arena();
+            if !crushed.is_empty() {
+                if crushed[0].0 == 0 {
+                    result.died = true;
+                    result.died_reason = Some(GameOverReason::Crushed);
+                }
+                if self.match_over() {
+                    self.end_game(GameOverReason::Crushed);
+                }
+            }
+        }
+
+        if self.state == GameState::Running {
+            let starved: Vec<_> = (0..self.players.len())
+                .filter(|&id| self.players[id].alive && self.starve(id, fed[id]))
+                .map(|id| (id, GameOverReason::Starved))
+                .collect();
+
+            if !starved.is_empty() {
+                if starved[0].0 == 0 {
+                    result.died = true;
+                    result.died_reason = Some(GameOverReason::Starved);
+                }
+                self.kill(&starved);
+                if self.match_over() {
+                    self.end_game(GameOverReason::Starved);
+                }
+            }
+        }
+
+        if self.state == GameState::Running {
+            self.respawn_players();
+        }
+
+        let elapsed_ms = self.started_at.elapsed().as_secs_f64() * 1000.0;
+        let milestone = self.scoring.milestone_length;
+        self.stats.record_tick(self.longest_snake(), elapsed_ms, tick_ms, milestone);
+
+        if self.state == GameState::Running {
+            self.check_campaign_goal();
+        }
+        self.run_clock(tick_ms);
+        if self.state == GameState::Running {
+            if let Some(reason) = rules.end_condition(self) {
+                self.finish(reason);
+            }
+        }
+
+        let tps = self.ticks_per_second();
+        if tps != self.last_tps {
+            self.last_tps = tps;
+            self.emit(GameEvent::SpeedChanged { ticks_per_second: tps });
+        }
+
+        if fps_measurements > 0 {
+            self.counter.tick(fps_measurements);
+        }
+
+        self.step_ghost();
+
+        result
+    }
+
+    // Runs as many fixed-rate ticks as `elapsed_ms` of real time covers. Time
+    // beyond MAX_STEPS_PER_ADVANCE ticks is dropped so a stalled tab
+    // doesn't fast-forward the game.
+    pub fn advance(&mut self, elapsed_ms: f64) -> u32 {
+        self.counter.tick(1);
+
+        let playing = |state| matches!(state, GameState::Running | GameState::Countdown);
+        if !playing(self.state) {
+            self.accumulator_ms = 0.0;
+            return 0;
+        }
+
+        self.accumulator_ms += elapsed_ms.max(0.0);
+        let mut steps = 0;
+
+        while playing(self.state) {
+            let step_ms = self.recommended_frame_delay_ms();
+            if self.accumulator_ms < step_ms {
+                break;
+            }
+            if steps == MAX_STEPS_PER_ADVANCE {
+                self.accumulator_ms = 0.0;
+                break;
+            }
+
+            self.accumulator_ms -= step_ms;
+            self.tick(0);
+            steps += 1;
+        }
+
+        steps
+    }
+
+    pub fn on_click(&mut self, direction: DirectionName) {
+        self.on_click_player(0, direction);
+    }
+
+    pub fn on_click_player(&mut self, player_id: u32, direction: DirectionName) {
+        let snake = match self.players.get_mut(player_id as usize) {
+            Some(player) => &mut player.snake,
+            None => return,
+        };
+
+        match (self.input_mode, direction) {
+            (InputMode::Absolute, direction) => snake.set_direction_name(direction),
+            (InputMode::Relative, DirectionName::Left) => snake.turn_left(),
+            (InputMode::Relative, DirectionName::Right) => snake.turn_right(),
+            (InputMode::Relative, DirectionName::Up | DirectionName::Down) => {}
+        }
+    }
+
+    // Adds another snake and returns its player id. Extra snakes keep their
+    // spawn through `reset`.
+    pub fn add_snake(&mut self, snake: Snake) -> Result<u32, String> {
+        for p in &snake.body {
+            if p.x >= self.width || p.y >= self.height {
+                return Err(format!(
+                    "snake segment ({}, {}) lies outside the {}x{} universe",
+                    p.x, p.y, self.width, self.height
+                ));
+            }
+            let idx = self.get_index(p.y, p.x);
+            let hazard = self.hazards.iter().any(|hazard| hazard.start == *p || hazard.position == *p);
+            if self.walls.contains(idx) || self.occupied.contains(idx) || self.portal_exit(idx).is_some() || hazard {
+                return Err(format!("snake segment ({}, {}) is already taken", p.x, p.y));
+            }
+        }
+
+        self.foods.retain(|food| !snake.body.contains(&food.position));
+        self.players.push(Player::new(snake, self.lives));
+        self.paint_snakes();
+        self.spawn_food();
+        Ok(self.players.len() as u32 - 1)
+    }
+
+    pub fn snake_count(&self) -> u32 {
+        self.players.len() as u32
+    }
+
+    pub fn alive_count(&self) -> u32 {
+        self.players.iter().filter(|player| player.alive).count() as u32
+    }
+
+    pub fn is_alive(&self, player_id: u32) -> bool {
+        self.players.get(player_id as usize).is_some_and(|player| player.alive)
+    }
+
+    pub fn player_score(&self, player_id: u32) -> u32 {
+        self.players.get(player_id as usize).map_or(0, |player| player.score)
+    }
+
+    pub fn player_death_reason(&self, player_id: u32) -> Option<GameOverReason> {
+        self.players.get(player_id as usize).and_then(|player| player.death)
+    }
+
+    pub fn collision_policy(&self) -> CollisionPolicy {
+        self.collision_policy
+    }
+
+    pub fn set_collision_policy(&mut self, policy: CollisionPolicy) {
+        self.collision_policy = policy;
+    }
+
+    // The last snake standing once a multi-snake match is over, or the one
+    // that filled the board.
+    pub fn winner(&self) -> Option<u32> {
+        self.winner
+    }
+
+    pub fn match_mode(&self) -> MatchMode {
+        self.match_mode
+    }
+
+    pub fn set_match_mode(&mut self, mode: MatchMode) {
+        self.match_mode = mode;
+    }
+
+    // Ticks until a downed co-op snake comes back; 0 while it is alive.
+    pub fn respawn_timer(&self, player_id: u32) -> u32 {
+        self.players.get(player_id as usize).map_or(0, |player| player.respawn_in)
+    }
+
+    // Drops turns that would crash on the very next tick.
+    pub fn set_assist(&mut self, enabled: bool) {
+        self.assist = enabled;
+    }
+
+    pub fn assist(&self) -> bool {
+        self.assist
+    }
+
+    pub fn input_mode(&self) -> InputMode {
+        self.input_mode
+    }
+
+    pub fn set_input_mode(&mut self, mode: InputMode) {
+        self.input_mode = mode;
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn on_event(&mut self, callback: js_sys::Function) {
+        self.listeners.push(callback);
+    }
+
+    #[cfg(feature = "wasm")]
+    pub fn clear_event_listeners(&mut self) {
+        self.listeners.clear();
+    }
+
+    pub fn render(&self) -> String {
+        self.to_string()
+    }
+
+    // Paints one `scale`×`scale` block of pixels per cell, ready for `putImageData`.
+    pub fn render_rgba(&mut self, scale: u32) {
+        let scale = scale.max(1);
+        let mut rgba = std::mem::take(&mut self.rgba);
+        self.paint_rgba(scale, &mut rgba);
+        self.rgba = rgba;
+        self.rgba_scale = scale;
+    }
+
+    pub fn rgba(&self) -> *const u8 {
+        self.rgba.as_ptr()
+    }
+
+    pub fn rgba_len(&self) -> usize {
+        self.rgba.len()
+    }
+
+    pub fn rgba_width(&self) -> u32 {
+        self.width * self.rgba_scale
+    }
+
+    pub fn rgba_height(&self) -> u32 {
+        self.height * self.rgba_scale
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // The board one `Cell` a byte. It is kept packed; the byte copy behind
+    // this pointer is made on the first call and kept up to date after.
+    pub fn cells(&self) -> *const Cell {
+        self.cells.as_ptr()
+    }
+
+    // One byte per cell alongside `cells`: the ticks since the cell last
+    // changed, 0 on the tick it did and 255 for long ago. Enough to fade a
+    // trail behind the snake or pulse an apple from when it appeared. Worked
+    // out by the call, so fetch it again each frame.
+    pub fn ages(&self) -> *const u8 {
+        let mut ages = self.ages.borrow_mut();
+        ages.clear();
+        ages.extend(self.changed_at.iter().map(|&tick| self.age_since(tick)));
+        ages.as_ptr()
+    }
+
+    pub fn age(&self, x: u32, y: u32) -> u8 {
+        if x >= self.width || y >= self.height {
+            return u8::MAX;
+        }
+        self.age_since(self.changed_at[self.get_index(y, x)])
+    }
+
+    // A copy of the board, one `Cell` value per byte, for callers that would
+    // rather not read wasm memory directly.
+    pub fn cells_u8(&self) -> Vec<u8> {
+        self.cells.to_bytes()
+    }
+
+    pub fn cells_view(&self) -> CellsView {
+        CellsView {
+            ptr: self.cells.as_ptr(),
+            len: self.cells.len() as u32,
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    pub fn set_wall(&mut self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let idx = self.get_index(y, x);
+        if self.occupied.contains(idx) || self.portal_exit(idx).is_some() {
+            return false;
+        }
+
+        self.walls.insert(idx);
+        self.set_cell(idx, Cell::Wall);
+        self.cover_food(idx);
+        true
+    }
+
+    pub fn clear_wall(&mut self, x: u32, y: u32) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let idx = self.get_index(y, x);
+        if !self.walls.contains(idx) {
+            return false;
+        }
+
+        self.walls.remove(idx);
+        self.set_cell(idx, Cell::Empty);
+        if self.state == GameState::Running {
+            self.spawn_food();
+        }
+
+        true
+    }
+
+    pub fn clear_walls(&mut self) {
+        for idx in 0..self.cells.len() {
+            if self.walls.contains(idx) {
+                self.walls.remove(idx);
+                self.set_cell(idx, Cell::Empty);
+            }
+        }
+
+        if self.state == GameState::Running {
+            self.spawn_food();
+        }
+    }
+
+    pub fn apple_count(&self) -> u32 {
+        self.apple_count
+    }
+
+    // The first food on the board, for frontends that draw a single apple.
+    pub fn apple(&self) -> Option<Position> {
+        self.foods.first().map(|food| food.position)
+    }
+
+    pub fn foods(&self) -> Vec<Food> {
+        self.foods.clone()
+    }
+
+    pub fn set_apple_count(&mut self, count: u32) {
+        self.apple_count = count.max(1);
+
+        while self.foods.len() > self.apple_count as usize {
+            let food = self.foods.pop().unwrap();
+            let idx = self.get_index(food.position.y, food.position.x);
+            self.set_cell(idx, Cell::Empty);
+        }
+        self.spawn_food();
+    }
+
+    pub fn set_special_food(&mut self, enabled: bool) {
+        self.special_food = enabled;
+    }
+
+    pub fn speed_multiplier(&self) -> f64 {
+        let boost = if self.speed_boost > 0 {
+            food::SPEED_BOOST_MULTIPLIER
+        } else {
+            1.0
+        };
+        boost * self.power_ups.speed_factor()
+    }
+
+    pub fn set_speed_curve(&mut self, curve: SpeedCurve, base: f64, step: f64, max: f64) -> Result<(), String> {
+        let speed = SpeedModel { curve, base, step, max };
+        speed.validate()?;
+        self.speed = speed;
+        self.last_tps = self.ticks_per_second();
+        Ok(())
+    }
+
+    pub fn ticks_per_second(&self) -> f64 {
+        self.speed.ticks_per_second(self.stats.apples_eaten) * self.speed_multiplier()
+    }
+
+    pub fn recommended_frame_delay_ms(&self) -> f64 {
+        1000.0 / self.ticks_per_second()
+    }
+
+    // Loses a tail segment every `interval` ticks without food; 0 disables hunger.
+    pub fn set_hunger(&mut self, interval: u32) {
+        self.hunger_interval = interval;
+        for player in &mut self.players {
+            player.hunger_ticks = 0;
+        }
+    }
+
+    pub fn hunger_interval(&self) -> u32 {
+        self.hunger_interval
+    }
+
+    pub fn hunger_timer(&self) -> u32 {
+        self.hunger_interval.saturating_sub(self.players[0].hunger_ticks)
+    }
+
+    pub fn activate_power_up(&mut self, kind: PowerUp, ticks: u32) {
+        self.power_ups.activate(kind, ticks);
+    }
+
+    pub fn has_effect(&self, kind: PowerUp) -> bool {
+        self.power_ups.is_active(kind)
+    }
+
+    pub fn active_effects(&self) -> Vec<ActiveEffect> {
+        self.power_ups.effects().to_vec()
+    }
+
+    pub fn is_wall(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.walls.contains(self.get_index(y, x))
+    }
+
+    pub fn changed_indices(&self) -> Vec<u32> {
+        self.dirty.clone()
+    }
+
+    // Flat `[index, cell, index, cell, ...]` pairs for cells touched by the last tick.
+    pub fn changed_cells(&self) -> Vec<u32> {
+        self.dirty
+            .iter()
+            .flat_map(|&idx| [idx, self.cells.get(idx as usize) as u32])
+            .collect()
+    }
+
+    pub fn snake_length(&self) -> u32 {
+        self.players[0].snake.body.len() as u32
+    }
+
+    pub fn snake_head(&self) -> Position {
+        *self.head()
+    }
+
+    // Where the snake is headed, e.g. to rotate the head sprite.
+    pub fn heading(&self) -> DirectionName {
+        self.players[0].snake.direction_name()
+    }
+
+    pub fn player_heading(&self, player_id: u32) -> Option<DirectionName> {
+        self.players.get(player_id as usize).map(|player| player.snake.direction_name())
+    }
+
+    // Head first, as flat [x, y, x, y, ...] pairs.
+    pub fn snake_positions(&self) -> Vec<u32> {
+        self.player_positions(0)
+    }
+
+    // Like `snake_positions` for any player; empty for an unknown id.
+    pub fn player_positions(&self, player_id: u32) -> Vec<u32> {
+        self.players
+            .get(player_id as usize)
+            .map_or_else(Vec::new, |player| player.snake.body.iter().flat_map(|p| [p.x, p.y]).collect())
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        matches!(self.state, GameState::GameOver | GameState::Victory | GameState::Finished)
+    }
+
+    pub fn is_victory(&self) -> bool {
+        self.state == GameState::Victory
+    }
+
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.game_over_reason
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn start(&mut self) {
+        if matches!(self.state, GameState::Menu | GameState::Countdown) {
+            self.state = GameState::Running;
+            self.countdown_ms = 0.0;
+        }
+    }
+
+    pub fn pause(&mut self) {
+        if self.state == GameState::Running {
+            self.state = GameState::Paused;
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if self.state == GameState::Paused {
+            self.state = GameState::Running;
+        }
+    }
+
+    pub fn score(&self) -> u32 {
+        self.stats.score
+    }
+
+    pub fn stats_json(&self) -> String {
+        self.stats.to_json()
+    }
+
+    pub fn elapsed_ticks(&self) -> u32 {
+        self.stats.ticks
+    }
+
+    // Game time, which only runs while ticks do: a pause stops the clock
+    // and a stalled tab doesn't skip it ahead.
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.stats.game_ms / 1000.0
+    }
+
+    // `elapsed_seconds` when the longest snake first reached each multiple
+    // of `Scoring::milestone_length`, for speedrun splits. None with
+    // milestones turned off.
+    pub fn split_times(&self) -> Vec<f64> {
+        self.stats.splits.iter().map(|ms| ms / 1000.0).collect()
+    }
+
+    pub fn fps(&self) -> f64 {
+        self.counter.fps
+    }
+
+    pub fn perf_stats(&self) -> PerfStats {
+        self.counter.perf_stats()
+    }
+
+    // For frontends that draw on their own; `GameLoop` times its render
+    // callback itself.
+    pub fn record_render_ms(&mut self, ms: f64) {
+        self.counter.record_render(ms);
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn wraps_x(&self) -> bool {
+        self.topology.wraps_x()
+    }
+
+    pub fn wraps_y(&self) -> bool {
+        self.topology.wraps_y()
+    }
+
+    pub fn set_wrap(&mut self, wrap_x: bool, wrap_y: bool) {
+        self.topology = UniverseTopology::from_wrap(wrap_x, wrap_y);
+    }
+
+    // Flat goes toroidal, anything else goes flat.
+    pub fn toggle_topology(&mut self) {
+        self.topology = match self.topology {
+            UniverseTopology::Flat => UniverseTopology::Toroidal,
+            _ => UniverseTopology::Flat,
+        };
+    }
+
+    fn end_game(&mut self, reason: GameOverReason) -> TickResult {
+        self.state = GameState::GameOver;
+        self.game_over_reason = Some(reason);
+        self.winner = self.players.iter().position(|player| player.alive).map(|id| id as u32);
+        self.emit(GameEvent::GameOver { reason });
+        TickResult::fatal(self.head(), reason)
+    }
+
+    fn win(&mut self, id: usize) {
+        self.state = GameState::Victory;
+        self.winner = Some(id as u32);
+        self.game_over_reason = Some(GameOverReason::BoardFull);
+        self.emit(GameEvent::GameOver {
+            reason: GameOverReason::BoardFull,
+        });
+    }
+
+    fn head(&self) -> &Position {
+        self.players[0].snake.body.front().unwrap()
+    }
+
+    fn longest_snake(&self) -> u32 {
+        self.players.iter().map(|player| player.snake.body.len() as u32).max().unwrap_or(0)
+    }
+
+    // With assist on, a queued turn straight into a wall or the snake's own
+    // body is dropped and the snake carries on as it was.
+    fn apply_turn(&mut self, id: usize) {
+        let turn = match self.players[id].snake.pending.pop_front() {
+            Some(turn) => turn,
+            None => return,
+        };
+        if self.assist && self.step_towards(id, turn).is_err() {
+            return;
+        }
+        if self.players[id].snake.direction != turn {
+            self.cue(Sound::Turn, id, 1.0, 0);
+        }
+        self.players[id].snake.direction = turn;
+        if let Some(recording) = &mut self.recording {
+            recording.record_turn(id as u32, turn.name());
+        }
+    }
+
+    // Where player `id` moves this tick, or why it can't; None once it is dead.
+    fn next_head(&self, id: usize) -> Option<Result<Position, GameOverReason>> {
+        let player = &self.players[id];
+        if !player.alive {
+            return None;
+        }
+        Some(self.step_towards(id, player.snake.direction))
+    }
+
+    // Wall and self collisions for one step; other snakes are left to the
+    // collision policy.
+    fn step_towards(&self, id: usize, direction: Direction) -> Result<Position, GameOverReason> {
+        let player = &self.players[id];
+        let head = player.snake.body.front().unwrap();
+        let ghost = self.power_ups.is_active(PowerUp::Ghost);
+
+        // A portal puts the head on the cell past its other end. Portals
+        // don't chain: coming out straight into another one is a crash.
+        let mut new_head = self.step_from(head, direction, ghost)?;
+        if let Some(exit) = self.portal_exit(self.get_index(new_head.y, new_head.x)) {
+            let exit = Position {
+                x: exit as u32 % self.width,
+                y: exit as u32 / self.width,
+            };
+            new_head = self.step_from(&exit, direction, ghost)?;
+        }
+
+        let new_idx = self.get_index(new_head.y, new_head.x);
+        if (self.walls.contains(new_idx) && !ghost) || self.portal_exit(new_idx).is_some() {
+            return Err(GameOverReason::HitObstacle);
+        }
+        if self.cells.get(new_idx) == Cell::Hazard {
+            return Err(GameOverReason::HitHazard);
+        }
+        if self.occupied.contains(new_idx)
+            && !self.power_ups.is_active(PowerUp::Invincible)
+            && player.snake.body.contains(&new_head)
+        {
+            return Err(GameOverReason::HitSelf);
+        }
+
+        Ok(new_head)
+    }
+
+    // Leaving through an edge that doesn't wrap hits the wall, unless the
+    // ghost power-up carries the snake across anyway.
+    fn step_from(&self, from: &Position, direction: Direction, ghost: bool) -> Result<Position, GameOverReason> {
+        let new_x = from.x as i32 + direction.vx;
+        let new_y = from.y as i32 + direction.vy;
+        let outside_x = new_x < 0 || new_x >= self.width as i32;
+        let outside_y = new_y < 0 || new_y >= self.height as i32;
+        if ((outside_x && !self.topology.wraps_x()) || (outside_y && !self.topology.wraps_y())) && !ghost {
+            return Err(GameOverReason::HitWall);
+        }
+
+        Ok(Position {
+            x: self.add_u32_i32(from.x, direction.vx, self.width),
+            y: self.add_u32_i32(from.y, direction.vy, self.height),
+        })
+    }
+
+    // (rammer, victim) pairs for heads moving into another snake's body or
+    // onto the same cell as another head.
+    fn snake_contacts(&self, planned: &[Option<Result<Position, GameOverReason>>]) -> Vec<(usize, usize)> {
+        if self.power_ups.is_active(PowerUp::Invincible) || self.collision_policy == CollisionPolicy::PassThrough {
+            return Vec::new();
+        }
+
+        let mut contacts = Vec::new();
+        for (rammer, target) in planned.iter().enumerate() {
+            let head = match target {
+                Some(Ok(head)) => head,
+                _ => continue,
+            };
+            let occupied = self.occupied.contains(self.get_index(head.y, head.x));
+
+            for (victim, player) in self.players.iter().enumerate() {
+                if victim == rammer || !player.alive {
+                    continue;
+                }
+                let head_on = planned[victim] == Some(Ok(*head));
+                if head_on || (occupied && player.snake.body.contains(head)) {
+                    contacts.push((rammer, victim));
+                }
+            }
+        }
+        contacts
+    }
+
+    // A lone snake plays until it dies; a versus match ends with one survivor.
+    fn match_over(&self) -> bool {
+        let alive = self.alive_count();
+        alive == 0 || (self.match_mode == MatchMode::Versus && self.players.len() > 1 && alive == 1)
+    }
+
+    // Counts down co-op respawn timers. A snake with nowhere safe to appear
+    // tries again on the next tick.
+    fn respawn_players(&mut self) {
+        for id in 0..self.players.len() {
+            let player = &mut self.players[id];
+            if player.alive || player.respawn_in == 0 {
+                continue;
+            }
+
+            player.respawn_in -= 1;
+            if player.respawn_in > 0 {
+                continue;
+            }
+
+            let length = player.spawn.body.len() as u32;
+            match self.find_spawn(length) {
+                Some(snake) => self.bring_back(id, snake),
+                None => self.players[id].respawn_in = 1,
+            }
+        }
+    }
+
+    fn bring_back(&mut self, id: usize, snake: Snake) {
+        let player = &mut self.players[id];
+        player.snake = snake;
+        player.alive = true;
+        player.death = None;
+        player.hunger_ticks = 0;
+        player.spawned_at = self.stats.ticks + 1;
+        self.paint_snakes();
+    }
+
+    // A straight run of empty cells for the body, plus a few more ahead of
+    // the head so the snake doesn't respawn straight into a crash.
+    fn find_spawn(&mut self, length: u32) -> Option<Snake> {
+        let headings = [
+            DirectionName::Right,
+            DirectionName::Left,
+            DirectionName::Down,
+            DirectionName::Up,
+        ];
+        let len = self.cells.len() as u32;
+        let start = self.rng.below(len);
+
+        for offset in 0..len {
+            let idx = (start + offset) % len;
+            let head = Position {
+                x: idx % self.width,
+                y: idx / self.width,
+            };
+            for &heading in &headings {
+                let direction = Direction::from_name(heading);
+                if (1 - length as i32..=SPAWN_CLEARANCE).all(|steps| self.is_empty_along(&head, &direction, steps)) {
+                    return Some(Snake::spawn(&head, direction, length));
+                }
+            }
+        }
+        None
+    }
+
+    fn is_empty_along(&self, from: &Position, direction: &Direction, steps: i32) -> bool {
+        let x = from.x as i32 + direction.vx * steps;
+        let y = from.y as i32 + direction.vy * steps;
+
+        x >= 0
+            && y >= 0
+            && x < self.width as i32
+            && y < self.height as i32
+            && self.cells.get(self.get_index(y as u32, x as u32)) == Cell::Empty
+    }
+
+    fn move_snake(&mut self, id: usize, new_head: Position) -> Option<Food> {
+        let new_idx = self.get_index(new_head.y, new_head.x);
+        let eaten = self
+            .foods
+            .iter()
+            .position(|food| food.position == new_head)
+            .map(|i| self.foods.swap_remove(i));
+
+        if let Some(food) = eaten {
+            self.burst(id, food.kind, food.position);
+            self.eat(id, food.kind);
+        }
+
+        if !eaten.is_some_and(|food| food.kind.grows()) {
+            self.pop_tail(id);
+        }
+
+        let neck = *self.players[id].snake.body.front().unwrap();
+        let neck_idx = self.get_index(neck.y, neck.x);
+        self.set_cell(neck_idx, Cell::SnakeBody);
+
+        if self.occupied.contains(new_idx) {
+            self.overlaps += 1;
+        }
+        self.players[id].snake.body.push_front(new_head);
+        self.set_cell(new_idx, Cell::SnakeHead);
+        self.occupied.insert(new_idx);
+
+        if let Some(food) = eaten {
+            if food.kind == FoodKind::Poison {
+                for _ in 0..food::POISON_SHRINK {
+                    if self.players[id].snake.body.len() > 2 {
+                        self.pop_tail(id);
+                    }
+                }
+            }
+
+            self.spawn_food();
+            if self.foods.is_empty() {
+                self.win(id);
+            }
+        }
+
+        eaten
+    }
+
+    // Corpses are cleared while anyone is still playing; the last snakes to
+    // die stay on the board.
+    fn kill(&mut self, deaths: &[(usize, GameOverReason)]) {
+        let mut revived = Vec::new();
+        for &(id, reason) in deaths {
+            self.flash_death(id, reason);
+            self.cue(Sound::Die, id, 1.0, 0);
+            self.players[id].alive = false;
+            self.players[id].death = Some(reason);
+            if self.lose_life(id) {
+                revived.push(id);
+            } else if self.match_mode == MatchMode::Coop {
+                self.players[id].respawn_in = RESPAWN_DELAY_TICKS;
+            }
+        }
+
+        if self.alive_count() == 0 && revived.is_empty() {
+            return;
+        }
+
+        for &(id, _) in deaths {
+            let body: Vec<usize> = self.players[id]
+                .snake
+                .body
+                .iter()
+                .map(|p| self.get_index(p.y, p.x))
+                .collect();
+            for idx in body {
+                self.set_cell(idx, self.background(idx));
+                self.occupied.remove(idx);
+            }
+        }
+        self.paint_snakes();
+        for id in revived {
+            self.revive(id);
+        }
+    }
+
+    // Native builds have no JS listeners, so events only reach the narration
+    // there.
+    #[cfg(feature = "wasm")]
+    fn emit(&mut self, event: GameEvent) {
+        if self.silent {
+            return;
+        }
+        self.narrate(event);
+        if self.listeners.is_empty() {
+            return;
+        }
+
+        let payload = event.to_js();
+        for listener in &self.listeners {
+            let _ = listener.call1(&JsValue::NULL, &payload);
+        }
+    }
+
+    #[cfg(not(feature = "wasm"))]
+    fn emit(&mut self, event: GameEvent) {
+        if !self.silent {
+            self.narrate(event);
+        }
+    }
+
+    fn set_cell(&mut self, idx: usize, cell: Cell) {
+        if self.cells.get(idx) == cell {
+            return;
+        }
+
+        match (self.cells.get(idx), cell) {
+            (Cell::Empty, _) => self.free.remove(idx as u32),
+            (_, Cell::Empty) => self.free.insert(idx as u32),
+            _ => {}
+        }
+        let layout = |cell| matches!(cell, Cell::Wall | Cell::Portal);
+        if layout(self.cells.get(idx)) || layout(cell) {
+            self.board_edits = self.board_edits.wrapping_add(1);
+        }
+
+        self.cells.set(idx, cell);
+        self.changed_at[idx] = self.age_clock;
+        if !self.dirty_mark.contains(idx) {
+            self.dirty_mark.insert(idx);
+            self.dirty.push(idx as u32);
+        }
+    }
+
+    fn age_since(&self, tick: u32) -> u8 {
+        self.age_clock.wrapping_sub(tick).min(u8::MAX as u32) as u8
+    }
+
+    // Change ticks for a new board of `len` cells, all long past.
+    pub(crate) fn unchanged_cells(&self, len: usize) -> Vec<u32> {
+        vec![self.age_clock.wrapping_sub(u8::MAX as u32); len]
+    }
+
+    fn clear_dirty(&mut self) {
+        for &idx in &self.dirty {
+            self.dirty_mark.remove(idx as usize);
+        }
+        self.dirty.clear();
+    }
+
+    fn paint_snakes(&mut self) {
+        let bodies: Vec<Vec<usize>> = self
+            .players
+            .iter()
+            .filter(|player| player.alive)
+            .map(|player| player.snake.body.iter().map(|p| self.get_index(p.y, p.x)).collect())
+            .collect();
+
+        for indices in bodies {
+            for (i, idx) in indices.into_iter().enumerate() {
+                self.set_cell(idx, if i == 0 { Cell::SnakeHead } else { Cell::SnakeBody });
+                self.occupied.insert(idx);
+            }
+        }
+    }
+
+    fn get_index(&self, row: u32, column: u32) -> usize {
+        (row * self.width + column) as usize
+    }
+
+    fn add_u32_i32(&self, u: u32, i: i32, modulo: u32) -> u32 {
+        (u as i64 + i as i64).rem_euclid(modulo as i64) as u32
+    }
+
+    // Tops the board up to `apple_count` food items while free cells remain.
+    fn spawn_food(&mut self) {
+        self.profiled(Section::FoodSpawn, Universe::fill_food);
+    }
+
+    fn fill_food(&mut self) {
+        let rules = self.rules();
+        while self.foods.len() < self.apple_count as usize {
+            let mut idx = match self.free.sample(&mut self.rng) {
+                Some(idx) => idx as usize,
+                None => break,
+            };
+            let proposed = Position {
+                x: idx as u32 % self.width,
+                y: idx as u32 / self.width,
+            };
+            if let Some(p) = rules.on_food_spawn(self, proposed) {
+                if p.x < self.width && p.y < self.height && self.cells.get(self.get_index(p.y, p.x)) == Cell::Empty {
+                    idx = self.get_index(p.y, p.x);
+                }
+            }
+            let kind = if self.special_food {
+                FoodKind::roll(&mut self.rng)
+            } else {
+                FoodKind::Normal
+            };
+            self.place_food(idx, kind);
+        }
+    }
+
+    fn eat(&mut self, id: usize, kind: FoodKind) {
+        let combo = if kind.grows() { self.chain_apple(id) } else { 1 };
+        let points = self.apple_points(self.rules().on_apple_eaten(self, id as u32, kind));
+        let points = points.saturating_mul(self.power_ups.score_multiplier()).saturating_mul(combo);
+        self.stats.record_food(kind, points);
+        self.cue(Sound::Eat, id, self.eat_pitch(kind), 0);
+        self.players[id].score = self.players[id].score.saturating_add(points);
+        if kind.grows() {
+            if let Some(campaign) = &mut self.campaign {
+                campaign.record_apple();
+            }
+        }
+        match kind {
+            FoodKind::Speed => self.speed_boost = food::SPEED_BOOST_TICKS,
+            FoodKind::PowerUp => {
+                let power_up = PowerUp::roll(&mut self.rng);
+                self.power_ups.activate(power_up, powerups::POWER_UP_TICKS);
+            }
+            _ => {}
+        }
+        self.emit(GameEvent::AppleEaten { score: self.stats.score });
+    }
+
+    // Segments may overlap while invincible, so a vacated cell is only
+    // cleared once no other segment still covers it.
+    fn pop_tail(&mut self, id: usize) {
+        let tail = self.players[id].snake.body.pop_back().unwrap();
+        let covered = |player: &Player| player.alive && player.snake.body.contains(&tail);
+        if self.overlaps > 0 && self.players.iter().any(covered) {
+            self.overlaps -= 1;
+            return;
+        }
+
+        let tail_idx = self.get_index(tail.y, tail.x);
+        self.set_cell(tail_idx, self.background(tail_idx));
+        self.occupied.remove(tail_idx);
+    }
+
+    // Returns true when hunger has shrunk the snake down to its head.
+    fn starve(&mut self, id: usize, fed: bool) -> bool {
+        let interval = self.hunger_interval;
+        let player = &mut self.players[id];
+        if interval == 0 || fed {
+            player.hunger_ticks = 0;
+            return false;
+        }
+
+        player.hunger_ticks += 1;
+        if player.hunger_ticks < interval {
+            return false;
+        }
+
+        player.hunger_ticks = 0;
+        self.pop_tail(id);
+        self.players[id].snake.body.len() <= 1
+    }
+
+    fn background(&self, idx: usize) -> Cell {
+        if self.walls.contains(idx) {
+            Cell::Wall
+        } else if self.portal_exit(idx).is_some() {
+            Cell::Portal
+        } else {
+            Cell::Empty
+        }
+    }
+
+    // Drops food that a wall or portal was just placed over and spawns a
+    // replacement.
+    fn cover_food(&mut self, idx: usize) {
+        if let Some(covered) = self.foods.iter().position(|food| self.get_index(food.position.y, food.position.x) == idx) {
+            self.foods.swap_remove(covered);
+            self.spawn_food();
+        }
+    }
+
+    fn age_food(&mut self) {
+        let mut expired = false;
+        let mut i = 0;
+        while i < self.foods.len() {
+            if self.foods[i].age() {
+                let food = self.foods.swap_remove(i);
+                let idx = self.get_index(food.position.y, food.position.x);
+                self.set_cell(idx, Cell::Empty);
+                expired = true;
+            } else {
+                i += 1;
+            }
+        }
+
+        if expired {
+            self.spawn_food();
+        }
+    }
+}
+
+impl Universe {
+    pub fn topology(&self) -> UniverseTopology {
+        self.topology
+    }
+
+    pub fn set_topology(&mut self, topology: UniverseTopology) {
+        self.topology = topology;
+    }
+
+    pub fn from_level(level: &Level, seed: u64) -> Result<Universe, String> {
+        level.validate()?;
+        let snake = level.snake()?;
+        let mut universe = Universe::new_with_seed(snake, DEFAULT_FPS_TARGET, level.width, level.height, seed)?;
+        universe.apply_layout(level);
+        Ok(universe)
+    }
+
+    // Swaps the board for `level` in place; score and stats carry over.
+    pub fn load_level(&mut self, level: &Level) -> Result<(), String> {
+        let len = level.validate()?;
+        let snake = level.snake()?;
+
+        self.width = level.width;
+        self.height = level.height;
+        self.cells = CellGrid::new(level.width, level.height);
+        self.changed_at = self.unchanged_cells(len);
+        self.free = FreeCells::full(len);
+        self.occupied = BitSet::new(len);
+        self.walls = BitSet::new(len);
+        self.portals.clear();
+        self.hazards.clear();
+        self.terrain.clear();
+        self.board_edits = self.board_edits.wrapping_add(1);
+        self.shrink = Shrink::new(self.shrink.interval);
+        self.dirty.clear();
+        self.dirty_mark = BitSet::new(len);
+        self.players = vec![Player::new(snake, self.lives)];
+        self.foods.clear();
+
+        self.paint_snakes();
+        self.spawn_food();
+        self.apply_layout(level);
+        Ok(())
+    }
+
+    fn apply_layout(&mut self, level: &Level) {
+        for &idx in &level.walls {
+            self.set_wall(idx % level.width, idx / level.width);
+        }
+
+        if let Some(apple) = &level.apple {
+            let idx = self.get_index(apple.y, apple.x);
+            if self.cells.get(idx) == Cell::Empty {
+                if let Some(random) = self.foods.pop() {
+                    let random_idx = self.get_index(random.position.y, random.position.x);
+                    self.set_cell(random_idx, Cell::Empty);
+                }
+                self.place_food(idx, FoodKind::Normal);
+            }
+        }
+    }
+
+    fn check_campaign_goal(&mut self) {
+        let completed = match &self.campaign {
+            Some(campaign) => campaign
+                .goal()
+                .is_met(self.players[0].snake.body.len() as u32, campaign.apples())
+                .then(|| campaign.current_level() as u32),
+            None => None,
+        };
+
+        if let Some(level) = completed {
+            self.emit(GameEvent::LevelComplete { level });
+            self.cue(Sound::LevelUp, 0, 1.0, level);
+            if !self.advance_level() {
+                self.state = GameState::Victory;
+                self.game_over_reason = Some(GameOverReason::CampaignComplete);
+                self.emit(GameEvent::GameOver {
+                    reason: GameOverReason::CampaignComplete,
+                });
+            }
+        }
+    }
+
+    fn place_food(&mut self, idx: usize, kind: FoodKind) {
+        let position = Position {
+            x: idx as u32 % self.width,
+            y: idx as u32 / self.width,
+        };
+        self.set_cell(idx, kind.cell());
+        self.foods.push(Food::new(position, kind));
+    }
+}
+
+// === Traits ===
+impl fmt::Display for Universe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (idx, cell) in self.cells.iter().enumerate() {
+            write!(f, "{}", self.theme.glyph(cell))?;
+            if (idx + 1).is_multiple_of(self.width as usize) {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// === Utility ===
+#[cfg(feature = "wasm")]
 #[wasm_bindgen]
 pub fn random_position(max: i32) -> i32 {
     (random() * (max as f64)).floor() as i32
 }
+
+#[cfg(feature = "wasm")]
+fn random_seed() -> u64 {
+    let high = (random() * 4_294_967_296.0) as u64;
+    let low = (random() * 4_294_967_296.0) as u64;
+    (high << 32) | low
+}
+
+// std's hasher keys are randomized per process, which is all a default seed needs.
+#[cfg(not(feature = "wasm"))]
+fn random_seed() -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    RandomState::new().build_hasher().finish()
+}
@@ -0,0 +1,110 @@
+use crate::{Cell, Direction, DirectionName, Position, Snake, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// A snake that still has lives comes back at this length.
+pub const RESPAWN_LENGTH: u32 = 4;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Player 0's lives left, counting the one being played; 0 once it is out.
+    pub fn lives(&self) -> u32 {
+        self.players[0].lives
+    }
+
+    pub fn player_lives(&self, player_id: u32) -> u32 {
+        self.players.get(player_id as usize).map_or(0, |player| player.lives)
+    }
+
+    // Every snake starts each game with `lives`, at least one. A death
+    // costs a life and the snake respawns right away somewhere safe; the
+    // game only ends as usual once a snake loses its last one.
+    pub fn set_lives(&mut self, lives: u32) {
+        self.lives = lives.max(1);
+        for player in &mut self.players {
+            player.lives = self.lives;
+        }
+    }
+}
+
+impl Universe {
+    // Takes a life from a snake that just died; true if it had one to spare.
+    pub(crate) fn lose_life(&mut self, id: usize) -> bool {
+        let player = &mut self.players[id];
+        player.lives = player.lives.saturating_sub(1);
+        player.lives > 0
+    }
+
+    // Brings a dead snake back where it has the longest clear run ahead. With
+    // no room anywhere it waits for the next tick like a co-op respawn.
+    pub(crate) fn revive(&mut self, id: usize) {
+        match self.find_safe_spawn(RESPAWN_LENGTH) {
+            Some(snake) => self.bring_back(id, snake),
+            None => self.players[id].respawn_in = 1,
+        }
+    }
+
+    pub(crate) fn find_safe_spawn(&self, length: u32) -> Option<Snake> {
+        // Opposite headings sit side by side, so `h ^ 1` turns around.
+        let headings = [
+            DirectionName::Right,
+            DirectionName::Left,
+            DirectionName::Down,
+            DirectionName::Up,
+        ];
+        let runs: Vec<Vec<u32>> = headings
+            .iter()
+            .map(|&heading| self.empty_runs(Direction::from_name(heading)))
+            .collect();
+        let mut best: Option<(u32, usize, usize)> = None;
+
+        for (h, ahead) in runs.iter().enumerate() {
+            for (idx, &run) in ahead.iter().enumerate() {
+                // The body trails back from the head, which leaves the run
+                // ahead of it less the head's own cell.
+                if runs[h ^ 1][idx] < length {
+                    continue;
+                }
+                let run = run.saturating_sub(1);
+                if best.is_none_or(|(longest, _, _)| run > longest) {
+                    best = Some((run, idx, h));
+                }
+            }
+        }
+
+        best.map(|(_, idx, h)| {
+            let head = Position {
+                x: idx as u32 % self.width,
+                y: idx as u32 / self.width,
+            };
+            Snake::spawn(&head, Direction::from_name(headings[h]), length)
+        })
+    }
+
+    // How many empty cells in a row start at each cell going `direction`,
+    // the cell itself included. Walking the board against `direction` means
+    // the next cell along has always been counted already.
+    fn empty_runs(&self, direction: Direction) -> Vec<u32> {
+        let len = self.cells.len();
+        let (width, height) = (self.width as i32, self.height as i32);
+        let backwards = direction.vx + direction.vy > 0;
+        let mut runs = vec![0; len];
+
+        for i in 0..len {
+            let idx = if backwards { len - 1 - i } else { i };
+            if self.cells.get(idx) != Cell::Empty {
+                continue;
+            }
+
+            let x = (idx as u32 % self.width) as i32 + direction.vx;
+            let y = (idx as u32 / self.width) as i32 + direction.vy;
+            let next = if x >= 0 && y >= 0 && x < width && y < height {
+                runs[(y * width + x) as usize]
+            } else {
+                0
+            };
+            runs[idx] = next + 1;
+        }
+        runs
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{Cell, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Which cell a minimap pixel shows when its patch of board holds several:
+// snakes over food over hazards and portals over walls over open floor.
+fn rank(cell: Cell) -> u8 {
+    match cell {
+        Cell::Empty => 0,
+        Cell::Ghost => 1,
+        Cell::Wall => 2,
+        Cell::Portal => 3,
+        Cell::Hazard => 4,
+        Cell::Poison => 5,
+        Cell::Apple => 6,
+        Cell::Speed | Cell::PowerUp => 7,
+        Cell::Golden => 8,
+        Cell::SnakeBody => 9,
+        Cell::SnakeHead => 10,
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // The board shrunk to `width` x `height`, one `Cell` a byte, row by row.
+    // Each byte stands for a patch of the board and keeps whatever in it
+    // matters most, so a lone apple or a snake's head never drops out.
+    pub fn minimap(&self, width: u32, height: u32) -> Vec<u8> {
+        let span = |i: u32, size: u32, scaled: u32| {
+            let start = (i as u64 * size as u64 / scaled as u64) as u32;
+            let end = ((i as u64 + 1) * size as u64 / scaled as u64) as u32;
+            start..end.max(start + 1)
+        };
+
+        let mut minimap = Vec::with_capacity(width as usize * height as usize);
+        for my in 0..height {
+            let rows = span(my, self.height, height);
+            for mx in 0..width {
+                let columns = span(mx, self.width, width);
+                let cell = rows
+                    .clone()
+                    .flat_map(|y| columns.clone().map(move |x| (x, y)))
+                    .map(|(x, y)| self.cells.get(self.get_index(y, x)))
+                    .max_by_key(|&cell| rank(cell))
+                    .unwrap_or(Cell::Empty);
+                minimap.push(cell as u8);
+            }
+        }
+        minimap
+    }
+}
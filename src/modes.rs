@@ -0,0 +1,156 @@
+use crate::food::FoodKind;
+use crate::rules::{Collision, Rules};
+use crate::sounds::{Sound, COUNTDOWN_SECONDS};
+use crate::{DirectionName, GameOverReason, GameState, Position, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// How a match is won besides clearing the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameMode {
+    // Play until every snake is dead.
+    Classic,
+    // The match ends after `seconds` of game time; the score is apples eaten.
+    Timed { seconds: u32 },
+    // Nothing kills: a snake about to crash turns aside or waits, for small kids.
+    Zen,
+}
+
+impl GameMode {
+    fn time_limit_ms(self) -> Option<f64> {
+        match self {
+            GameMode::Classic | GameMode::Zen => None,
+            GameMode::Timed { seconds } => Some(seconds as f64 * 1000.0),
+        }
+    }
+
+    // A short name for tagging scores: "classic", "zen" or "timed-60".
+    pub fn tag(self) -> String {
+        match self {
+            GameMode::Classic => "classic".to_string(),
+            GameMode::Timed { seconds } => format!("timed-{}", seconds),
+            GameMode::Zen => "zen".to_string(),
+        }
+    }
+
+    pub(crate) fn rules(self) -> Rc<dyn Rules> {
+        match self {
+            GameMode::Classic => Rc::new(Classic),
+            GameMode::Timed { .. } => Rc::new(Timed),
+            GameMode::Zen => Rc::new(Zen),
+        }
+    }
+}
+
+struct Classic;
+
+impl Rules for Classic {}
+
+struct Timed;
+
+impl Rules for Timed {
+    fn on_apple_eaten(&self, _universe: &Universe, _player: u32, kind: FoodKind) -> u32 {
+        kind.grows() as u32
+    }
+
+    fn end_condition(&self, universe: &Universe) -> Option<GameOverReason> {
+        (universe.time_left_ms <= 0.0).then_some(GameOverReason::TimeUp)
+    }
+}
+
+// A snake heading into a wall, itself or a hazard slides along it instead,
+// trying a left turn before a right one, and waits where it is when boxed in.
+struct Zen;
+
+impl Rules for Zen {
+    fn on_head_computed(
+        &self,
+        universe: &Universe,
+        player: u32,
+        head: Result<Position, GameOverReason>,
+    ) -> Option<DirectionName> {
+        head.err()?;
+        let id = player as usize;
+        let heading = universe.players[id].snake.direction;
+        [heading.rotated_left(), heading.rotated_right()]
+            .iter()
+            .find(|&&direction| universe.step_towards(id, direction).is_ok())
+            .map(|direction| direction.name())
+    }
+
+    fn on_collision(&self, _universe: &Universe, _player: u32, _reason: GameOverReason) -> Collision {
+        Collision::Stop
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Switches to a timed match of `seconds`, or back to classic with 0. The
+    // clock starts over.
+    pub fn set_time_limit(&mut self, seconds: u32) {
+        self.set_game_mode(if seconds == 0 {
+            GameMode::Classic
+        } else {
+            GameMode::Timed { seconds }
+        });
+    }
+
+    // Zen on, or back to classic.
+    pub fn set_zen(&mut self, enabled: bool) {
+        self.set_game_mode(if enabled { GameMode::Zen } else { GameMode::Classic });
+    }
+
+    pub fn is_zen(&self) -> bool {
+        self.mode == GameMode::Zen
+    }
+
+    pub fn mode_tag(&self) -> String {
+        self.mode.tag()
+    }
+
+    // For the HUD; None outside timed mode. Ticks are counted at the current
+    // speed, so they may stretch or shrink as the snake speeds up.
+    pub fn time_remaining_ticks(&self) -> Option<u32> {
+        self.mode.time_limit_ms()?;
+        Some((self.time_left_ms / self.recommended_frame_delay_ms()).ceil() as u32)
+    }
+
+    pub fn time_remaining_seconds(&self) -> Option<f64> {
+        self.mode.time_limit_ms()?;
+        Some(self.time_left_ms / 1000.0)
+    }
+}
+
+impl Universe {
+    pub fn game_mode(&self) -> GameMode {
+        self.mode
+    }
+
+    pub fn set_game_mode(&mut self, mode: GameMode) {
+        self.mode = mode;
+        self.restart_clock();
+    }
+
+    pub(crate) fn restart_clock(&mut self) {
+        self.time_left_ms = self.mode.time_limit_ms().unwrap_or(0.0);
+    }
+
+    // Takes one tick of `tick_ms` off the clock. Game time, not wall-clock
+    // time, so peers and replays agree.
+    pub(crate) fn run_clock(&mut self, tick_ms: f64) {
+        if self.mode.time_limit_ms().is_none() || self.state != GameState::Running {
+            return;
+        }
+
+        let before = (self.time_left_ms / 1000.0).ceil() as u32;
+        self.time_left_ms = (self.time_left_ms - tick_ms).max(0.0);
+        let after = (self.time_left_ms / 1000.0).ceil() as u32;
+        if after < before && (1..=COUNTDOWN_SECONDS).contains(&after) {
+            self.cue(Sound::Countdown, 0, 1.0, after);
+        }
+    }
+}
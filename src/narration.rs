@@ -0,0 +1,169 @@
+use crate::food::FoodKind;
+use crate::{Cell, Direction, GameEvent, GameOverReason, Position, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Lines are dropped oldest first past this, like `effects::MAX_QUEUED_EFFECTS`.
+pub const MAX_QUEUED_NARRATION: usize = 64;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // A short summary of player 0's surroundings for a screen reader, e.g.
+    // "Length 12, score 4, apple 2 cells ahead and 5 cells to the left, wall
+    // 3 cells ahead". Directions are from the snake's point of view.
+    pub fn describe(&self) -> String {
+        let snake = &self.players[0].snake;
+        let head = *snake.body.front().unwrap();
+        let mut parts = vec![
+            format!("Length {}", snake.body.len()),
+            format!("score {}", self.players[0].score),
+        ];
+
+        let nearest = self.foods.iter().min_by_key(|food| {
+            let (forward, right) = self.bearing(head, food.position, snake.direction);
+            forward.abs() + right.abs()
+        });
+        if let Some(food) = nearest {
+            let (forward, right) = self.bearing(head, food.position, snake.direction);
+            parts.push(format!("{} {}", food_name(food.kind), directions(forward, right)));
+        }
+        parts.push(self.look_ahead(head, snake.direction));
+
+        if let Some(reason) = self.game_over_reason {
+            parts.push(format!("game over, {}", reason_text(reason)));
+        }
+        parts.join(", ")
+    }
+
+    pub fn narration(&self) -> bool {
+        self.narration.is_some()
+    }
+
+    // With narration on, game events are also put into words, e.g. "Apple
+    // eaten, score 3", for `take_narration` to read out.
+    pub fn set_narration(&mut self, enabled: bool) {
+        if enabled != self.narration() {
+            self.narration = if enabled { Some(Vec::new()) } else { None };
+        }
+    }
+
+    // Lines since the last call, oldest first.
+    pub fn take_narration(&mut self) -> Vec<String> {
+        self.narration.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+impl Universe {
+    pub(crate) fn narrate(&mut self, event: GameEvent) {
+        let line = match event {
+            GameEvent::AppleEaten { score } => format!("Apple eaten, score {}", score),
+            GameEvent::GameOver { reason } => format!("Game over, {}", reason_text(reason)),
+            GameEvent::SpeedChanged { ticks_per_second } => format!("Speed {:.0} moves a second", ticks_per_second),
+            GameEvent::LevelComplete { level } => format!("Level {} complete", level + 1),
+            GameEvent::ArenaShrunk { .. } => "The arena closed in".to_string(),
+            GameEvent::ComboBroken { player: 0, combo } => format!("Combo of {} broken", combo),
+            GameEvent::ComboBroken { .. } => return,
+        };
+        self.say(line);
+    }
+
+    pub(crate) fn narrate_countdown(&mut self, count: u32) {
+        self.say(count.to_string());
+    }
+
+    fn say(&mut self, line: String) {
+        if let Some(lines) = &mut self.narration {
+            if lines.len() == MAX_QUEUED_NARRATION {
+                lines.remove(0);
+            }
+            lines.push(line);
+        }
+    }
+
+    // How far `to` is ahead of and to the right of `from` for a snake facing
+    // `heading`, the short way round on wrapping boards.
+    fn bearing(&self, from: Position, to: Position, heading: Direction) -> (i64, i64) {
+        let dx = wrapped(to.x as i64 - from.x as i64, self.width, self.topology.wraps_x());
+        let dy = wrapped(to.y as i64 - from.y as i64, self.height, self.topology.wraps_y());
+        let (vx, vy) = (heading.vx as i64, heading.vy as i64);
+        (dx * vx + dy * vy, dy * vx - dx * vy)
+    }
+
+    fn look_ahead(&self, head: Position, heading: Direction) -> String {
+        let mut p = head;
+        for distance in 1..=self.width.max(self.height) {
+            p = match self.step_from(&p, heading, false) {
+                Ok(p) => p,
+                Err(_) => return format!("wall {} ahead", cells(distance as i64)),
+            };
+            let what = match self.cells.get(self.get_index(p.y, p.x)) {
+                Cell::Wall => "wall",
+                Cell::Hazard => "hazard",
+                Cell::SnakeHead | Cell::SnakeBody if self.players[0].snake.body.contains(&p) => "own body",
+                Cell::SnakeHead | Cell::SnakeBody => "snake",
+                _ => continue,
+            };
+            return format!("{} {} ahead", what, cells(distance as i64));
+        }
+        "clear ahead".to_string()
+    }
+}
+
+fn wrapped(d: i64, size: u32, wraps: bool) -> i64 {
+    let size = size as i64;
+    if wraps && d.abs() * 2 > size {
+        d - d.signum() * size
+    } else {
+        d
+    }
+}
+
+fn cells(n: i64) -> String {
+    if n == 1 {
+        "1 cell".to_string()
+    } else {
+        format!("{} cells", n)
+    }
+}
+
+fn directions(forward: i64, right: i64) -> String {
+    let mut parts = Vec::new();
+    if forward != 0 {
+        let side = if forward > 0 { "ahead" } else { "behind" };
+        parts.push(format!("{} {}", cells(forward.abs()), side));
+    }
+    if right != 0 {
+        let side = if right > 0 { "to the right" } else { "to the left" };
+        parts.push(format!("{} {}", cells(right.abs()), side));
+    }
+    if parts.is_empty() {
+        "right here".to_string()
+    } else {
+        parts.join(" and ")
+    }
+}
+
+fn food_name(kind: FoodKind) -> &'static str {
+    match kind {
+        FoodKind::Normal => "apple",
+        FoodKind::Poison => "poison",
+        FoodKind::Golden => "golden apple",
+        FoodKind::Speed => "speed apple",
+        FoodKind::PowerUp => "power-up",
+    }
+}
+
+fn reason_text(reason: GameOverReason) -> &'static str {
+    match reason {
+        GameOverReason::HitWall => "hit the wall",
+        GameOverReason::HitSelf => "bit itself",
+        GameOverReason::HitObstacle => "hit an obstacle",
+        GameOverReason::BoardFull => "the board is full",
+        GameOverReason::CampaignComplete => "campaign complete",
+        GameOverReason::Starved => "starved",
+        GameOverReason::HitSnake => "hit another snake",
+        GameOverReason::HitHazard => "hit a hazard",
+        GameOverReason::Crushed => "crushed by the arena",
+        GameOverReason::TimeUp => "time's up",
+    }
+}
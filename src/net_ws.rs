@@ -0,0 +1,196 @@
+use crate::netcode::{Lockstep, Message};
+use crate::{Cell, DirectionName, Universe};
+use std::cell::RefCell;
+use std::rc::{Rc, Weak};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{BinaryType, MessageEvent, WebSocket};
+
+struct ClientState {
+    socket: WebSocket,
+    room: String,
+    lockstep: Option<Lockstep>,
+    rtt_ms: Option<f64>,
+    error: Option<String>,
+    on_open: Option<Closure<dyn FnMut()>>,
+    on_message: Option<Closure<dyn FnMut(MessageEvent)>>,
+}
+
+// A match over a WebSocket speaking the `netcode::Message` protocol. The
+// server's `Start` sets up the lockstep; after that the page calls `tick`
+// on its own clock and `turn` on input, exactly as with a local game.
+#[wasm_bindgen]
+pub struct MatchClient {
+    inner: Rc<RefCell<ClientState>>,
+}
+
+#[wasm_bindgen]
+impl MatchClient {
+    pub fn connect(url: &str, room: &str) -> Result<MatchClient, JsValue> {
+        let socket = WebSocket::new(url)?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+
+        let inner = Rc::new(RefCell::new(ClientState {
+            socket: socket.clone(),
+            room: room.to_string(),
+            lockstep: None,
+            rtt_ms: None,
+            error: None,
+            on_open: None,
+            on_message: None,
+        }));
+
+        let weak = Rc::downgrade(&inner);
+        let on_open = Closure::wrap(Box::new(move || MatchClient::opened(&weak)) as Box<dyn FnMut()>);
+        let weak = Rc::downgrade(&inner);
+        let on_message = Closure::wrap(
+            Box::new(move |event: MessageEvent| MatchClient::message(&weak, event)) as Box<dyn FnMut(MessageEvent)>
+        );
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+
+        {
+            let mut state = inner.borrow_mut();
+            state.on_open = Some(on_open);
+            state.on_message = Some(on_message);
+        }
+        Ok(MatchClient { inner })
+    }
+
+    // True once the server has started the match.
+    pub fn is_ready(&self) -> bool {
+        self.inner.borrow().lockstep.is_some()
+    }
+
+    pub fn player(&self) -> Option<u32> {
+        self.inner.borrow().lockstep.as_ref().map(|lockstep| lockstep.local_player())
+    }
+
+    // The last protocol error from the server, if any.
+    pub fn error(&self) -> Option<String> {
+        self.inner.borrow().error.clone()
+    }
+
+    pub fn rtt_ms(&self) -> Option<f64> {
+        self.inner.borrow().rtt_ms
+    }
+
+    pub fn turn(&self, direction: DirectionName) -> Result<(), JsValue> {
+        let mut state = self.inner.borrow_mut();
+        let message = match &mut state.lockstep {
+            Some(lockstep) => Message::Input {
+                player: lockstep.local_player(),
+                tick: lockstep.local_input(direction),
+                direction: Some(direction),
+            },
+            None => return Ok(()),
+        };
+        state.send(&message)
+    }
+
+    // Advances the match by one tick if every player's input is in, and
+    // tells the others how far this player is confirmed.
+    pub fn tick(&self) -> Result<bool, JsValue> {
+        let mut state = self.inner.borrow_mut();
+        let heartbeat = match &mut state.lockstep {
+            Some(lockstep) => {
+                if !lockstep.tick() {
+                    return Ok(false);
+                }
+                lockstep.heartbeat()
+            }
+            None => return Ok(false),
+        };
+        state.send(&heartbeat)?;
+        Ok(true)
+    }
+
+    pub fn ping(&self) -> Result<(), JsValue> {
+        self.inner.borrow().send(&Message::Ping {
+            sent_ms: js_sys::Date::now(),
+        })
+    }
+
+    pub fn close(&self) -> Result<(), JsValue> {
+        let state = self.inner.borrow();
+        state.socket.set_onopen(None);
+        state.socket.set_onmessage(None);
+        state.socket.close()
+    }
+
+    pub fn state_hash(&self) -> Option<u64> {
+        self.inner.borrow().lockstep.as_ref().map(|lockstep| lockstep.state_hash())
+    }
+
+    pub fn render(&self) -> String {
+        self.with_universe(Universe::render).unwrap_or_default()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.with_universe(Universe::width).unwrap_or(0)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.with_universe(Universe::height).unwrap_or(0)
+    }
+
+    pub fn cells(&self) -> *const Cell {
+        self.with_universe(Universe::cells).unwrap_or(std::ptr::null())
+    }
+}
+
+impl ClientState {
+    fn send(&self, message: &Message) -> Result<(), JsValue> {
+        self.socket.send_with_u8_array(&message.to_bytes())
+    }
+
+    fn handle(&mut self, message: Message) -> Result<(), String> {
+        match message {
+            Message::Start { player, state } => {
+                self.lockstep = Some(Lockstep::new(Universe::from_snapshot(*state)?, player)?);
+                Ok(())
+            }
+            Message::Ping { sent_ms } => {
+                self.rtt_ms = Some(js_sys::Date::now() - sent_ms);
+                Ok(())
+            }
+            Message::Input { .. } => match &mut self.lockstep {
+                Some(lockstep) => lockstep.receive(&message),
+                None => Err("input before the match started".to_string()),
+            },
+            Message::Join { .. } => Err("unexpected join from the server".to_string()),
+        }
+    }
+}
+
+impl MatchClient {
+    fn with_universe<T>(&self, f: impl FnOnce(&Universe) -> T) -> Option<T> {
+        self.inner.borrow().lockstep.as_ref().map(|lockstep| f(lockstep.universe()))
+    }
+
+    fn opened(weak: &Weak<RefCell<ClientState>>) {
+        if let Some(inner) = weak.upgrade() {
+            let mut state = inner.borrow_mut();
+            let join = Message::Join { room: state.room.clone() };
+            if let Err(err) = state.send(&join) {
+                state.error = Some(format!("{:?}", err));
+            }
+        }
+    }
+
+    fn message(weak: &Weak<RefCell<ClientState>>, event: MessageEvent) {
+        let inner = match weak.upgrade() {
+            Some(inner) => inner,
+            None => return,
+        };
+
+        let bytes = match event.data().dyn_into::<js_sys::ArrayBuffer>() {
+            Ok(buffer) => js_sys::Uint8Array::new(&buffer).to_vec(),
+            Err(_) => return,
+        };
+        let mut state = inner.borrow_mut();
+        if let Err(err) = Message::from_bytes(&bytes).and_then(|message| state.handle(message)) {
+            state.error = Some(err);
+        }
+    }
+}
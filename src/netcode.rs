@@ -0,0 +1,369 @@
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::{Cell, DirectionName, Universe};
+use std::collections::{BTreeMap, VecDeque};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const DEFAULT_INPUT_DELAY: u32 = 2;
+pub const DEFAULT_MAX_PREDICTION: u32 = 8;
+
+// What to do when a remote player's input for the next tick hasn't arrived.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LatePolicy {
+    Stall,
+    Predict,
+    // Predict, then rewind to a late input's tick and re-simulate.
+    Rollback,
+}
+
+// Deterministic lockstep over any transport the host provides. Every peer
+// runs the same seeded universe and feeds it the same turns on the same
+// ticks. Local turns are scheduled `input_delay` ticks ahead so they can reach
+// the other peers in time; a remote player is confirmed up to a tick once
+// every turn it makes before that tick is known.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Lockstep {
+    universe: Universe,
+    local_player: u32,
+    input_delay: u32,
+    late_policy: LatePolicy,
+    max_prediction: u32,
+    tick: u32,
+    confirmed: Vec<u32>,
+    inputs: BTreeMap<u32, Vec<(u32, DirectionName)>>,
+    // Under `Rollback`, the state before each tick not yet confirmed, oldest first.
+    states: VecDeque<Snapshot>,
+    mispredictions: u32,
+    rollbacks: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Lockstep {
+    pub fn new(universe: Universe, local_player: u32) -> Result<Lockstep, String> {
+        if local_player >= universe.snake_count() {
+            return Err(format!("no player {} in this universe", local_player));
+        }
+
+        Ok(Lockstep {
+            confirmed: vec![0; universe.snake_count() as usize],
+            universe,
+            local_player,
+            input_delay: DEFAULT_INPUT_DELAY,
+            late_policy: LatePolicy::Stall,
+            max_prediction: DEFAULT_MAX_PREDICTION,
+            tick: 0,
+            inputs: BTreeMap::new(),
+            states: VecDeque::new(),
+            mispredictions: 0,
+            rollbacks: 0,
+        })
+    }
+
+    // Every peer in a match must use the same delay.
+    pub fn set_input_delay(&mut self, ticks: u32) {
+        self.input_delay = ticks;
+    }
+
+    pub fn set_late_policy(&mut self, policy: LatePolicy) {
+        self.late_policy = policy;
+        if policy != LatePolicy::Rollback {
+            self.states.clear();
+        }
+    }
+
+    // How many ticks `Predict` and `Rollback` may run ahead of the slowest
+    // peer, which is also how many states rollback keeps.
+    pub fn set_max_prediction(&mut self, ticks: u32) {
+        self.max_prediction = ticks;
+    }
+
+    pub fn local_player(&self) -> u32 {
+        self.local_player
+    }
+
+    pub fn current_tick(&self) -> u32 {
+        self.tick
+    }
+
+    // Every tick below this is final for all players. Peers share the input
+    // delay, so nobody can turn before it.
+    pub fn confirmed_tick(&self) -> u32 {
+        let remote = (0..self.confirmed.len() as u32).filter(|&player| player != self.local_player);
+        remote
+            .map(|player| self.confirmed[player as usize].max(self.input_delay))
+            .min()
+            .unwrap_or(u32::MAX)
+            .min(self.local_confirmed_tick())
+    }
+
+    // Send this to the other peers as a heartbeat: no local turn can land
+    // before it anymore.
+    pub fn local_confirmed_tick(&self) -> u32 {
+        self.tick + self.input_delay
+    }
+
+    pub fn mispredictions(&self) -> u32 {
+        self.mispredictions
+    }
+
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    // Schedules a local turn and returns the tick to send along with it.
+    pub fn local_input(&mut self, direction: DirectionName) -> u32 {
+        let tick = self.local_confirmed_tick();
+        self.inputs.entry(tick).or_default().push((self.local_player, direction));
+        tick
+    }
+
+    // Remote turns must arrive in order per player, as over a WebSocket; a
+    // turn also confirms everything that player did before its tick.
+    pub fn apply_remote_input(&mut self, player: u32, tick: u32, direction: DirectionName) -> Result<(), String> {
+        self.check_remote(player, tick)?;
+        self.inputs.entry(tick).or_default().push((player, direction));
+        self.confirmed[player as usize] = tick;
+        self.late_input(tick);
+        self.forget();
+        Ok(())
+    }
+
+    // A heartbeat: `player` has no turns before `tick` beyond those sent.
+    pub fn confirm_remote(&mut self, player: u32, tick: u32) -> Result<(), String> {
+        self.check_remote(player, tick)?;
+        self.confirmed[player as usize] = tick;
+        self.forget();
+        Ok(())
+    }
+
+    // Runs the next tick once every player's turns for it are known, or
+    // predicts "no turn" for late players under `Predict` and `Rollback`.
+    // Returns false while stalled.
+    pub fn tick(&mut self) -> bool {
+        if self.confirmed_tick() <= self.tick {
+            let predict = self.late_policy != LatePolicy::Stall
+                && self.tick - self.confirmed_tick() < self.max_prediction;
+            if !predict {
+                return false;
+            }
+        }
+
+        self.simulate(false);
+        self.forget();
+        true
+    }
+
+    pub fn state_hash(&self) -> u64 {
+        self.universe.state_hash()
+    }
+
+    pub fn render(&self) -> String {
+        self.universe.render()
+    }
+
+    pub fn width(&self) -> u32 {
+        self.universe.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.universe.height()
+    }
+
+    pub fn cells(&self) -> *const Cell {
+        self.universe.cells()
+    }
+}
+
+impl Lockstep {
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+
+    // For draining sounds, effects and narration. Changing the game through
+    // it puts this peer out of sync.
+    pub fn universe_mut(&mut self) -> &mut Universe {
+        &mut self.universe
+    }
+
+    // Feeds a relayed `Input` into the match; other messages are not its business.
+    pub fn receive(&mut self, message: &Message) -> Result<(), String> {
+        match *message {
+            Message::Input {
+                player,
+                tick,
+                direction: Some(direction),
+            } => self.apply_remote_input(player, tick, direction),
+            Message::Input {
+                player,
+                tick,
+                direction: None,
+            } => self.confirm_remote(player, tick),
+            _ => Ok(()),
+        }
+    }
+
+    // The heartbeat to send after each tick.
+    pub fn heartbeat(&self) -> Message {
+        Message::Input {
+            player: self.local_player,
+            tick: self.local_confirmed_tick(),
+            direction: None,
+        }
+    }
+
+    fn check_remote(&self, player: u32, tick: u32) -> Result<(), String> {
+        if player == self.local_player || player as usize >= self.confirmed.len() {
+            return Err(format!("{} is not a remote player", player));
+        }
+        if tick < self.confirmed[player as usize] {
+            return Err(format!(
+                "tick {} from player {} arrived after tick {}",
+                tick, player, self.confirmed[player as usize]
+            ));
+        }
+        Ok(())
+    }
+
+    // Runs the next tick; `again` when it is being re-run after a rollback.
+    fn simulate(&mut self, again: bool) {
+        if self.late_policy == LatePolicy::Rollback {
+            self.states.push_back(self.universe.save_state());
+        }
+        if let Some(turns) = self.inputs.get(&self.tick) {
+            for &(player, direction) in turns {
+                self.universe.on_click_player(player, direction);
+            }
+        }
+        if again {
+            self.universe.tick_silently();
+        } else {
+            self.universe.tick(0);
+        }
+        self.tick += 1;
+    }
+
+    // Nothing can arrive for a confirmed tick, so its input and state go.
+    fn forget(&mut self) {
+        let confirmed = self.confirmed_tick().min(self.tick);
+        self.inputs = self.inputs.split_off(&confirmed);
+        while !self.states.is_empty() && self.tick - (self.states.len() as u32) < confirmed {
+            self.states.pop_front();
+        }
+    }
+
+    // A turn for a tick that already ran on a prediction is replayed from
+    // the state before that tick; without one the simulations have diverged.
+    fn late_input(&mut self, tick: u32) {
+        let first = self.tick - self.states.len() as u32;
+        if tick >= self.tick {
+            return;
+        }
+
+        if tick >= first {
+            let now = self.tick;
+            let state = self.states.drain((tick - first) as usize..).next().expect("a state for the late tick");
+            self.universe.load_state(state).expect("states taken from this game restore cleanly");
+            self.tick = tick;
+            while self.tick < now {
+                self.simulate(true);
+            }
+            self.rollbacks += 1;
+        } else {
+            self.mispredictions += 1;
+        }
+    }
+}
+
+impl Universe {
+    // A tick that already played out on a prediction: it has been seen and
+    // heard once, so nothing goes out to the player this time round.
+    fn tick_silently(&mut self) {
+        self.silent = true;
+        self.tick(0);
+        self.silent = false;
+    }
+}
+
+// === Wire protocol ===
+
+// One message per frame, e.g. per binary WebSocket message. The reference
+// server answers `Join` with `Start` once the room is full, relays every
+// `Input` to the other players in the room and echoes `Ping` back.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    Join { room: String },
+    Start { player: u32, state: Box<Snapshot> },
+    // A turn, or with no direction a heartbeat confirming the ticks before `tick`.
+    Input { player: u32, tick: u32, direction: Option<DirectionName> },
+    Ping { sent_ms: f64 },
+}
+
+pub const PROTOCOL_VERSION: u8 = 1;
+
+const JOIN: u8 = 0;
+const START: u8 = 1;
+const INPUT: u8 = 2;
+const PING: u8 = 3;
+
+impl Message {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        match self {
+            Message::Join { room } => {
+                w.u8(JOIN);
+                w.u8(PROTOCOL_VERSION);
+                w.len(room.len());
+                w.raw(room.as_bytes());
+            }
+            Message::Start { player, state } => {
+                w.u8(START);
+                w.varint(*player as u64);
+                let state = state.to_bytes();
+                w.len(state.len());
+                w.raw(&state);
+            }
+            Message::Input { player, tick, direction } => {
+                w.u8(INPUT);
+                w.varint(*player as u64);
+                w.varint(*tick as u64);
+                w.option(*direction, |w, direction| w.variant(direction));
+            }
+            Message::Ping { sent_ms } => {
+                w.u8(PING);
+                w.f64(*sent_ms);
+            }
+        }
+        w.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Message, String> {
+        let mut r = Reader::new(bytes);
+        let message = match r.u8()? {
+            JOIN => {
+                match r.u8()? {
+                    PROTOCOL_VERSION => {}
+                    v => return Err(format!("unsupported protocol version {}", v)),
+                }
+                let len = r.len()?;
+                let room = String::from_utf8(r.take(len)?.to_vec()).map_err(|_| "room is not UTF-8".to_string())?;
+                Message::Join { room }
+            }
+            START => {
+                let player = r.u32()?;
+                let len = r.len()?;
+                let state = Box::new(Snapshot::from_bytes(r.take(len)?)?);
+                Message::Start { player, state }
+            }
+            INPUT => Message::Input {
+                player: r.u32()?,
+                tick: r.u32()?,
+                direction: r.option(|r| r.variant())?,
+            },
+            PING => Message::Ping { sent_ms: r.f64()? },
+            tag => return Err(format!("unknown message {}", tag)),
+        };
+        r.finish()?;
+        Ok(message)
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Frames the min/max/percentile figures look back over, two seconds at 60fps.
+pub const PERF_WINDOW: usize = 120;
+
+// The last PERF_WINDOW durations of something, in milliseconds.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Window {
+    samples: VecDeque<f64>,
+}
+
+impl Window {
+    pub fn push(&mut self, ms: f64) {
+        if self.samples.len() == PERF_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ms);
+    }
+
+    pub fn min(&self) -> f64 {
+        self.samples.iter().copied().reduce(f64::min).unwrap_or(0.0)
+    }
+
+    pub fn max(&self) -> f64 {
+        self.samples.iter().copied().reduce(f64::max).unwrap_or(0.0)
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f64>() / self.samples.len() as f64
+    }
+
+    // The time a share `p` of the samples came in under, by nearest rank;
+    // 0 when empty.
+    pub fn percentile(&self, p: f64) -> f64 {
+        let mut sorted: Vec<f64> = self.samples.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let rank = (p.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        sorted.get(rank.saturating_sub(1)).copied().unwrap_or(0.0)
+    }
+}
+
+// Frame, tick and render timings over the last PERF_WINDOW samples of each,
+// for a debug overlay. Frames are measured between `advance` calls, ticks
+// inside `tick`, and renders by `GameLoop` or whatever the frontend reports
+// through `record_render_ms`. All times are milliseconds.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PerfStats {
+    pub(crate) fps: f64,
+    pub(crate) frame_min_ms: f64,
+    pub(crate) frame_max_ms: f64,
+    pub(crate) frame_p95_ms: f64,
+    pub(crate) tick_mean_ms: f64,
+    pub(crate) tick_max_ms: f64,
+    pub(crate) render_mean_ms: f64,
+    pub(crate) render_max_ms: f64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl PerfStats {
+    // The smoothed rate `Universe::fps` reports.
+    pub fn fps(&self) -> f64 {
+        self.fps
+    }
+
+    pub fn frame_min_ms(&self) -> f64 {
+        self.frame_min_ms
+    }
+
+    pub fn frame_max_ms(&self) -> f64 {
+        self.frame_max_ms
+    }
+
+    // 95% of recent frames took no longer than this.
+    pub fn frame_p95_ms(&self) -> f64 {
+        self.frame_p95_ms
+    }
+
+    pub fn tick_mean_ms(&self) -> f64 {
+        self.tick_mean_ms
+    }
+
+    pub fn tick_max_ms(&self) -> f64 {
+        self.tick_max_ms
+    }
+
+    pub fn render_mean_ms(&self) -> f64 {
+        self.render_mean_ms
+    }
+
+    pub fn render_max_ms(&self) -> f64 {
+        self.render_max_ms
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{Cell, GameState, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Links two cells so a snake moving into either one comes out of the
+    // other, still heading the same way. Portal cells themselves are never
+    // occupied; food on them is moved elsewhere.
+    pub fn add_portal(&mut self, ax: u32, ay: u32, bx: u32, by: u32) -> Result<(), String> {
+        let mut ends = [0; 2];
+        for (end, &(x, y)) in ends.iter_mut().zip(&[(ax, ay), (bx, by)]) {
+            if x >= self.width || y >= self.height {
+                return Err(format!("portal ({}, {}) lies outside the {}x{} universe", x, y, self.width, self.height));
+            }
+            let idx = self.get_index(y, x);
+            if self.walls.contains(idx) || self.occupied.contains(idx) || self.portal_exit(idx).is_some() {
+                return Err(format!("portal ({}, {}) is already taken", x, y));
+            }
+            *end = idx;
+        }
+        if ends[0] == ends[1] {
+            return Err("a portal needs two different ends".to_string());
+        }
+
+        for &idx in &ends {
+            self.set_cell(idx, Cell::Portal);
+            self.cover_food(idx);
+        }
+        self.portals.push((ends[0] as u32, ends[1] as u32));
+        Ok(())
+    }
+
+    pub fn clear_portals(&mut self) {
+        for (a, b) in std::mem::take(&mut self.portals) {
+            self.set_cell(a as usize, Cell::Empty);
+            self.set_cell(b as usize, Cell::Empty);
+        }
+
+        if self.state == GameState::Running {
+            self.spawn_food();
+        }
+    }
+
+    pub fn portal_count(&self) -> u32 {
+        self.portals.len() as u32
+    }
+
+    pub fn is_portal(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.portal_exit(self.get_index(y, x)).is_some()
+    }
+}
+
+impl Universe {
+    // The other end of the portal at `idx`.
+    pub(crate) fn portal_exit(&self, idx: usize) -> Option<usize> {
+        let idx = idx as u32;
+        let exit = self.portals.iter().find_map(|&(a, b)| {
+            if a == idx {
+                Some(b)
+            } else if b == idx {
+                Some(a)
+            } else {
+                None
+            }
+        });
+        exit.map(|exit| exit as usize)
+    }
+}
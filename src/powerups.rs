@@ -0,0 +1,101 @@
+use crate::rng::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const POWER_UP_TICKS: u32 = 60;
+pub const SLOW_MOTION_FACTOR: f64 = 0.5;
+pub const SCORE_MULTIPLIER: u32 = 2;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PowerUp {
+    Invincible,
+    SlowMotion,
+    ScoreMultiplier,
+    Ghost,
+}
+
+impl PowerUp {
+    pub(crate) fn roll(rng: &mut Rng) -> PowerUp {
+        match rng.below(4) {
+            0 => PowerUp::Invincible,
+            1 => PowerUp::SlowMotion,
+            2 => PowerUp::ScoreMultiplier,
+            _ => PowerUp::Ghost,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ActiveEffect {
+    kind: PowerUp,
+    ticks_left: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl ActiveEffect {
+    pub fn kind(&self) -> PowerUp {
+        self.kind
+    }
+
+    pub fn ticks_left(&self) -> u32 {
+        self.ticks_left
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PowerUps {
+    effects: Vec<ActiveEffect>,
+}
+
+impl PowerUps {
+    // Re-activating a running effect extends it rather than stacking.
+    pub fn activate(&mut self, kind: PowerUp, ticks: u32) {
+        match self.effects.iter_mut().find(|effect| effect.kind == kind) {
+            Some(effect) => effect.ticks_left = effect.ticks_left.max(ticks),
+            None => self.effects.push(ActiveEffect { kind, ticks_left: ticks }),
+        }
+        self.effects.retain(|effect| effect.ticks_left > 0);
+    }
+
+    pub fn is_active(&self, kind: PowerUp) -> bool {
+        self.effects.iter().any(|effect| effect.kind == kind)
+    }
+
+    pub fn tick(&mut self) {
+        for effect in &mut self.effects {
+            effect.ticks_left -= 1;
+        }
+        self.effects.retain(|effect| effect.ticks_left > 0);
+    }
+
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn effects(&self) -> &[ActiveEffect] {
+        &self.effects
+    }
+
+    pub fn score_multiplier(&self) -> u32 {
+        if self.is_active(PowerUp::ScoreMultiplier) {
+            SCORE_MULTIPLIER
+        } else {
+            1
+        }
+    }
+
+    pub fn speed_factor(&self) -> f64 {
+        if self.is_active(PowerUp::SlowMotion) {
+            SLOW_MOTION_FACTOR
+        } else {
+            1.0
+        }
+    }
+}
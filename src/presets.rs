@@ -0,0 +1,68 @@
+use crate::modes::GameMode;
+use crate::speed::{SpeedCurve, DEFAULT_MAX_TICKS_PER_SECOND, DEFAULT_TICKS_PER_SECOND};
+use crate::{Universe, UniverseTopology};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Ready-made combinations of the rule settings, so a frontend can offer a
+// difficulty picker instead of every knob.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    // Wrapping board, steady speed, one apple: the game as it always was.
+    Classic,
+    // Slower, three apples, and assist drops turns into walls.
+    Casual,
+    // Walled board, speeding up with every apple, and a snake that shrinks
+    // when it goes hungry.
+    Hardcore,
+}
+
+const CASUAL_TICKS_PER_SECOND: f64 = 7.0;
+const CASUAL_APPLES: u32 = 3;
+const HARDCORE_SPEED_STEP: f64 = 0.05;
+const HARDCORE_HUNGER: u32 = 40;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Overwrites topology, speed, apples, hunger, assist and game mode. Takes
+    // effect right away; call `reset` to start a fresh game with it.
+    pub fn apply_preset(&mut self, preset: Preset) {
+        let (topology, curve, base, step, apples, hunger, assist) = match preset {
+            Preset::Classic => (
+                UniverseTopology::Toroidal,
+                SpeedCurve::Constant,
+                DEFAULT_TICKS_PER_SECOND,
+                0.0,
+                1,
+                0,
+                false,
+            ),
+            Preset::Casual => (
+                UniverseTopology::Toroidal,
+                SpeedCurve::Constant,
+                CASUAL_TICKS_PER_SECOND,
+                0.0,
+                CASUAL_APPLES,
+                0,
+                true,
+            ),
+            Preset::Hardcore => (
+                UniverseTopology::Flat,
+                SpeedCurve::Exponential,
+                DEFAULT_TICKS_PER_SECOND,
+                HARDCORE_SPEED_STEP,
+                1,
+                HARDCORE_HUNGER,
+                false,
+            ),
+        };
+
+        self.set_topology(topology);
+        self.set_speed_curve(curve, base, step, DEFAULT_MAX_TICKS_PER_SECOND).expect("preset speeds are valid");
+        self.set_apple_count(apples);
+        self.set_hunger(hunger);
+        self.set_assist(assist);
+        self.set_game_mode(GameMode::Classic);
+    }
+}
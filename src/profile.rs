@@ -0,0 +1,115 @@
+use crate::{Instant, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// The parts of a tick the profiler tells apart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Section {
+    // Planning every head and settling crashes between snakes.
+    Collisions,
+    FoodSpawn,
+    // Moving snakes across the board.
+    CellUpdates,
+}
+
+impl Section {
+    const ALL: [Section; 3] = [Section::Collisions, Section::FoodSpawn, Section::CellUpdates];
+
+    fn name(self) -> &'static str {
+        match self {
+            Section::Collisions => "collisions",
+            Section::FoodSpawn => "food_spawn",
+            Section::CellUpdates => "cell_updates",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Totals {
+    calls: u64,
+    total_ms: f64,
+    max_ms: f64,
+}
+
+// CPU time per Section since the last reset. Sections nest, and each only
+// counts its own time: food spawned by a snake eating while cells update
+// is booked as spawning. Timed with `Instant`, which reads
+// `performance.now` on wasm.
+#[derive(Default)]
+pub(crate) struct Profile {
+    ticks: u64,
+    totals: [Totals; 3],
+    // Open sections with when they started and the time spent in sections
+    // nested inside them so far.
+    open: Vec<(Section, Instant, f64)>,
+}
+
+impl Profile {
+    pub fn record_tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    fn enter(&mut self, section: Section) {
+        self.open.push((section, Instant::now(), 0.0));
+    }
+
+    fn exit(&mut self) {
+        let (section, started, nested_ms) = self.open.pop().expect("profiler section exited twice");
+        let ms = started.elapsed().as_secs_f64() * 1000.0;
+        let own_ms = (ms - nested_ms).max(0.0);
+
+        let totals = &mut self.totals[section as usize];
+        totals.calls += 1;
+        totals.total_ms += own_ms;
+        totals.max_ms = totals.max_ms.max(own_ms);
+        if let Some(parent) = self.open.last_mut() {
+            parent.2 += ms;
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let sections: Vec<_> = Section::ALL
+            .iter()
+            .map(|&section| {
+                let totals = self.totals[section as usize];
+                let per_tick_ms = if self.ticks == 0 {
+                    0.0
+                } else {
+                    totals.total_ms / self.ticks as f64
+                };
+                format!(
+                    "\"{}\":{{\"calls\":{},\"total_ms\":{},\"max_ms\":{},\"per_tick_ms\":{}}}",
+                    section.name(),
+                    totals.calls,
+                    totals.total_ms,
+                    totals.max_ms,
+                    per_tick_ms
+                )
+            })
+            .collect();
+        format!("{{\"ticks\":{},{}}}", self.ticks, sections.join(","))
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Where tick time went since the game started or `reset_profile`:
+    // `{"ticks":N,"collisions":{...},"food_spawn":{...},"cell_updates":{...}}`
+    // with each section's `calls`, `total_ms`, `max_ms` and `per_tick_ms`.
+    pub fn profile_json(&self) -> String {
+        self.profile.to_json()
+    }
+
+    pub fn reset_profile(&mut self) {
+        self.profile = Profile::default();
+    }
+}
+
+impl Universe {
+    pub(crate) fn profiled<T>(&mut self, section: Section, f: impl FnOnce(&mut Universe) -> T) -> T {
+        self.profile.enter(section);
+        let result = f(self);
+        self.profile.exit();
+        result
+    }
+}
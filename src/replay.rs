@@ -0,0 +1,258 @@
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::{Direction, DirectionName, GameOverReason, GameState, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+const MAGIC: &[u8; 4] = b"SNRP";
+pub const REPLAY_VERSION: u8 = 1;
+// Longest replay `verify_replay` plays through, a bit over a day at 10
+// ticks a second, so a forged tick count can't keep a server busy.
+pub const MAX_VERIFIED_TICKS: u32 = 1_000_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Turn {
+    tick: u32,
+    player: u32,
+    direction: DirectionName,
+}
+
+// The game as it was when recording started plus every turn a snake took,
+// keyed by the recorded tick it took effect on. The seeded RNG makes
+// everything else (food, respawns) come out the same on playback; settings
+// changed mid-recording are not captured.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    start: Snapshot,
+    turns: Vec<Turn>,
+    ticks: u32,
+    played: u32,
+    cursor: usize,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Replay {
+    pub fn seed(&self) -> u64 {
+        self.start.seed()
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn turn_count(&self) -> usize {
+        self.turns.len()
+    }
+
+    // A fresh game at the start of the recording, with playback rewound to match.
+    pub fn restart(&mut self) -> Result<Universe, String> {
+        self.played = 0;
+        self.cursor = 0;
+        Universe::from_snapshot(self.start.clone())
+    }
+
+    // Plays the next recorded tick; false once the recording has run out.
+    pub fn step(&mut self, universe: &mut Universe) -> bool {
+        // Recording starts with the game, before any countdown has run.
+        while universe.state == GameState::Countdown {
+            universe.tick(0);
+        }
+        if self.played >= self.ticks || universe.state != GameState::Running {
+            return false;
+        }
+
+        while let Some(turn) = self.turns.get(self.cursor).filter(|turn| turn.tick == self.played) {
+            if let Some(player) = universe.players.get_mut(turn.player as usize) {
+                player.snake.pending.clear();
+                player.snake.pending.push_back(Direction::from_name(turn.direction));
+            }
+            self.cursor += 1;
+        }
+
+        universe.tick(0);
+        self.played += 1;
+        true
+    }
+
+    // Turns are stored as varint tick deltas, so a replay costs about three
+    // bytes per turn on top of the starting snapshot.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.header(MAGIC, REPLAY_VERSION);
+
+        let start = self.start.to_bytes();
+        w.len(start.len());
+        w.raw(&start);
+        w.varint(self.ticks as u64);
+
+        w.len(self.turns.len());
+        let mut last = 0;
+        for turn in &self.turns {
+            w.varint((turn.tick - last) as u64);
+            w.varint(turn.player as u64);
+            w.variant(turn.direction);
+            last = turn.tick;
+        }
+        w.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, String> {
+        let mut r = Reader::new(bytes);
+        r.header(MAGIC, REPLAY_VERSION, "replay")?;
+
+        let len = r.len()?;
+        let start = Snapshot::from_bytes(r.take(len)?)?;
+        let ticks = r.u32()?;
+
+        let mut last = 0u32;
+        let turns = r.list(|r| {
+            let tick = last
+                .checked_add(r.u32()?)
+                .filter(|&tick| tick < ticks)
+                .ok_or_else(|| "turn lies past the end of the replay".to_string())?;
+            last = tick;
+            Ok(Turn {
+                tick,
+                player: r.u32()?,
+                direction: r.variant()?,
+            })
+        })?;
+        r.finish()?;
+
+        Ok(Replay {
+            start,
+            turns,
+            ticks,
+            played: 0,
+            cursor: 0,
+        })
+    }
+}
+
+impl Replay {
+    fn new(start: Snapshot) -> Replay {
+        Replay {
+            start,
+            turns: Vec::new(),
+            ticks: 0,
+            played: 0,
+            cursor: 0,
+        }
+    }
+
+    // Called as a running tick begins, before any turn of that tick is recorded.
+    pub(crate) fn record_tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    // Drops the last `ticks` recorded ticks; false if that reaches back past
+    // the start of the recording.
+    pub(crate) fn truncate(&mut self, ticks: u32) -> bool {
+        if ticks > self.ticks {
+            return false;
+        }
+
+        self.ticks -= ticks;
+        let end = self.ticks;
+        self.turns.retain(|turn| turn.tick < end);
+        true
+    }
+
+    pub(crate) fn record_turn(&mut self, player: u32, direction: DirectionName) {
+        self.turns.push(Turn {
+            tick: self.ticks - 1,
+            player,
+            direction,
+        });
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Recording carries over `reset`, starting again from the new game.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Replay::new(self.save_state()));
+    }
+
+    pub fn stop_recording(&mut self) -> Option<Replay> {
+        self.recording.take()
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+}
+
+// What a replay really scores when played back.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerifiedScore {
+    score: u32,
+    apples_eaten: u32,
+    ticks: u32,
+    seed: u64,
+    state: GameState,
+    game_over_reason: Option<GameOverReason>,
+    state_hash: u64,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl VerifiedScore {
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn apples_eaten(&self) -> u32 {
+        self.apples_eaten
+    }
+
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn state(&self) -> GameState {
+        self.state
+    }
+
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.game_over_reason
+    }
+
+    // Lets a server spot two submissions of the very same run.
+    pub fn state_hash(&self) -> u64 {
+        self.state_hash
+    }
+}
+
+// Plays a submitted replay from scratch and reports the score it earns, for
+// checking leaderboard entries on a server as well as in the browser. Only
+// recordings of a whole game under the default rules or a preset count: the
+// starting snapshot has to be exactly the new game its seed gives, or it
+// could carry a made-up score, lives or scoring rules.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn verify_replay(replay_bytes: &[u8]) -> Result<VerifiedScore, String> {
+    let mut replay = Replay::from_bytes(replay_bytes)?;
+    if replay.ticks > MAX_VERIFIED_TICKS {
+        return Err(format!("replay runs {} ticks, more than {}", replay.ticks, MAX_VERIFIED_TICKS));
+    }
+
+    if !replay.start.is_new_game() {
+        return Err("replay doesn't start with a new game".to_string());
+    }
+    let mut universe = replay.restart()?;
+    while replay.step(&mut universe) {}
+
+    Ok(VerifiedScore {
+        score: universe.score(),
+        apples_eaten: universe.stats.apples_eaten,
+        ticks: universe.stats.ticks,
+        seed: replay.seed(),
+        state: universe.state,
+        game_over_reason: universe.game_over_reason,
+        state_hash: universe.state_hash(),
+    })
+}
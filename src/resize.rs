@@ -0,0 +1,176 @@
+use crate::bitset::BitSet;
+use crate::grid::CellGrid;
+use crate::{Cell, FreeCells, Position, Snake, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Changes the board size in place, keeping as much of the game as fits.
+    // Snakes are moved whole to stay inside the board, or respawn somewhere
+    // safe at their length when they can't be; food wraps or clamps onto
+    // the new board; walls, portals and hazards past the new edges go. A
+    // shrinking arena opens back up, and the rewind history starts over.
+    // Fails, leaving the game as it was, if a snake finds no room at all.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), String> {
+        if width == 0 || height == 0 || width.checked_mul(height).is_none() {
+            return Err(format!("invalid universe size {}x{}", width, height));
+        }
+
+        let before = self.save_state();
+        let (old_width, old_height) = (self.width, self.height);
+        self.erase_ghost();
+        self.reopen_arena();
+        let inside = |p: &Position| p.x < width && p.y < height;
+        let at = |idx: u32| Position {
+            x: idx % old_width,
+            y: idx / old_width,
+        };
+        let walls: Vec<_> = self.walls.iter().map(|idx| at(idx as u32)).filter(inside).collect();
+        let portals: Vec<_> = self
+            .portals
+            .iter()
+            .map(|&(a, b)| (at(a), at(b)))
+            .filter(|(a, b)| inside(a) && inside(b))
+            .collect();
+        self.hazards.retain(|hazard| inside(&hazard.start) && inside(&hazard.position));
+        let terrain: Vec<_> = self.terrain.iter().map(|(&idx, &terrain)| (at(idx), terrain)).collect();
+        let foods = std::mem::take(&mut self.foods);
+
+        let len = (width * height) as usize;
+        self.width = width;
+        self.height = height;
+        self.cells = CellGrid::new(width, height);
+        self.changed_at = self.unchanged_cells(len);
+        self.free = FreeCells::full(len);
+        self.occupied = BitSet::new(len);
+        self.walls = BitSet::new(len);
+        self.dirty.clear();
+        self.dirty_mark = BitSet::new(len);
+
+        for p in walls {
+            let idx = self.get_index(p.y, p.x);
+            self.walls.insert(idx);
+            self.set_cell(idx, Cell::Wall);
+        }
+        self.portals.clear();
+        for (a, b) in portals {
+            let (a, b) = (self.get_index(a.y, a.x), self.get_index(b.y, b.x));
+            self.set_cell(a, Cell::Portal);
+            self.set_cell(b, Cell::Portal);
+            self.portals.push((a as u32, b as u32));
+        }
+        self.terrain = terrain
+            .into_iter()
+            .filter(|(p, _)| inside(p))
+            .map(|(p, terrain)| (self.get_index(p.y, p.x) as u32, terrain))
+            .collect();
+        self.board_edits = self.board_edits.wrapping_add(1);
+        let hazards: Vec<_> = self.hazards.iter().map(|hazard| hazard.position).collect();
+        for p in hazards {
+            let idx = self.get_index(p.y, p.x);
+            self.set_cell(idx, Cell::Hazard);
+        }
+
+        // Snakes only keep their place while it is clear of what stayed on
+        // the board and they don't run across a seam that moved.
+        let mut homeless = Vec::new();
+        for id in 0..self.players.len() {
+            let snake = &self.players[id].snake;
+            let moved = fit(snake, width, height)
+                .filter(|_| width == old_width || !crosses(snake, |p| p.x, old_width))
+                .filter(|_| height == old_height || !crosses(snake, |p| p.y, old_height))
+                .filter(|snake| snake.body.iter().all(|p| self.cells.get(self.get_index(p.y, p.x)) == Cell::Empty));
+            match moved {
+                Some(snake) => self.players[id].snake = snake,
+                None => {
+                    // Off the board until it has a new place.
+                    let body = std::mem::take(&mut self.players[id].snake.body);
+                    homeless.push((id, body.len() as u32));
+                }
+            }
+        }
+        self.paint_snakes();
+        for (id, length) in homeless {
+            if let Err(err) = self.rehome(id, length) {
+                self.load_state(before).expect("states taken from this game restore cleanly");
+                return Err(err);
+            }
+        }
+        for player in &mut self.players {
+            player.spawn = fit(&player.spawn, width, height).unwrap_or_else(|| player.snake.clone());
+            player.previous.clear();
+            player.previous.extend(player.snake.body.iter().copied());
+        }
+
+        for mut food in foods {
+            let Position { x, y } = food.position;
+            let x = if self.topology.wraps_x() { x % width } else { x.min(width - 1) };
+            let y = if self.topology.wraps_y() { y % height } else { y.min(height - 1) };
+            let idx = self.get_index(y, x);
+            if self.cells.get(idx) == Cell::Empty {
+                food.position = Position { x, y };
+                self.set_cell(idx, food.kind.cell());
+                self.foods.push(food);
+            }
+        }
+        self.spawn_food();
+
+        self.history.clear();
+        if self.recording.is_some() {
+            self.start_recording();
+        }
+        self.refresh_fog();
+        Ok(())
+    }
+}
+
+impl Universe {
+    // Finds a new place for a snake that no longer fits, `length` long if
+    // there's room, down to a single free cell if not.
+    fn rehome(&mut self, id: usize, length: u32) -> Result<(), String> {
+        let snake = match (2..=length).rev().find_map(|length| self.find_safe_spawn(length)) {
+            Some(snake) => snake,
+            None => {
+                let idx = self.free.sample(&mut self.rng).ok_or_else(|| {
+                    format!("no room for snake {} on a {}x{} board", id, self.width, self.height)
+                })?;
+                let head = Position {
+                    x: idx % self.width,
+                    y: idx / self.width,
+                };
+                Snake::spawn(&head, self.players[id].snake.direction, 1)
+            }
+        };
+        self.players[id].snake = snake;
+        self.paint_snakes();
+        Ok(())
+    }
+}
+
+// Whether `snake` wraps from one edge of a `size` long axis to the other.
+fn crosses(snake: &Snake, axis: impl Fn(&Position) -> u32, size: u32) -> bool {
+    let body: Vec<_> = snake.body.iter().map(axis).collect();
+    size > 2 && body.windows(2).any(|pair| pair[0].abs_diff(pair[1]) == size - 1)
+}
+
+// `snake` moved just far enough to lie within a `width` x `height` board;
+// None when it spans more than that.
+fn fit(snake: &Snake, width: u32, height: u32) -> Option<Snake> {
+    let max_x = snake.body.iter().map(|p| p.x).max()?;
+    let max_y = snake.body.iter().map(|p| p.y).max()?;
+    let min_x = snake.body.iter().map(|p| p.x).min()?;
+    let min_y = snake.body.iter().map(|p| p.y).min()?;
+    let dx = (max_x + 1).saturating_sub(width);
+    let dy = (max_y + 1).saturating_sub(height);
+    if dx > min_x || dy > min_y {
+        return None;
+    }
+
+    let mut moved = snake.clone();
+    for p in &mut moved.body {
+        p.x -= dx;
+        p.y -= dy;
+    }
+    Some(moved)
+}
@@ -0,0 +1,78 @@
+use crate::snapshot::Snapshot;
+use crate::Universe;
+use std::collections::VecDeque;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// The states before each of the last `depth` running ticks, oldest first,
+// each with the board edit count it was taken at. States taken between
+// edits share one copy of the walls, portals and terrain.
+#[derive(Default)]
+pub(crate) struct History {
+    depth: usize,
+    states: VecDeque<(u32, Snapshot)>,
+}
+
+impl History {
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.depth > 0
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.states.clear();
+    }
+
+    // The newest state, if the board hasn't been edited since it was taken.
+    pub(crate) fn layout(&self, board_edits: u32) -> Option<&Snapshot> {
+        self.states.back().filter(|(edits, _)| *edits == board_edits).map(|(_, state)| state)
+    }
+
+    pub(crate) fn push(&mut self, state: Snapshot, board_edits: u32) {
+        if self.states.len() == self.depth {
+            self.states.pop_front();
+        }
+        self.states.push_back((board_edits, state));
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Keeps the last `ticks` states around for `rewind`; 0 turns it off.
+    pub fn set_rewind_depth(&mut self, ticks: u32) {
+        let depth = ticks as usize;
+        self.history.depth = depth;
+        while self.history.states.len() > depth {
+            self.history.states.pop_front();
+        }
+    }
+
+    pub fn rewind_depth(&self) -> u32 {
+        self.history.depth as u32
+    }
+
+    pub fn rewind_available(&self) -> u32 {
+        self.history.states.len() as u32
+    }
+
+    // Goes back up to `ticks` running ticks, e.g. to undo a death, and
+    // returns how far it went. An active recording drops the undone ticks.
+    pub fn rewind(&mut self, ticks: u32) -> u32 {
+        let ticks = (ticks as usize).min(self.history.states.len());
+        if ticks == 0 {
+            return 0;
+        }
+
+        let keep = self.history.states.len() - ticks;
+        let (_, state) = self.history.states.drain(keep..).next().expect("rewinding at least one tick");
+        self.load_state(state).expect("states taken from this game restore cleanly");
+
+        let restart = match &mut self.recording {
+            Some(recording) => !recording.truncate(ticks as u32),
+            None => false,
+        };
+        if restart {
+            self.start_recording();
+        }
+        ticks as u32
+    }
+}
@@ -0,0 +1,89 @@
+use crate::food::FoodKind;
+use crate::{DirectionName, GameEvent, GameOverReason, GameState, Position, Universe};
+use std::rc::Rc;
+
+// What becomes of a snake that is about to crash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collision {
+    Die,
+    // Skip this tick's move and stay put.
+    Stop,
+}
+
+// The hooks a tick plays by. Every hook defaults to the classic game, so a
+// mode only overrides what it changes. The head and collision hooks see the
+// board as it was before anyone moved.
+pub trait Rules {
+    // Each living snake's next head, or the crash it runs into, before
+    // snakes are checked against each other. Return a heading to send the
+    // snake that way instead.
+    fn on_head_computed(
+        &self,
+        _universe: &Universe,
+        _player: u32,
+        _head: Result<Position, GameOverReason>,
+    ) -> Option<DirectionName> {
+        None
+    }
+
+    fn on_collision(&self, _universe: &Universe, _player: u32, _reason: GameOverReason) -> Collision {
+        Collision::Die
+    }
+
+    // Points for eating `kind`, before `Scoring` weights and multipliers.
+    fn on_apple_eaten(&self, _universe: &Universe, _player: u32, kind: FoodKind) -> u32 {
+        kind.points()
+    }
+
+    // Food is about to appear at `proposed`, a free cell picked at random.
+    // Return another cell to put it there instead; anything but a free cell
+    // is ignored.
+    fn on_food_spawn(&self, _universe: &Universe, _proposed: Position) -> Option<Position> {
+        None
+    }
+
+    // Checked after every running tick; a reason finishes the match, the
+    // top scorer winning.
+    fn end_condition(&self, _universe: &Universe) -> Option<GameOverReason> {
+        None
+    }
+}
+
+impl Universe {
+    // Plays by `rules` instead of the game mode's until `clear_rules`.
+    // Like controllers they stay through `reset` and `load_state` and
+    // aren't saved.
+    pub fn set_rules(&mut self, rules: Rc<dyn Rules>) {
+        self.clear_rules();
+        self.rules = Some(rules);
+    }
+
+    pub fn clear_rules(&mut self) {
+        self.rules = None;
+        #[cfg(feature = "wasm")]
+        {
+            self.script = None;
+        }
+    }
+
+    pub(crate) fn rules(&self) -> Rc<dyn Rules> {
+        match &self.rules {
+            Some(rules) => Rc::clone(rules),
+            None => self.mode.rules(),
+        }
+    }
+
+    pub(crate) fn finish(&mut self, reason: GameOverReason) {
+        // A tie has no winner.
+        let best = self.players.iter().map(|player| player.score).max().unwrap_or(0);
+        let mut leaders = (0..self.players.len()).filter(|&id| self.players[id].score == best);
+        self.winner = match (leaders.next(), leaders.next()) {
+            (Some(id), None) => Some(id as u32),
+            _ => None,
+        };
+
+        self.state = GameState::Finished;
+        self.game_over_reason = Some(reason);
+        self.emit(GameEvent::GameOver { reason });
+    }
+}
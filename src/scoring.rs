@@ -0,0 +1,103 @@
+use crate::Universe;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// How points are earned. The defaults score the classic way: a point an
+// apple (more for golden ones) and nothing else.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
+pub struct Scoring {
+    // Scales what each kind of food is worth; poison stays worth nothing.
+    pub apple: u32,
+    // For every tick a snake is alive at its end.
+    pub tick: u32,
+    // For every time a snake grows to a multiple of `milestone_length`; a
+    // length of 0 turns milestones off.
+    pub milestone: u32,
+    pub milestone_length: u32,
+    // Extra points an apple per tick/second above the speed curve's base,
+    // rounded to the nearest point.
+    pub speed_bonus: f64,
+}
+
+impl Default for Scoring {
+    fn default() -> Scoring {
+        Scoring {
+            apple: 1,
+            tick: 0,
+            milestone: 0,
+            milestone_length: 10,
+            speed_bonus: 0.0,
+        }
+    }
+}
+
+// The `Scoring` weights one at a time, for JS.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn set_apple_points(&mut self, points: u32) {
+        self.scoring.apple = points;
+    }
+
+    pub fn set_tick_points(&mut self, points: u32) {
+        self.scoring.tick = points;
+    }
+
+    // `points` every time a snake grows to a multiple of `length`, or never
+    // with a `length` of 0.
+    pub fn set_milestone_points(&mut self, points: u32, length: u32) {
+        self.scoring.milestone = points;
+        self.scoring.milestone_length = length;
+    }
+
+    pub fn set_speed_bonus(&mut self, bonus: f64) {
+        self.scoring.speed_bonus = bonus;
+    }
+}
+
+impl Universe {
+    pub fn scoring(&self) -> Scoring {
+        self.scoring
+    }
+
+    pub fn set_scoring(&mut self, scoring: Scoring) {
+        self.scoring = scoring;
+    }
+
+    // `points` is what the rules give the food, before multipliers.
+    pub(crate) fn apple_points(&self, points: u32) -> u32 {
+        if points == 0 {
+            return 0;
+        }
+
+        let faster = (self.ticks_per_second() - self.speed.base).max(0.0);
+        let bonus = (faster * self.scoring.speed_bonus).round() as u32;
+        points.saturating_mul(self.scoring.apple).saturating_add(bonus)
+    }
+
+    // Survival and length milestones, once every snake has moved.
+    pub(crate) fn score_tick(&mut self, fed: &[bool]) {
+        let Scoring {
+            tick,
+            milestone,
+            milestone_length,
+            ..
+        } = self.scoring;
+        for (id, &fed) in fed.iter().enumerate() {
+            if !self.players[id].alive {
+                continue;
+            }
+
+            let len = self.players[id].snake.body.len() as u32;
+            let reached = fed && milestone_length > 0 && len.is_multiple_of(milestone_length);
+            self.award(id, tick.saturating_add(if reached { milestone } else { 0 }));
+        }
+    }
+
+    pub(crate) fn award(&mut self, id: usize, points: u32) {
+        self.stats.record_points(points);
+        self.players[id].score = self.players[id].score.saturating_add(points);
+    }
+}
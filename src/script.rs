@@ -0,0 +1,159 @@
+use crate::food::FoodKind;
+use crate::rules::{Collision, Rules};
+use crate::snapshot::Variants;
+use crate::{DirectionName, GameOverReason, Position, Universe};
+use js_sys::{Function, Object, Reflect};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+// Rule hooks written in JS, for modding without rebuilding the wasm. Each
+// hook gets a plain object describing the snake it is about and returns a
+// decision; a hook that is missing, throws or returns something unusable
+// leaves the game mode's rule in charge.
+#[derive(Clone, Default)]
+pub(crate) struct ScriptRules {
+    head: Option<Function>,
+    collision: Option<Function>,
+    apple: Option<Function>,
+    spawn: Option<Function>,
+    end: Option<Function>,
+}
+
+fn view(universe: &Universe, player: u32) -> Object {
+    let view = Object::new();
+    let id = player as usize;
+    let head = universe.players[id].snake.body.front().unwrap();
+    set(&view, "player", player.into());
+    set(&view, "width", universe.width.into());
+    set(&view, "height", universe.height.into());
+    set(&view, "score", universe.players[id].score.into());
+    set(&view, "length", (universe.players[id].snake.body.len() as u32).into());
+    set(&view, "headX", head.x.into());
+    set(&view, "headY", head.y.into());
+    set(&view, "heading", universe.players[id].snake.direction.name().into());
+    set(&view, "ticks", universe.stats.ticks.into());
+    view
+}
+
+fn set(object: &Object, key: &str, value: JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), &value).expect("view object is extensible");
+}
+
+fn call(hook: &Option<Function>, view: &Object) -> Option<JsValue> {
+    let result = hook.as_ref()?.call1(&JsValue::NULL, view).ok()?;
+    (!result.is_undefined() && !result.is_null()).then_some(result)
+}
+
+fn variant<T: Variants>(value: &JsValue, index: fn(T) -> u32) -> Option<T> {
+    let n = value.as_f64()?;
+    T::VARIANTS.iter().copied().find(|&variant| index(variant) as f64 == n)
+}
+
+fn number(object: &JsValue, key: &str) -> Option<u32> {
+    let n = Reflect::get(object, &JsValue::from_str(key)).ok()?.as_f64()?;
+    (n >= 0.0 && n.fract() == 0.0 && n <= u32::MAX as f64).then_some(n as u32)
+}
+
+impl Rules for ScriptRules {
+    // Sees `nextX`/`nextY`, or `crash` with the reason, and may return a
+    // `DirectionName`.
+    fn on_head_computed(
+        &self,
+        universe: &Universe,
+        player: u32,
+        head: Result<Position, GameOverReason>,
+    ) -> Option<DirectionName> {
+        if self.head.is_none() {
+            return universe.mode.rules().on_head_computed(universe, player, head);
+        }
+        let view = view(universe, player);
+        match head {
+            Ok(p) => {
+                set(&view, "nextX", p.x.into());
+                set(&view, "nextY", p.y.into());
+            }
+            Err(reason) => set(&view, "crash", reason.into()),
+        }
+        variant(&call(&self.head, &view)?, |direction| direction as u32)
+    }
+
+    // Sees `reason`; returning true keeps the snake alive, standing still.
+    fn on_collision(&self, universe: &Universe, player: u32, reason: GameOverReason) -> Collision {
+        if self.collision.is_none() {
+            return universe.mode.rules().on_collision(universe, player, reason);
+        }
+        let view = view(universe, player);
+        set(&view, "reason", reason.into());
+        match call(&self.collision, &view).and_then(|stop| stop.as_bool()) {
+            Some(true) => Collision::Stop,
+            _ => Collision::Die,
+        }
+    }
+
+    // Sees `kind` and `points`, the mode's score for it; returns the points.
+    fn on_apple_eaten(&self, universe: &Universe, player: u32, kind: FoodKind) -> u32 {
+        let points = universe.mode.rules().on_apple_eaten(universe, player, kind);
+        if self.apple.is_none() {
+            return points;
+        }
+        let view = view(universe, player);
+        set(&view, "kind", kind.into());
+        set(&view, "points", points.into());
+        match call(&self.apple, &view).and_then(|value| value.as_f64()) {
+            Some(n) if n >= 0.0 && n <= u32::MAX as f64 => n as u32,
+            _ => points,
+        }
+    }
+
+    // Player 0's view with the proposed `x`/`y`; may return `{ x, y }`.
+    fn on_food_spawn(&self, universe: &Universe, proposed: Position) -> Option<Position> {
+        if self.spawn.is_none() {
+            return universe.mode.rules().on_food_spawn(universe, proposed);
+        }
+        let view = view(universe, 0);
+        set(&view, "x", proposed.x.into());
+        set(&view, "y", proposed.y.into());
+        let cell = call(&self.spawn, &view)?;
+        Some(Position {
+            x: number(&cell, "x")?,
+            y: number(&cell, "y")?,
+        })
+    }
+
+    // Player 0's view after each tick; a `GameOverReason` ends the match.
+    fn end_condition(&self, universe: &Universe) -> Option<GameOverReason> {
+        let ended = universe.mode.rules().end_condition(universe);
+        if self.end.is_none() || ended.is_some() {
+            return ended;
+        }
+        variant(&call(&self.end, &view(universe, 0))?, |reason| reason as u32)
+    }
+}
+
+#[wasm_bindgen]
+impl Universe {
+    // Hooks are "head", "collision", "apple", "spawn" and "end"; see
+    // `ScriptRules` for what each one sees and returns. They replace any
+    // rules set from Rust and stay through `reset` like them.
+    pub fn set_rule_hook(&mut self, hook: &str, callback: Function) -> Result<(), String> {
+        let mut rules = self.script.as_deref().cloned().unwrap_or_default();
+        let slot = match hook {
+            "head" => &mut rules.head,
+            "collision" => &mut rules.collision,
+            "apple" => &mut rules.apple,
+            "spawn" => &mut rules.spawn,
+            "end" => &mut rules.end,
+            _ => return Err(format!("unknown rule hook {:?}", hook)),
+        };
+        *slot = Some(callback);
+
+        let rules = Rc::new(rules);
+        self.script = Some(Rc::clone(&rules));
+        self.rules = Some(rules);
+        Ok(())
+    }
+
+    pub fn clear_rule_hooks(&mut self) {
+        self.clear_rules();
+    }
+}
@@ -0,0 +1,103 @@
+use crate::config::Config;
+use crate::{storage, InputMode, Universe};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+const STORAGE_KEY: &str = "rust-snake-wasm/settings";
+
+// The player's preferences: the game setup plus how they like to steer.
+// Kept in localStorage as JSON so they survive a reload.
+#[wasm_bindgen]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    config: Config,
+    input_mode: InputMode,
+    assist: bool,
+    haptics: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            config: Config::default(),
+            input_mode: InputMode::Absolute,
+            assist: false,
+            haptics: true,
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl Settings {
+    // The saved settings, or the defaults when there are none or they
+    // can't be read anymore.
+    pub fn load() -> Settings {
+        storage::load(STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), JsValue> {
+        let json = serde_json::to_string(self).expect("settings serialize to JSON");
+        storage::save(STORAGE_KEY, &json)
+    }
+
+    pub fn forget() -> Result<(), JsValue> {
+        storage::remove(STORAGE_KEY)
+    }
+
+    // What `universe` is set up with right now.
+    pub fn from_universe(universe: &Universe) -> Settings {
+        Settings {
+            config: universe.config(),
+            input_mode: universe.input_mode(),
+            assist: universe.assist(),
+            haptics: universe.haptics(),
+        }
+    }
+
+    // A new game with these settings.
+    pub fn new_universe(&self) -> Result<Universe, String> {
+        let mut universe = Universe::from_config(&self.config)?;
+        universe.set_input_mode(self.input_mode);
+        universe.set_assist(self.assist);
+        universe.set_haptics(self.haptics);
+        Ok(universe)
+    }
+
+    // The config object as `Universe::new_with_config` takes it.
+    pub fn config(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(&self.config).map_err(|err| err.to_string())
+    }
+
+    pub fn set_config(&mut self, config: JsValue) -> Result<(), String> {
+        self.config = serde_wasm_bindgen::from_value(config).map_err(|err| err.to_string())?;
+        Ok(())
+    }
+
+    pub fn input_mode(&self) -> InputMode {
+        self.input_mode
+    }
+
+    pub fn set_input_mode(&mut self, mode: InputMode) {
+        self.input_mode = mode;
+    }
+
+    pub fn assist(&self) -> bool {
+        self.assist
+    }
+
+    pub fn set_assist(&mut self, enabled: bool) {
+        self.assist = enabled;
+    }
+
+    // Vibration on eating and dying, see `Universe::set_vibration_ms`.
+    pub fn haptics(&self) -> bool {
+        self.haptics
+    }
+
+    pub fn set_haptics(&mut self, enabled: bool) {
+        self.haptics = enabled;
+    }
+}
@@ -0,0 +1,147 @@
+use crate::ai::{AiStrategy, Controller};
+use crate::{DirectionName, GameOverReason, GameState, Snake, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+const MATCH_SIZE: u32 = 32;
+
+// What a run of ticks added up to.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BatchResult {
+    ticks: u32,
+    apples: u32,
+    score: u32,
+    died_reason: Option<GameOverReason>,
+    won: bool,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl BatchResult {
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn apples(&self) -> u32 {
+        self.apples
+    }
+
+    pub fn score(&self) -> u32 {
+        self.score
+    }
+
+    pub fn died(&self) -> bool {
+        self.died_reason.is_some()
+    }
+
+    pub fn died_reason(&self) -> Option<GameOverReason> {
+        self.died_reason
+    }
+
+    pub fn won(&self) -> bool {
+        self.won
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Runs up to `n` ticks in one call, stopping early once the game ends.
+    // With a strategy, that AI drives the player snake for the batch only.
+    pub fn advance_n_ticks(&mut self, n: u32, strategy: Option<AiStrategy>) -> BatchResult {
+        match strategy {
+            Some(strategy) => self.advance_n_ticks_with(n, strategy.controller().as_mut()),
+            None => self.run_batch(n, |_| {}),
+        }
+    }
+}
+
+impl Universe {
+    pub fn advance_n_ticks_with(&mut self, n: u32, controller: &mut dyn Controller) -> BatchResult {
+        self.run_batch(n, |universe| {
+            let direction = controller.next_direction(&universe.board_view(0).unwrap());
+            universe.players[0].snake.pending.clear();
+            universe.players[0].snake.set_direction_name(direction);
+        })
+    }
+
+    fn run_batch(&mut self, n: u32, mut steer: impl FnMut(&mut Universe)) -> BatchResult {
+        let score = self.stats.score;
+        let apples = self.stats.apples_eaten;
+        let mut result = BatchResult::default();
+
+        while result.ticks < n && self.state == GameState::Running {
+            steer(self);
+            let tick = self.tick(0);
+            result.ticks += 1;
+            result.won |= tick.won();
+        }
+
+        if self.state == GameState::GameOver {
+            result.died_reason = self.game_over_reason;
+        }
+        result.score = self.stats.score - score;
+        result.apples = self.stats.apples_eaten - apples;
+        result
+    }
+}
+
+// === Matches ===
+// Summary of a headless two-snake match. `winner` is only set when one snake
+// outlived the other; a match that hits the tick limit has none.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MatchResult {
+    ticks: u32,
+    scores: [u32; 2],
+    deaths: [Option<GameOverReason>; 2],
+    winner: Option<u32>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl MatchResult {
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn score(&self, player_id: u32) -> u32 {
+        self.scores.get(player_id as usize).copied().unwrap_or(0)
+    }
+
+    pub fn death_reason(&self, player_id: u32) -> Option<GameOverReason> {
+        self.deaths.get(player_id as usize).copied().flatten()
+    }
+
+    pub fn winner(&self) -> Option<u32> {
+        self.winner
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn simulate_match(seed: u64, a: AiStrategy, b: AiStrategy, max_ticks: u32) -> MatchResult {
+    simulate_match_with(seed, a.controller(), b.controller(), max_ticks)
+}
+
+// Both snakes start facing each other from opposite corners of a 32x32 board.
+pub fn simulate_match_with(
+    seed: u64,
+    a: Box<dyn Controller>,
+    b: Box<dyn Controller>,
+    max_ticks: u32,
+) -> MatchResult {
+    let snake = Snake::new_at(8, 10, DirectionName::Right, 4);
+    let mut universe = Universe::new_with_seed(snake, 60.0, MATCH_SIZE, MATCH_SIZE, seed)
+        .expect("spawn fits the match board");
+    universe
+        .add_snake(Snake::new_at(MATCH_SIZE - 9, MATCH_SIZE - 11, DirectionName::Left, 4))
+        .expect("spawns don't overlap");
+    universe.attach_controller(0, a);
+    universe.attach_controller(1, b);
+
+    let ticks = universe.advance_n_ticks(max_ticks, None).ticks();
+    MatchResult {
+        ticks,
+        scores: [universe.player_score(0), universe.player_score(1)],
+        deaths: [universe.player_death_reason(0), universe.player_death_reason(1)],
+        winner: universe.winner(),
+    }
+}
@@ -0,0 +1,1156 @@
+use crate::ai::Controller;
+use crate::bitset::BitSet;
+use crate::combo::Combo;
+use crate::food::{Food, FoodKind};
+use crate::grid::CellGrid;
+use crate::arena::Shrink;
+use crate::hazards::{Hazard, HazardKind};
+use crate::levels::{Campaign, Goal, Level};
+use crate::modes::GameMode;
+use crate::powerups::{PowerUp, PowerUps};
+use crate::presets::Preset;
+use crate::rng::Rng;
+use crate::scoring::Scoring;
+use crate::speed::{SpeedCurve, SpeedModel};
+use crate::terrain::Terrain;
+use crate::{
+    Cell, CollisionPolicy, Direction, DirectionName, FreeCells, GameOverReason, GameState, InputMode, MatchMode,
+    Player, Position, Snake, Stats, Universe, UniverseTopology, DEFAULT_FPS_TARGET,
+};
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::convert::TryFrom;
+use std::iter;
+use std::rc::Rc;
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "wasm")]
+use wasm_timer::Instant;
+
+// The state a game needs to carry on: cells, the free list and the dirty
+// tracking are repainted from walls, food and snakes on restore. Settings
+// that belong to the frontend (key map, swipe dead zone, listeners) and
+// attached controllers are left out.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Snapshot {
+    width: u32,
+    height: u32,
+    // The board's layout rarely changes from one tick to the next, so
+    // snapshots taken in between can share it.
+    walls: Rc<Vec<u32>>,
+    portals: Rc<Vec<(u32, u32)>>,
+    hazards: Vec<Hazard>,
+    terrain: Rc<Vec<(u32, Terrain)>>,
+    shrink: Shrink,
+    mode: GameMode,
+    time_left_ms: f64,
+    players: Vec<PlayerState>,
+    lives: u32,
+    countdown_seconds: u32,
+    countdown_ms: f64,
+    collision_policy: CollisionPolicy,
+    match_mode: MatchMode,
+    winner: Option<u32>,
+    foods: Vec<Food>,
+    apple_count: u32,
+    special_food: bool,
+    speed_boost: u32,
+    power_ups: PowerUps,
+    overlaps: u32,
+    hunger_interval: u32,
+    speed: SpeedModel,
+    scoring: Scoring,
+    state: GameState,
+    game_over_reason: Option<GameOverReason>,
+    topology: UniverseTopology,
+    seed: u64,
+    rng: Rng,
+    stats: Stats,
+    campaign: Option<Campaign>,
+    input_mode: InputMode,
+    assist: bool,
+    combos: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct PlayerState {
+    snake: Snake,
+    spawn: Snake,
+    score: u32,
+    alive: bool,
+    death: Option<GameOverReason>,
+    hunger_ticks: u32,
+    respawn_in: u32,
+    combo: Combo,
+    lives: u32,
+    spawned_at: u32,
+    mired: bool,
+}
+
+impl PlayerState {
+    fn into_player(self, controller: Option<Box<dyn Controller>>) -> Player {
+        Player {
+            snake: self.snake,
+            previous: Vec::new(),
+            spawn: self.spawn,
+            score: self.score,
+            alive: self.alive,
+            death: self.death,
+            hunger_ticks: self.hunger_ticks,
+            respawn_in: self.respawn_in,
+            combo: self.combo,
+            lives: self.lives,
+            spawned_at: self.spawned_at,
+            mired: self.mired,
+            controller,
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Whether this is a game just started from its seed with the default
+    // snake, under the default rules or one of the presets: no score, lives,
+    // power-ups or rule tweaks carried in from elsewhere.
+    pub(crate) fn is_new_game(&self) -> bool {
+        let rules = [None, Some(Preset::Classic), Some(Preset::Casual), Some(Preset::Hardcore)];
+        rules.iter().any(|&preset| self.new_game(preset).is_ok_and(|fresh| fresh == *self))
+    }
+
+    fn new_game(&self, preset: Option<Preset>) -> Result<Snapshot, String> {
+        let mut universe =
+            Universe::new_with_seed(Snake::default(), DEFAULT_FPS_TARGET, self.width, self.height, self.seed)?;
+        if let Some(preset) = preset {
+            universe.apply_preset(preset);
+        }
+        universe.reset_with_seed(self.seed);
+        Ok(universe.save_state())
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let len = self.width.checked_mul(self.height).filter(|&len| len > 0);
+        let len = len.ok_or_else(|| format!("invalid universe size {}x{}", self.width, self.height))?;
+        let outside = |p: &Position| p.x >= self.width || p.y >= self.height;
+
+        if self.players.is_empty() {
+            return Err("snapshot has no snakes".to_string());
+        }
+        self.speed.validate()?;
+
+        for (id, player) in self.players.iter().enumerate() {
+            if player.snake.body.is_empty() || player.spawn.body.is_empty() {
+                return Err(format!("snake {} has no segments", id));
+            }
+            if let Some(p) = player.snake.body.iter().chain(&player.spawn.body).find(|p| outside(p)) {
+                return Err(format!(
+                    "snake {} segment ({}, {}) lies outside the {}x{} universe",
+                    id, p.x, p.y, self.width, self.height
+                ));
+            }
+        }
+
+        if let Some(food) = self.foods.iter().find(|food| outside(&food.position)) {
+            return Err(format!(
+                "food at ({}, {}) lies outside the {}x{} universe",
+                food.position.x, food.position.y, self.width, self.height
+            ));
+        }
+
+        for hazard in self.hazards.iter().chain(&self.shrink.hazards) {
+            if outside(&hazard.start) || outside(&hazard.position) {
+                return Err(format!("a hazard lies outside the {}x{} universe", self.width, self.height));
+            }
+            if hazard.wait == 0 || hazard.wait > hazard.period {
+                return Err(format!("hazard waits {} of {} ticks", hazard.wait, hazard.period));
+            }
+        }
+
+        if let Some(&(idx, _)) = self.terrain.iter().find(|&&(idx, _)| idx >= len) {
+            return Err(format!("terrain {} lies outside the {}x{} universe", idx, self.width, self.height));
+        }
+
+        if let Some(idx) = self.walls.iter().find(|&&idx| idx >= len) {
+            return Err(format!("wall {} lies outside the {}x{} universe", idx, self.width, self.height));
+        }
+
+        let portals = self.portals.iter().chain(&self.shrink.portals);
+        if let Some(idx) = portals.flat_map(|&(a, b)| [a, b]).find(|&idx| idx >= len) {
+            return Err(format!("portal {} lies outside the {}x{} universe", idx, self.width, self.height));
+        }
+
+        if let Some(idx) = self.shrink.closed.iter().find(|&&idx| idx >= len) {
+            return Err(format!("wall {} lies outside the {}x{} universe", idx, self.width, self.height));
+        }
+
+        match &self.campaign {
+            Some(campaign) if campaign.current >= campaign.stages.len() => {
+                Err(format!("campaign is on level {} of {}", campaign.current, campaign.stages.len()))
+            }
+            Some(campaign) => campaign
+                .stages
+                .iter()
+                .enumerate()
+                .try_for_each(|(n, (level, _))| level.validate().map(|_| ()).map_err(|e| format!("level {}: {}", n, e))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Universe {
+    pub fn save_state(&self) -> Snapshot {
+        self.save_state_sharing(None)
+    }
+
+    // `save_state`, with walls, portals and terrain shared with `layout`
+    // instead of copied, for when the board hasn't been edited since it
+    // was taken.
+    pub(crate) fn save_state_sharing(&self, layout: Option<&Snapshot>) -> Snapshot {
+        let (walls, portals, terrain) = match layout {
+            Some(layout) => (layout.walls.clone(), layout.portals.clone(), layout.terrain.clone()),
+            None => (
+                Rc::new(self.walls.iter().map(|idx| idx as u32).collect()),
+                Rc::new(self.portals.clone()),
+                Rc::new(self.terrain.iter().map(|(&idx, &terrain)| (idx, terrain)).collect()),
+            ),
+        };
+        let players = self
+            .players
+            .iter()
+            .map(|player| PlayerState {
+                snake: player.snake.clone(),
+                spawn: player.spawn.clone(),
+                score: player.score,
+                alive: player.alive,
+                death: player.death,
+                hunger_ticks: player.hunger_ticks,
+                respawn_in: player.respawn_in,
+                combo: player.combo,
+                lives: player.lives,
+                spawned_at: player.spawned_at,
+                mired: player.mired,
+            })
+            .collect();
+
+        Snapshot {
+            width: self.width,
+            height: self.height,
+            walls,
+            portals,
+            hazards: self.hazards.clone(),
+            terrain,
+            shrink: self.shrink.clone(),
+            mode: self.mode,
+            time_left_ms: self.time_left_ms,
+            players,
+            lives: self.lives,
+            countdown_seconds: self.countdown_seconds,
+            countdown_ms: self.countdown_ms,
+            collision_policy: self.collision_policy,
+            match_mode: self.match_mode,
+            winner: self.winner,
+            foods: self.foods.clone(),
+            apple_count: self.apple_count,
+            special_food: self.special_food,
+            speed_boost: self.speed_boost,
+            power_ups: self.power_ups.clone(),
+            overlaps: self.overlaps,
+            hunger_interval: self.hunger_interval,
+            speed: self.speed,
+            scoring: self.scoring,
+            state: self.state,
+            game_over_reason: self.game_over_reason,
+            topology: self.topology,
+            seed: self.seed,
+            rng: self.rng.clone(),
+            stats: self.stats.clone(),
+            campaign: self.campaign.clone(),
+            input_mode: self.input_mode,
+            assist: self.assist,
+            combos: self.combos,
+        }
+    }
+
+    pub fn from_snapshot(snapshot: Snapshot) -> Result<Universe, String> {
+        snapshot.validate()?;
+        let snake = snapshot.players[0].snake.clone();
+        let mut universe =
+            Universe::new_with_seed(snake, DEFAULT_FPS_TARGET, snapshot.width, snapshot.height, snapshot.seed)?;
+        universe.load_state(snapshot)?;
+        Ok(universe)
+    }
+
+    // Puts the game back to `snapshot` in place. Controllers stay attached
+    // to the players with the same ids, event listeners stay registered.
+    pub fn load_state(&mut self, snapshot: Snapshot) -> Result<(), String> {
+        snapshot.validate()?;
+        let len = (snapshot.width * snapshot.height) as usize;
+
+        self.width = snapshot.width;
+        self.height = snapshot.height;
+        self.cells = CellGrid::new(snapshot.width, snapshot.height);
+        self.changed_at = self.unchanged_cells(len);
+        self.free = FreeCells::full(len);
+        self.occupied = BitSet::new(len);
+        self.walls = BitSet::new(len);
+        self.dirty.clear();
+        self.dirty_mark = BitSet::new(len);
+
+        for &idx in snapshot.walls.iter() {
+            self.walls.insert(idx as usize);
+            self.set_cell(idx as usize, Cell::Wall);
+        }
+        for &(a, b) in snapshot.portals.iter() {
+            self.set_cell(a as usize, Cell::Portal);
+            self.set_cell(b as usize, Cell::Portal);
+        }
+        self.portals = Rc::unwrap_or_clone(snapshot.portals);
+        for hazard in &snapshot.hazards {
+            let idx = self.get_index(hazard.position.y, hazard.position.x);
+            self.set_cell(idx, Cell::Hazard);
+        }
+        self.hazards = snapshot.hazards;
+        self.terrain = snapshot.terrain.iter().copied().collect();
+        self.board_edits = self.board_edits.wrapping_add(1);
+        self.shrink = snapshot.shrink;
+        self.mode = snapshot.mode;
+        self.time_left_ms = snapshot.time_left_ms;
+        for food in &snapshot.foods {
+            let idx = self.get_index(food.position.y, food.position.x);
+            self.set_cell(idx, food.kind.cell());
+        }
+        self.foods = snapshot.foods;
+
+        let controllers: Vec<_> = self.players.iter_mut().map(|player| player.controller.take()).collect();
+        self.players = snapshot
+            .players
+            .into_iter()
+            .zip(controllers.into_iter().chain(iter::repeat_with(|| None)))
+            .map(|(state, controller)| state.into_player(controller))
+            .collect();
+        self.paint_snakes();
+
+        self.lives = snapshot.lives;
+        self.countdown_seconds = snapshot.countdown_seconds;
+        self.countdown_ms = snapshot.countdown_ms;
+        self.collision_policy = snapshot.collision_policy;
+        self.match_mode = snapshot.match_mode;
+        self.winner = snapshot.winner;
+        self.apple_count = snapshot.apple_count;
+        self.special_food = snapshot.special_food;
+        self.speed_boost = snapshot.speed_boost;
+        self.power_ups = snapshot.power_ups;
+        self.overlaps = snapshot.overlaps;
+        self.hunger_interval = snapshot.hunger_interval;
+        self.speed = snapshot.speed;
+        self.scoring = snapshot.scoring;
+        self.state = snapshot.state;
+        self.game_over_reason = snapshot.game_over_reason;
+        self.topology = snapshot.topology;
+        self.seed = snapshot.seed;
+        self.rng = snapshot.rng;
+        self.campaign = snapshot.campaign;
+        self.input_mode = snapshot.input_mode;
+        self.assist = snapshot.assist;
+        self.combos = snapshot.combos;
+
+        // Backdate the clock so the elapsed time carries on from the snapshot.
+        let elapsed = Duration::try_from_secs_f64(snapshot.stats.elapsed_ms / 1000.0).unwrap_or_default();
+        let now = Instant::now();
+        #[cfg(not(feature = "wasm"))]
+        let started_at = now.checked_sub(elapsed).unwrap_or(now);
+        #[cfg(feature = "wasm")]
+        let started_at = now - elapsed;
+        self.started_at = started_at;
+        self.stats = snapshot.stats;
+        self.last_tps = self.ticks_per_second();
+        self.accumulator_ms = 0.0;
+
+        // Whatever was queued belongs to the game that was left.
+        self.effects.clear();
+        self.sounds.clear();
+        if let Some(lines) = &mut self.narration {
+            lines.clear();
+        }
+        self.seek_ghost();
+        self.refresh_fog();
+        Ok(())
+    }
+}
+
+// === Binary format ===
+// "SNAK" and a version byte, then the fields in declaration order. Integers
+// are LEB128 varints, the seed, RNG state and floats are little-endian
+// words, clock times are whole milliseconds and walls are a row-major
+// bitmap.
+const MAGIC: &[u8; 4] = b"SNAK";
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+// Fieldless enums are stored as their index in `VARIANTS`.
+pub(crate) trait Variants: Copy + PartialEq + 'static {
+    const VARIANTS: &'static [Self];
+}
+
+impl Variants for DirectionName {
+    const VARIANTS: &'static [Self] = &[
+        DirectionName::Up,
+        DirectionName::Down,
+        DirectionName::Left,
+        DirectionName::Right,
+    ];
+}
+
+impl Variants for GameOverReason {
+    const VARIANTS: &'static [Self] = &[
+        GameOverReason::HitWall,
+        GameOverReason::HitSelf,
+        GameOverReason::HitObstacle,
+        GameOverReason::BoardFull,
+        GameOverReason::CampaignComplete,
+        GameOverReason::Starved,
+        GameOverReason::HitSnake,
+        GameOverReason::HitHazard,
+        GameOverReason::Crushed,
+        GameOverReason::TimeUp,
+    ];
+}
+
+impl Variants for GameState {
+    const VARIANTS: &'static [Self] = &[
+        GameState::Menu,
+        GameState::Countdown,
+        GameState::Running,
+        GameState::Paused,
+        GameState::GameOver,
+        GameState::Victory,
+        GameState::Finished,
+    ];
+}
+
+impl Variants for CollisionPolicy {
+    const VARIANTS: &'static [Self] = &[
+        CollisionPolicy::BodyKillsRammer,
+        CollisionPolicy::HeadOnKillsBoth,
+        CollisionPolicy::LongerWins,
+        CollisionPolicy::PassThrough,
+    ];
+}
+
+impl Variants for MatchMode {
+    const VARIANTS: &'static [Self] = &[MatchMode::Versus, MatchMode::Coop];
+}
+
+impl Variants for InputMode {
+    const VARIANTS: &'static [Self] = &[InputMode::Absolute, InputMode::Relative];
+}
+
+impl Variants for FoodKind {
+    const VARIANTS: &'static [Self] = &[
+        FoodKind::Normal,
+        FoodKind::Poison,
+        FoodKind::Golden,
+        FoodKind::Speed,
+        FoodKind::PowerUp,
+    ];
+}
+
+impl Variants for HazardKind {
+    const VARIANTS: &'static [Self] = &[HazardKind::Patrol, HazardKind::Rogue];
+}
+
+impl Variants for PowerUp {
+    const VARIANTS: &'static [Self] = &[
+        PowerUp::Invincible,
+        PowerUp::SlowMotion,
+        PowerUp::ScoreMultiplier,
+        PowerUp::Ghost,
+    ];
+}
+
+impl Variants for SpeedCurve {
+    const VARIANTS: &'static [Self] = &[SpeedCurve::Constant, SpeedCurve::Linear, SpeedCurve::Exponential];
+}
+
+impl Variants for Terrain {
+    const VARIANTS: &'static [Self] = &[
+        Terrain::Plain,
+        Terrain::Ice,
+        Terrain::Mud,
+        Terrain::ConveyorUp,
+        Terrain::ConveyorDown,
+        Terrain::ConveyorLeft,
+        Terrain::ConveyorRight,
+    ];
+}
+
+impl Variants for Cell {
+    const VARIANTS: &'static [Self] = &[
+        Cell::Empty,
+        Cell::SnakeHead,
+        Cell::SnakeBody,
+        Cell::Apple,
+        Cell::Wall,
+        Cell::Poison,
+        Cell::Golden,
+        Cell::Speed,
+        Cell::PowerUp,
+        Cell::Ghost,
+        Cell::Portal,
+        Cell::Hazard,
+    ];
+}
+
+#[derive(Default)]
+pub(crate) struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    pub(crate) fn header(&mut self, magic: &[u8; 4], version: u8) {
+        self.raw(magic);
+        self.u8(version);
+    }
+
+    pub(crate) fn raw(&mut self, bytes: &[u8]) {
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub(crate) fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub(crate) fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub(crate) fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub(crate) fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.u8(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.u8(value as u8);
+    }
+
+    pub(crate) fn len(&mut self, len: usize) {
+        self.varint(len as u64);
+    }
+
+    pub(crate) fn word(&mut self, value: u64) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub(crate) fn f64(&mut self, value: f64) {
+        self.word(value.to_bits());
+    }
+
+    pub(crate) fn variant<T: Variants>(&mut self, value: T) {
+        let idx = T::VARIANTS.iter().position(|&v| v == value).expect("every variant is listed");
+        self.u8(idx as u8);
+    }
+
+    pub(crate) fn option<T>(&mut self, value: Option<T>, write: impl FnOnce(&mut Writer, T)) {
+        self.bool(value.is_some());
+        if let Some(value) = value {
+            write(self, value);
+        }
+    }
+
+    pub(crate) fn position(&mut self, position: &Position) {
+        self.varint(position.x as u64);
+        self.varint(position.y as u64);
+    }
+
+    pub(crate) fn snake(&mut self, snake: &Snake) {
+        self.len(snake.body.len());
+        for position in &snake.body {
+            self.position(position);
+        }
+        self.variant(snake.direction.name());
+        self.len(snake.pending.len());
+        for direction in &snake.pending {
+            self.variant(direction.name());
+        }
+    }
+
+    pub(crate) fn level(&mut self, level: &Level) {
+        self.varint(level.width as u64);
+        self.varint(level.height as u64);
+        self.len(level.walls.len());
+        for &idx in &level.walls {
+            self.varint(idx as u64);
+        }
+        self.position(&level.spawn);
+        self.option(level.apple.as_ref(), Writer::position);
+    }
+
+    pub(crate) fn goal(&mut self, goal: Goal) {
+        let (tag, target) = match goal {
+            Goal::Length(target) => (0, target),
+            Goal::Apples(target) => (1, target),
+        };
+        self.u8(tag);
+        self.varint(target as u64);
+    }
+
+    pub(crate) fn hazard(&mut self, hazard: &Hazard) {
+        self.variant(hazard.kind);
+        self.position(&hazard.start);
+        self.variant(hazard.start_heading);
+        self.position(&hazard.position);
+        self.variant(hazard.heading);
+        self.varint(hazard.period as u64);
+        self.varint(hazard.wait as u64);
+    }
+
+    pub(crate) fn topology(&mut self, topology: UniverseTopology) {
+        match topology {
+            UniverseTopology::Flat => self.u8(0),
+            UniverseTopology::Toroidal => self.u8(1),
+            UniverseTopology::CylinderX => self.u8(2),
+            UniverseTopology::CylinderY => self.u8(3),
+            UniverseTopology::Custom { wrap_x, wrap_y } => {
+                self.u8(4);
+                self.bool(wrap_x);
+                self.bool(wrap_y);
+            }
+        }
+    }
+
+    pub(crate) fn game_mode(&mut self, mode: GameMode) {
+        match mode {
+            GameMode::Classic => self.u8(0),
+            GameMode::Timed { seconds } => {
+                self.u8(1);
+                self.varint(seconds as u64);
+            }
+            GameMode::Zen => self.u8(2),
+        }
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Reader<'a> {
+        Reader { bytes }
+    }
+
+    pub(crate) fn header(&mut self, magic: &[u8; 4], version: u8, what: &str) -> Result<(), String> {
+        if self.take(magic.len()).ok() != Some(&magic[..]) {
+            return Err(format!("not a snake {}", what));
+        }
+        match self.u8()? {
+            v if v == version => Ok(()),
+            v => Err(format!("unsupported {} version {}", what, v)),
+        }
+    }
+
+    pub(crate) fn finish(&self) -> Result<(), String> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("{} trailing bytes", self.bytes.len()))
+        }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if len > self.bytes.len() {
+            return Err("snapshot is truncated".to_string());
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn bool(&mut self) -> Result<bool, String> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            other => Err(format!("invalid flag {}", other)),
+        }
+    }
+
+    pub(crate) fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7F) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err("varint is too long".to_string())
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        let value = self.varint()?;
+        u32::try_from(value).map_err(|_| format!("{} does not fit in 32 bits", value))
+    }
+
+    // Every element takes at least a byte, so longer lists can't be valid.
+    pub(crate) fn len(&mut self) -> Result<usize, String> {
+        let len = self.u32()? as usize;
+        if len > self.bytes.len() {
+            return Err("snapshot is truncated".to_string());
+        }
+        Ok(len)
+    }
+
+    pub(crate) fn word(&mut self) -> Result<u64, String> {
+        let mut word = [0; 8];
+        word.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(word))
+    }
+
+    pub(crate) fn f64(&mut self) -> Result<f64, String> {
+        Ok(f64::from_bits(self.word()?))
+    }
+
+    pub(crate) fn variant<T: Variants>(&mut self) -> Result<T, String> {
+        let idx = self.u8()?;
+        T::VARIANTS
+            .get(idx as usize)
+            .copied()
+            .ok_or_else(|| format!("invalid variant {}", idx))
+    }
+
+    pub(crate) fn option<T>(&mut self, read: impl FnOnce(&mut Reader<'a>) -> Result<T, String>) -> Result<Option<T>, String> {
+        if self.bool()? {
+            read(self).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub(crate) fn list<T>(&mut self, mut read: impl FnMut(&mut Reader<'a>) -> Result<T, String>) -> Result<Vec<T>, String> {
+        (0..self.len()?).map(|_| read(self)).collect()
+    }
+
+    pub(crate) fn position(&mut self) -> Result<Position, String> {
+        Ok(Position {
+            x: self.u32()?,
+            y: self.u32()?,
+        })
+    }
+
+    pub(crate) fn snake(&mut self) -> Result<Snake, String> {
+        let body = self.list(Reader::position)?.into();
+        let direction = Direction::from_name(self.variant()?);
+        let pending = self.list(|r| r.variant().map(Direction::from_name))?.into();
+        Ok(Snake {
+            body,
+            direction,
+            pending,
+        })
+    }
+
+    pub(crate) fn level(&mut self) -> Result<Level, String> {
+        Ok(Level {
+            width: self.u32()?,
+            height: self.u32()?,
+            walls: self.list(Reader::u32)?,
+            spawn: self.position()?,
+            apple: self.option(Reader::position)?,
+        })
+    }
+
+    pub(crate) fn goal(&mut self) -> Result<Goal, String> {
+        match self.u8()? {
+            0 => Ok(Goal::Length(self.u32()?)),
+            1 => Ok(Goal::Apples(self.u32()?)),
+            other => Err(format!("invalid goal {}", other)),
+        }
+    }
+
+    pub(crate) fn hazard(&mut self) -> Result<Hazard, String> {
+        Ok(Hazard {
+            kind: self.variant()?,
+            start: self.position()?,
+            start_heading: self.variant()?,
+            position: self.position()?,
+            heading: self.variant()?,
+            period: self.u32()?,
+            wait: self.u32()?,
+        })
+    }
+
+    pub(crate) fn topology(&mut self) -> Result<UniverseTopology, String> {
+        match self.u8()? {
+            0 => Ok(UniverseTopology::Flat),
+            1 => Ok(UniverseTopology::Toroidal),
+            2 => Ok(UniverseTopology::CylinderX),
+            3 => Ok(UniverseTopology::CylinderY),
+            4 => Ok(UniverseTopology::Custom {
+                wrap_x: self.bool()?,
+                wrap_y: self.bool()?,
+            }),
+            other => Err(format!("invalid topology {}", other)),
+        }
+    }
+
+    pub(crate) fn game_mode(&mut self) -> Result<GameMode, String> {
+        match self.u8()? {
+            0 => Ok(GameMode::Classic),
+            1 => Ok(GameMode::Timed { seconds: self.u32()? }),
+            2 => Ok(GameMode::Zen),
+            other => Err(format!("invalid game mode {}", other)),
+        }
+    }
+}
+
+impl Snapshot {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        w.header(MAGIC, SNAPSHOT_VERSION);
+
+        w.varint(self.width as u64);
+        w.varint(self.height as u64);
+        let mut walls = vec![0u8; (self.width as usize * self.height as usize).div_ceil(8)];
+        for &idx in self.walls.iter() {
+            walls[idx as usize / 8] |= 1 << (idx % 8);
+        }
+        w.raw(&walls);
+        w.len(self.portals.len());
+        for &(a, b) in self.portals.iter() {
+            w.varint(a as u64);
+            w.varint(b as u64);
+        }
+        w.len(self.hazards.len());
+        for hazard in &self.hazards {
+            w.hazard(hazard);
+        }
+        w.len(self.terrain.len());
+        for &(idx, terrain) in self.terrain.iter() {
+            w.varint(idx as u64);
+            w.variant(terrain);
+        }
+        w.varint(self.shrink.interval as u64);
+        w.varint(self.shrink.countdown as u64);
+        w.varint(self.shrink.rings as u64);
+        w.len(self.shrink.closed.len());
+        for &idx in &self.shrink.closed {
+            w.varint(idx as u64);
+        }
+        w.len(self.shrink.portals.len());
+        for &(a, b) in &self.shrink.portals {
+            w.varint(a as u64);
+            w.varint(b as u64);
+        }
+        w.len(self.shrink.hazards.len());
+        for hazard in &self.shrink.hazards {
+            w.hazard(hazard);
+        }
+        w.game_mode(self.mode);
+        w.f64(self.time_left_ms);
+
+        w.len(self.players.len());
+        for player in &self.players {
+            w.snake(&player.snake);
+            w.snake(&player.spawn);
+            w.varint(player.score as u64);
+            w.bool(player.alive);
+            w.option(player.death, Writer::variant);
+            w.varint(player.hunger_ticks as u64);
+            w.varint(player.respawn_in as u64);
+            w.option(Some(player.combo).filter(|&combo| combo != Combo::default()), |w, combo| {
+                w.varint(combo.chain as u64);
+                w.varint(combo.multiplier as u64);
+                w.varint(combo.idle_ticks as u64);
+            });
+            w.varint(player.lives as u64);
+            w.varint(player.spawned_at as u64);
+            w.bool(player.mired);
+        }
+        w.varint(self.lives as u64);
+        w.varint(self.countdown_seconds as u64);
+        // Only a game still counting down has any time left on it.
+        w.option(Some(self.countdown_ms).filter(|&ms| ms > 0.0), Writer::f64);
+        w.variant(self.collision_policy);
+        w.variant(self.match_mode);
+        w.option(self.winner, |w, id| w.varint(id as u64));
+
+        w.len(self.foods.len());
+        for food in &self.foods {
+            w.position(&food.position);
+            w.variant(food.kind);
+            w.option(food.ticks_left, |w, ticks| w.varint(ticks as u64));
+        }
+        w.varint(self.apple_count as u64);
+        w.bool(self.special_food);
+        w.varint(self.speed_boost as u64);
+
+        w.len(self.power_ups.effects().len());
+        for effect in self.power_ups.effects() {
+            w.variant(effect.kind());
+            w.varint(effect.ticks_left() as u64);
+        }
+        w.varint(self.overlaps as u64);
+        w.varint(self.hunger_interval as u64);
+
+        w.variant(self.speed.curve);
+        w.f64(self.speed.base);
+        w.f64(self.speed.step);
+        w.f64(self.speed.max);
+        // Most games score the classic way, which costs a single byte.
+        let scoring = Some(self.scoring).filter(|&scoring| scoring != Scoring::default());
+        w.option(scoring, |w, scoring| {
+            w.varint(scoring.apple as u64);
+            w.varint(scoring.tick as u64);
+            w.varint(scoring.milestone as u64);
+            w.varint(scoring.milestone_length as u64);
+            w.f64(scoring.speed_bonus);
+        });
+
+        w.variant(self.state);
+        w.option(self.game_over_reason, Writer::variant);
+        w.topology(self.topology);
+        w.word(self.seed);
+        for word in self.rng.state().iter() {
+            w.word(*word);
+        }
+
+        w.varint(self.stats.score as u64);
+        w.varint(self.stats.apples_eaten as u64);
+        w.varint(self.stats.ticks as u64);
+        w.varint(self.stats.length as u64);
+        w.varint(self.stats.max_length as u64);
+        // Clock times to the millisecond are plenty for a timer.
+        w.varint(self.stats.elapsed_ms.round() as u64);
+        w.varint(self.stats.game_ms.round() as u64);
+        w.len(self.stats.splits.len());
+        for &ms in &self.stats.splits {
+            w.varint(ms.round() as u64);
+        }
+
+        w.option(self.campaign.as_ref(), |w, campaign| {
+            w.len(campaign.stages.len());
+            for (level, goal) in campaign.stages.iter() {
+                w.level(level);
+                w.goal(*goal);
+            }
+            w.len(campaign.current);
+            w.varint(campaign.apples as u64);
+        });
+        w.variant(self.input_mode);
+        w.bool(self.assist);
+        w.bool(self.combos);
+        w.into_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Snapshot, String> {
+        let mut r = Reader::new(bytes);
+        r.header(MAGIC, SNAPSHOT_VERSION, "snapshot")?;
+
+        let width = r.u32()?;
+        let height = r.u32()?;
+        let len = width
+            .checked_mul(height)
+            .ok_or_else(|| format!("invalid universe size {}x{}", width, height))?;
+        let bitmap = r.take((len as usize).div_ceil(8))?;
+        let walls = (0..len).filter(|&idx| bitmap[idx as usize / 8] & (1 << (idx % 8)) != 0).collect();
+        let portals = r.list(|r| Ok((r.u32()?, r.u32()?)))?;
+        let hazards = r.list(Reader::hazard)?;
+        let terrain = r.list(|r| Ok((r.u32()?, r.variant()?)))?;
+        let shrink = Shrink {
+            interval: r.u32()?,
+            countdown: r.u32()?,
+            rings: r.u32()?,
+            closed: r.list(Reader::u32)?,
+            portals: r.list(|r| Ok((r.u32()?, r.u32()?)))?,
+            hazards: r.list(Reader::hazard)?,
+        };
+        let mode = r.game_mode()?;
+        let time_left_ms = r.f64()?;
+
+        let players = r.list(|r| {
+            Ok(PlayerState {
+                snake: r.snake()?,
+                spawn: r.snake()?,
+                score: r.u32()?,
+                alive: r.bool()?,
+                death: r.option(Reader::variant)?,
+                hunger_ticks: r.u32()?,
+                respawn_in: r.u32()?,
+                combo: r
+                    .option(|r| {
+                        Ok(Combo {
+                            chain: r.u32()?,
+                            multiplier: r.u32()?,
+                            idle_ticks: r.u32()?,
+                        })
+                    })?
+                    .unwrap_or_default(),
+                lives: r.u32()?,
+                spawned_at: r.u32()?,
+                mired: r.bool()?,
+            })
+        })?;
+        let lives = r.u32()?;
+        let countdown_seconds = r.u32()?;
+        let countdown_ms = r.option(Reader::f64)?.unwrap_or(0.0);
+        let collision_policy = r.variant()?;
+        let match_mode = r.variant()?;
+        let winner = r.option(Reader::u32)?;
+
+        let foods = r.list(|r| {
+            Ok(Food {
+                position: r.position()?,
+                kind: r.variant()?,
+                ticks_left: r.option(Reader::u32)?,
+            })
+        })?;
+        let apple_count = r.u32()?;
+        let special_food = r.bool()?;
+        let speed_boost = r.u32()?;
+
+        let mut power_ups = PowerUps::default();
+        for _ in 0..r.len()? {
+            let kind = r.variant()?;
+            power_ups.activate(kind, r.u32()?);
+        }
+        let overlaps = r.u32()?;
+        let hunger_interval = r.u32()?;
+
+        let speed = SpeedModel {
+            curve: r.variant()?,
+            base: r.f64()?,
+            step: r.f64()?,
+            max: r.f64()?,
+        };
+        let scoring = r
+            .option(|r| {
+                Ok(Scoring {
+                    apple: r.u32()?,
+                    tick: r.u32()?,
+                    milestone: r.u32()?,
+                    milestone_length: r.u32()?,
+                    speed_bonus: r.f64()?,
+                })
+            })?
+            .unwrap_or_default();
+
+        let state = r.variant()?;
+        let game_over_reason = r.option(Reader::variant)?;
+        let topology = r.topology()?;
+        let seed = r.word()?;
+        let rng = Rng::from_state([r.word()?, r.word()?, r.word()?, r.word()?]);
+
+        let stats = Stats {
+            score: r.u32()?,
+            apples_eaten: r.u32()?,
+            ticks: r.u32()?,
+            length: r.u32()?,
+            max_length: r.u32()?,
+            elapsed_ms: r.u32()? as f64,
+            game_ms: r.u32()? as f64,
+            splits: r.list(|r| Ok(r.u32()? as f64))?,
+        };
+
+        let campaign = r.option(|r| {
+            Ok(Campaign {
+                stages: Rc::new(r.list(|r| Ok((r.level()?, r.goal()?)))?),
+                current: r.u32()? as usize,
+                apples: r.u32()?,
+            })
+        })?;
+        let input_mode = r.variant()?;
+        let assist = r.bool()?;
+        let combos = r.bool()?;
+
+        r.finish()?;
+
+        Ok(Snapshot {
+            width,
+            height,
+            walls: Rc::new(walls),
+            portals: Rc::new(portals),
+            hazards,
+            terrain: Rc::new(terrain),
+            shrink,
+            mode,
+            time_left_ms,
+            players,
+            lives,
+            countdown_seconds,
+            countdown_ms,
+            collision_policy,
+            match_mode,
+            winner,
+            foods,
+            apple_count,
+            special_food,
+            speed_boost,
+            power_ups,
+            overlaps,
+            hunger_interval,
+            speed,
+            scoring,
+            state,
+            game_over_reason,
+            topology,
+            seed,
+            rng,
+            stats,
+            campaign,
+            input_mode,
+            assist,
+            combos,
+        })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // `save_state` in the binary format, small enough to stream every tick.
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.save_state().to_bytes()
+    }
+
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.load_state(Snapshot::from_bytes(bytes)?)
+    }
+}
+
+// === Serde ===
+#[cfg(feature = "serde")]
+impl Serialize for Universe {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.save_state().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Universe {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Universe, D::Error> {
+        Universe::from_snapshot(Snapshot::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("game state serializes to JSON")
+    }
+
+    pub fn from_json(json: &str) -> Result<Universe, String> {
+        serde_json::from_str(json).map_err(|err| err.to_string())
+    }
+}
@@ -0,0 +1,91 @@
+use crate::food::FoodKind;
+use crate::Universe;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Cues are dropped oldest first past this, like `effects::MAX_QUEUED_EFFECTS`.
+pub const MAX_QUEUED_SOUNDS: usize = 256;
+// Seconds left in a timed match from which every second beeps.
+pub const COUNTDOWN_SECONDS: u32 = 5;
+// Ticks before the arena shrinks from which every tick beeps.
+pub const SHRINK_WARNING_TICKS: u32 = 3;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    Eat,
+    Turn,
+    Die,
+    LevelUp,
+    // Time running out or the arena about to close; `count` says how soon.
+    Countdown,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SoundCue {
+    sound: Sound,
+    player: u32,
+    pitch: f64,
+    count: u32,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SoundCue {
+    pub fn sound(&self) -> Sound {
+        self.sound
+    }
+
+    pub fn player(&self) -> u32 {
+        self.player
+    }
+
+    // A playback rate around 1: better food eats higher, poison lower.
+    pub fn pitch(&self) -> f64 {
+        self.pitch
+    }
+
+    // Seconds or ticks left for a `Countdown`, the level just completed for
+    // `LevelUp`.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Cues since the last call, oldest first.
+    pub fn take_sounds(&mut self) -> Vec<SoundCue> {
+        std::mem::take(&mut self.sounds)
+    }
+}
+
+impl Universe {
+    pub(crate) fn cue(&mut self, sound: Sound, player: usize, pitch: f64, count: u32) {
+        if self.silent {
+            return;
+        }
+        if self.sounds.len() == MAX_QUEUED_SOUNDS {
+            self.sounds.remove(0);
+        }
+        self.sounds.push(SoundCue {
+            sound,
+            player: player as u32,
+            pitch,
+            count,
+        });
+        self.buzz(sound, player);
+        if sound == Sound::Countdown {
+            self.narrate_countdown(count);
+        }
+    }
+
+    pub(crate) fn eat_pitch(&self, kind: FoodKind) -> f64 {
+        match kind {
+            FoodKind::Normal => 1.0,
+            FoodKind::Speed | FoodKind::PowerUp => 1.25,
+            FoodKind::Golden => 1.5,
+            FoodKind::Poison => 0.75,
+        }
+    }
+}
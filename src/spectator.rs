@@ -0,0 +1,174 @@
+use crate::hash::checksum;
+use crate::snapshot::{Reader, Snapshot, Writer};
+use crate::{Cell, GameState, Universe};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// A delta is "SNDL" and a version byte, the length and checksum of the frame
+// it applies to, the new length, then runs of bytes kept from the previous
+// frame each followed by a literal run of changed bytes.
+const MAGIC: &[u8; 4] = b"SNDL";
+pub const DELTA_VERSION: u8 = 1;
+pub const DEFAULT_KEYFRAME_INTERVAL: u32 = 60;
+
+// Unchanged runs shorter than this are cheaper to send as part of a literal.
+const MIN_KEEP: usize = 4;
+
+// The broadcasting side of a spectated game. Frames are binary snapshots
+// and deltas against the previous frame; every `keyframe_interval`th frame
+// is a full snapshot so spectators can join late or recover from a gap.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct SnapshotStream {
+    keyframe_interval: u32,
+    frames: u32,
+    last: Vec<u8>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl SnapshotStream {
+    pub fn new(keyframe_interval: u32) -> SnapshotStream {
+        SnapshotStream {
+            keyframe_interval: keyframe_interval.max(1),
+            frames: 0,
+            last: Vec::new(),
+        }
+    }
+
+    pub fn next_frame(&mut self, universe: &Universe) -> Vec<u8> {
+        let bytes = universe.snapshot();
+        let frame = if self.frames.is_multiple_of(self.keyframe_interval) {
+            bytes.clone()
+        } else {
+            delta(&self.last, &bytes)
+        };
+        self.frames += 1;
+        self.last = bytes;
+        frame
+    }
+
+    // Makes the next frame a full snapshot, e.g. when a spectator joins.
+    pub fn request_keyframe(&mut self) {
+        self.frames = 0;
+    }
+}
+
+// A read-only view of someone else's game. It never simulates; each frame
+// pushed in replaces the state and the usual rendering accessors show it.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Spectator {
+    universe: Universe,
+    last: Vec<u8>,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Starts spectating from a keyframe.
+    pub fn from_snapshot_stream(keyframe: &[u8]) -> Result<Spectator, String> {
+        Ok(Spectator {
+            universe: Universe::from_snapshot(Snapshot::from_bytes(keyframe)?)?,
+            last: keyframe.to_vec(),
+        })
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Spectator {
+    // Applies a keyframe or delta. A delta for some other frame than the
+    // last one applied is rejected; wait for the next keyframe.
+    pub fn push(&mut self, frame: &[u8]) -> Result<(), String> {
+        let bytes = if frame.starts_with(MAGIC) {
+            apply(&self.last, frame)?
+        } else {
+            frame.to_vec()
+        };
+        self.universe.load_state(Snapshot::from_bytes(&bytes)?)?;
+        self.last = bytes;
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.universe.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.universe.height()
+    }
+
+    pub fn cells(&self) -> *const Cell {
+        self.universe.cells()
+    }
+
+    pub fn render(&self) -> String {
+        self.universe.render()
+    }
+
+    pub fn score(&self) -> u32 {
+        self.universe.score()
+    }
+
+    pub fn state(&self) -> GameState {
+        self.universe.state()
+    }
+}
+
+impl Spectator {
+    pub fn universe(&self) -> &Universe {
+        &self.universe
+    }
+}
+
+fn delta(base: &[u8], bytes: &[u8]) -> Vec<u8> {
+    let mut w = Writer::default();
+    w.header(MAGIC, DELTA_VERSION);
+    w.len(base.len());
+    w.word(checksum(base));
+    w.len(bytes.len());
+
+    let same = |i: usize| base.get(i) == bytes.get(i);
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        while i < bytes.len() && same(i) {
+            i += 1;
+        }
+        w.len(i - start);
+
+        let literal = i;
+        while i < bytes.len() && !(i..i + MIN_KEEP).all(|j| j >= bytes.len() || same(j)) {
+            i += 1;
+        }
+        w.len(i - literal);
+        w.raw(&bytes[literal..i]);
+    }
+    w.into_bytes()
+}
+
+fn apply(base: &[u8], frame: &[u8]) -> Result<Vec<u8>, String> {
+    let mut r = Reader::new(frame);
+    r.header(MAGIC, DELTA_VERSION, "delta")?;
+    if r.u32()? as usize != base.len() || r.word()? != checksum(base) {
+        return Err("delta does not follow the last frame".to_string());
+    }
+
+    let len = r.u32()? as usize;
+    let mut bytes = Vec::new();
+    while bytes.len() < len {
+        let keep = r.u32()? as usize;
+        let kept = base
+            .get(bytes.len()..bytes.len() + keep)
+            .ok_or_else(|| "delta keeps bytes past the last frame".to_string())?;
+        bytes.extend_from_slice(kept);
+
+        let literal = r.len()?;
+        if keep + literal == 0 {
+            return Err("delta makes no progress".to_string());
+        }
+        bytes.extend_from_slice(r.take(literal)?);
+    }
+    r.finish()?;
+
+    if bytes.len() != len {
+        return Err("delta overruns the frame length".to_string());
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+pub const DEFAULT_TICKS_PER_SECOND: f64 = 10.0;
+pub const DEFAULT_MAX_TICKS_PER_SECOND: f64 = 30.0;
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SpeedCurve {
+    Constant,
+    Linear,
+    Exponential,
+}
+
+// `step` is ticks/second added per apple for `Linear`, and the growth rate
+// per apple for `Exponential` (0.05 = 5% faster per apple).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SpeedModel {
+    pub curve: SpeedCurve,
+    pub base: f64,
+    pub step: f64,
+    pub max: f64,
+}
+
+impl SpeedModel {
+    pub fn ticks_per_second(&self, apples: u32) -> f64 {
+        let tps = match self.curve {
+            SpeedCurve::Constant => self.base,
+            SpeedCurve::Linear => self.base + self.step * apples as f64,
+            SpeedCurve::Exponential => self.base * (1.0 + self.step).powf(apples as f64),
+        };
+        tps.min(self.max).max(f64::EPSILON)
+    }
+
+    // A speed that never slows down and tops out at or above where it starts.
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !(self.base.is_finite() && self.base > 0.0) {
+            return Err(format!("invalid base speed {}", self.base));
+        }
+        if !(self.max.is_finite() && self.max >= self.base) {
+            return Err(format!("top speed {} is below the base speed {}", self.max, self.base));
+        }
+        if !(self.step.is_finite() && self.step >= 0.0) {
+            return Err(format!("invalid speed step {}", self.step));
+        }
+        Ok(())
+    }
+}
+
+impl Default for SpeedModel {
+    fn default() -> SpeedModel {
+        SpeedModel {
+            curve: SpeedCurve::Constant,
+            base: DEFAULT_TICKS_PER_SECOND,
+            step: 0.0,
+            max: DEFAULT_MAX_TICKS_PER_SECOND,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use wasm_bindgen::prelude::*;
+
+// Thin wrappers over `window.localStorage`. Reading treats a missing or
+// blocked store like an empty one.
+fn local_storage() -> Result<web_sys::Storage, JsValue> {
+    let window = web_sys::window().ok_or_else(|| JsValue::from_str("no window available"))?;
+    window
+        .local_storage()?
+        .ok_or_else(|| JsValue::from_str("localStorage is not available"))
+}
+
+pub(crate) fn load(key: &str) -> Option<String> {
+    local_storage().ok()?.get_item(key).ok()?
+}
+
+pub(crate) fn save(key: &str, value: &str) -> Result<(), JsValue> {
+    local_storage()?.set_item(key, value)
+}
+
+pub(crate) fn remove(key: &str) -> Result<(), JsValue> {
+    local_storage()?.remove_item(key)
+}
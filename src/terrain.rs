@@ -0,0 +1,126 @@
+use crate::food::Food;
+use crate::{Direction, DirectionName, GameState, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Ground under the board's cells, kept apart from what stands on them: a
+// snake, food or wall can sit on any terrain. It only changes how snakes
+// move over it.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Terrain {
+    Plain = 0,
+    // A head on ice slides on: turns wait until it is off.
+    Ice = 1,
+    // A head in mud only moves every other tick.
+    Mud = 2,
+    // A head that moves onto a conveyor is carried one cell further along
+    // it, if that cell is clear; a blocked belt doesn't push.
+    ConveyorUp = 3,
+    ConveyorDown = 4,
+    ConveyorLeft = 5,
+    ConveyorRight = 6,
+}
+
+impl Terrain {
+    fn conveyor(self) -> Option<DirectionName> {
+        match self {
+            Terrain::ConveyorUp => Some(DirectionName::Up),
+            Terrain::ConveyorDown => Some(DirectionName::Down),
+            Terrain::ConveyorLeft => Some(DirectionName::Left),
+            Terrain::ConveyorRight => Some(DirectionName::Right),
+            Terrain::Plain | Terrain::Ice | Terrain::Mud => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Lays `terrain` under (`x`, `y`); `Terrain::Plain` takes it up again.
+    // Terrain stays through `reset` like walls do. False off the board.
+    pub fn set_terrain(&mut self, x: u32, y: u32, terrain: Terrain) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+
+        let idx = self.get_index(y, x) as u32;
+        self.board_edits = self.board_edits.wrapping_add(1);
+        if terrain == Terrain::Plain {
+            self.terrain.remove(&idx);
+        } else {
+            self.terrain.insert(idx, terrain);
+        }
+        true
+    }
+
+    pub fn terrain_at(&self, x: u32, y: u32) -> Terrain {
+        if x >= self.width || y >= self.height {
+            return Terrain::Plain;
+        }
+        self.terrain_under(self.get_index(y, x))
+    }
+
+    pub fn clear_terrain(&mut self) {
+        self.terrain.clear();
+        self.board_edits = self.board_edits.wrapping_add(1);
+    }
+
+    // The terrain layer, one `Terrain` a byte laid out like `cells`, for
+    // the renderer to draw under the board.
+    pub fn terrain_u8(&self) -> Vec<u8> {
+        let mut layer = vec![Terrain::Plain as u8; self.cells.len()];
+        for (&idx, &terrain) in &self.terrain {
+            layer[idx as usize] = terrain as u8;
+        }
+        layer
+    }
+}
+
+impl Universe {
+    fn terrain_under(&self, idx: usize) -> Terrain {
+        self.terrain.get(&(idx as u32)).copied().unwrap_or(Terrain::Plain)
+    }
+
+    fn head_terrain(&self, id: usize) -> Terrain {
+        let head = self.players[id].snake.body.front().unwrap();
+        self.terrain_under(self.get_index(head.y, head.x))
+    }
+
+    pub(crate) fn on_ice(&self, id: usize) -> bool {
+        self.head_terrain(id) == Terrain::Ice
+    }
+
+    // A head in mud sits out every other tick, starting with the one after
+    // it got there.
+    pub(crate) fn stuck_in_mud(&mut self, id: usize) -> bool {
+        let in_mud = self.players[id].alive && self.head_terrain(id) == Terrain::Mud;
+        let player = &mut self.players[id];
+        player.mired = in_mud && !player.mired;
+        player.mired
+    }
+
+    // Carries the snakes in `moved` that landed on a conveyor one cell
+    // along it, heading unchanged. Returns who rode and what they ate.
+    pub(crate) fn ride_conveyors(&mut self, moved: &[usize]) -> Vec<(usize, Option<Food>)> {
+        let mut rides = Vec::new();
+        for &id in moved {
+            if self.state != GameState::Running {
+                break;
+            }
+            let belt = match self.head_terrain(id).conveyor() {
+                Some(belt) => Direction::from_name(belt),
+                None => continue,
+            };
+            if let Ok(target) = self.step_towards(id, belt) {
+                if !self.occupied.contains(self.get_index(target.y, target.x)) {
+                    rides.push((id, self.move_snake(id, target)));
+                }
+            }
+        }
+        rides
+    }
+}
@@ -0,0 +1,234 @@
+use crate::{Cell, Universe};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::*;
+
+// Red, green, blue and alpha from 0 to 255.
+pub type Color = [u8; 4];
+
+const fn rgb(hex: u32) -> Color {
+    [(hex >> 16) as u8, (hex >> 8) as u8, hex as u8, 0xFF]
+}
+
+const CLASSIC_GLYPHS: &str = "◻◆◼●▩✖★➤✚◌◎◆";
+// Plain ASCII with no two cells alike, for terminals and screen readers.
+const ASCII_GLYPHS: &str = ".@o*#x$>+~O!";
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemePreset {
+    // Green snake on white, as the game always looked.
+    Classic,
+    Dark,
+    // Saturated colors on black and ASCII glyphs.
+    HighContrast,
+    // The Okabe-Ito palette, told apart with any color vision.
+    ColorblindSafe,
+}
+
+// How the board looks: a color per cell for the pixel renderers, grid lines
+// between cells if any, and the glyphs `render` prints.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Theme {
+    background: Color,
+    snake_head: Color,
+    snake_body: Color,
+    apple: Color,
+    wall: Color,
+    poison: Color,
+    golden: Color,
+    speed: Color,
+    power_up: Color,
+    ghost: Color,
+    portal: Color,
+    hazard: Color,
+    grid: Option<Color>,
+    // One per cell, in the order of `Cell`.
+    glyphs: String,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Theme {
+    pub fn preset(preset: ThemePreset) -> Theme {
+        match preset {
+            ThemePreset::Classic => Theme {
+                background: rgb(0xFFFFFF),
+                snake_head: rgb(0x1B5E20),
+                snake_body: rgb(0x43A047),
+                apple: rgb(0xE53935),
+                wall: rgb(0x424242),
+                poison: rgb(0x8E24AA),
+                golden: rgb(0xFFC107),
+                speed: rgb(0x29B6F6),
+                power_up: rgb(0xAB47BC),
+                ghost: rgb(0xCFD8DC),
+                portal: rgb(0x00ACC1),
+                hazard: rgb(0xFF5722),
+                grid: None,
+                glyphs: CLASSIC_GLYPHS.to_string(),
+            },
+            ThemePreset::Dark => Theme {
+                background: rgb(0x121212),
+                snake_head: rgb(0xA5D6A7),
+                snake_body: rgb(0x4CAF50),
+                apple: rgb(0xEF5350),
+                wall: rgb(0x616161),
+                poison: rgb(0xBA68C8),
+                golden: rgb(0xFFD54F),
+                speed: rgb(0x4FC3F7),
+                power_up: rgb(0xCE93D8),
+                ghost: rgb(0x37474F),
+                portal: rgb(0x26C6DA),
+                hazard: rgb(0xFF7043),
+                grid: Some(rgb(0x1E1E1E)),
+                glyphs: CLASSIC_GLYPHS.to_string(),
+            },
+            ThemePreset::HighContrast => Theme {
+                background: rgb(0x000000),
+                snake_head: rgb(0xFFFFFF),
+                snake_body: rgb(0x00FF00),
+                apple: rgb(0xFF0000),
+                wall: rgb(0x808080),
+                poison: rgb(0xFF00FF),
+                golden: rgb(0xFFFF00),
+                speed: rgb(0x00FFFF),
+                power_up: rgb(0xFF8000),
+                ghost: rgb(0x404040),
+                portal: rgb(0x0080FF),
+                hazard: rgb(0xFF0080),
+                grid: None,
+                glyphs: ASCII_GLYPHS.to_string(),
+            },
+            ThemePreset::ColorblindSafe => Theme {
+                background: rgb(0xFFFFFF),
+                snake_head: rgb(0x0072B2),
+                snake_body: rgb(0x56B4E9),
+                apple: rgb(0xD55E00),
+                wall: rgb(0x000000),
+                poison: rgb(0xCC79A7),
+                golden: rgb(0xF0E442),
+                speed: rgb(0x009E73),
+                power_up: rgb(0xE69F00),
+                ghost: rgb(0xDDDDDD),
+                portal: rgb(0x999999),
+                hazard: rgb(0x661100),
+                grid: Some(rgb(0xEEEEEE)),
+                glyphs: CLASSIC_GLYPHS.to_string(),
+            },
+        }
+    }
+
+    // As a CSS color, e.g. "rgba(27, 94, 32, 1)".
+    pub fn css(&self, cell: Cell) -> String {
+        css(self.color(cell))
+    }
+
+    pub fn set_color(&mut self, cell: Cell, r: u8, g: u8, b: u8, a: u8) {
+        *self.slot(cell) = [r, g, b, a];
+    }
+
+    pub fn grid_css(&self) -> Option<String> {
+        self.grid.map(css)
+    }
+
+    pub fn set_grid(&mut self, r: u8, g: u8, b: u8, a: u8) {
+        self.grid = Some([r, g, b, a]);
+    }
+
+    pub fn clear_grid(&mut self) {
+        self.grid = None;
+    }
+
+    pub fn glyph(&self, cell: Cell) -> char {
+        self.glyphs.chars().nth(cell as usize).unwrap_or('?')
+    }
+
+    pub fn set_glyph(&mut self, cell: Cell, glyph: char) {
+        let mut glyphs: Vec<char> = self.glyphs.chars().collect();
+        glyphs.resize(CLASSIC_GLYPHS.chars().count(), '?');
+        glyphs[cell as usize] = glyph;
+        self.glyphs = glyphs.into_iter().collect();
+    }
+}
+
+impl Theme {
+    pub fn color(&self, cell: Cell) -> Color {
+        match cell {
+            Cell::Empty => self.background,
+            Cell::SnakeHead => self.snake_head,
+            Cell::SnakeBody => self.snake_body,
+            Cell::Apple => self.apple,
+            Cell::Wall => self.wall,
+            Cell::Poison => self.poison,
+            Cell::Golden => self.golden,
+            Cell::Speed => self.speed,
+            Cell::PowerUp => self.power_up,
+            Cell::Ghost => self.ghost,
+            Cell::Portal => self.portal,
+            Cell::Hazard => self.hazard,
+        }
+    }
+
+    pub fn grid(&self) -> Option<Color> {
+        self.grid
+    }
+
+    fn slot(&mut self, cell: Cell) -> &mut Color {
+        match cell {
+            Cell::Empty => &mut self.background,
+            Cell::SnakeHead => &mut self.snake_head,
+            Cell::SnakeBody => &mut self.snake_body,
+            Cell::Apple => &mut self.apple,
+            Cell::Wall => &mut self.wall,
+            Cell::Poison => &mut self.poison,
+            Cell::Golden => &mut self.golden,
+            Cell::Speed => &mut self.speed,
+            Cell::PowerUp => &mut self.power_up,
+            Cell::Ghost => &mut self.ghost,
+            Cell::Portal => &mut self.portal,
+            Cell::Hazard => &mut self.hazard,
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::preset(ThemePreset::Classic)
+    }
+}
+
+#[cfg(feature = "config")]
+#[wasm_bindgen]
+impl Theme {
+    // A theme from a plain JS object shaped like the serialized `Theme`.
+    pub fn from_js(theme: JsValue) -> Result<Theme, String> {
+        serde_wasm_bindgen::from_value(theme).map_err(|err| err.to_string())
+    }
+
+    pub fn to_js(&self) -> Result<JsValue, String> {
+        serde_wasm_bindgen::to_value(self).map_err(|err| err.to_string())
+    }
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Universe {
+    // Used by `render` and `render_rgba`.
+    pub fn theme(&self) -> Theme {
+        self.theme.clone()
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
+    pub fn set_theme_preset(&mut self, preset: ThemePreset) {
+        self.theme = Theme::preset(preset);
+    }
+}
+
+pub(crate) fn css([r, g, b, a]: Color) -> String {
+    format!("rgba({}, {}, {}, {})", r, g, b, a as f64 / 255.0)
+}
@@ -0,0 +1,195 @@
+use crate::snapshot::Variants;
+use crate::theme::Theme;
+use crate::{Cell, Universe};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{
+    HtmlCanvasElement, WebGl2RenderingContext as Gl, WebGlProgram, WebGlShader, WebGlTexture, WebGlUniformLocation,
+};
+
+// One triangle covering the viewport, made up from the vertex index so no
+// vertex buffers are needed.
+const VERTEX_SHADER: &str = r#"#version 300 es
+out vec2 uv;
+void main() {
+    vec2 p = vec2(float((gl_VertexID << 1) & 2), float(gl_VertexID & 2));
+    uv = vec2(p.x, 1.0 - p.y);
+    gl_Position = vec4(p * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+// Looks each pixel's cell up in the board texture and paints it from the
+// palette, or with the grid color on the last pixel of a cell when the grid
+// is opaque at all.
+const FRAGMENT_SHADER: &str = r#"#version 300 es
+precision highp float;
+precision highp usampler2D;
+uniform usampler2D cells;
+uniform vec4 palette[12];
+uniform vec4 grid;
+in vec2 uv;
+out vec4 color;
+void main() {
+    ivec2 size = textureSize(cells, 0);
+    vec2 board = uv * vec2(size);
+    ivec2 cell = min(ivec2(board), size - 1);
+    color = palette[texelFetch(cells, cell, 0).r];
+    vec2 edge = fract(board) + fwidth(board);
+    if (grid.a > 0.0 && (edge.x >= 1.0 || edge.y >= 1.0)) {
+        color = grid;
+    }
+}
+"#;
+
+// Draws the whole board with WebGL2 in a single call, for boards too big
+// for `CanvasRenderer`. The cells go up as a one-byte-per-cell texture each
+// frame and the shader colors them, stretched over the canvas as it is
+// sized; keep the canvas a multiple of the board for square cells.
+#[wasm_bindgen]
+pub struct GlRenderer {
+    canvas: HtmlCanvasElement,
+    gl: Gl,
+    program: WebGlProgram,
+    texture: WebGlTexture,
+    palette: WebGlUniformLocation,
+    colors: Vec<f32>,
+    grid: [f32; 4],
+    texture_size: (u32, u32),
+    bytes: Vec<u8>,
+}
+
+#[wasm_bindgen]
+impl GlRenderer {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement) -> Result<GlRenderer, JsValue> {
+        let gl = canvas
+            .get_context("webgl2")?
+            .ok_or_else(|| JsValue::from_str("canvas has no webgl2 context"))?
+            .dyn_into::<Gl>()?;
+
+        let vertex = compile(&gl, Gl::VERTEX_SHADER, VERTEX_SHADER)?;
+        let fragment = compile(&gl, Gl::FRAGMENT_SHADER, FRAGMENT_SHADER)?;
+        let program = link(&gl, &vertex, &fragment)?;
+        let palette = gl
+            .get_uniform_location(&program, "palette")
+            .ok_or_else(|| JsValue::from_str("shader has no palette"))?;
+        let texture = gl
+            .create_texture()
+            .ok_or_else(|| JsValue::from_str("can't create a texture"))?;
+
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&texture));
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MIN_FILTER, Gl::NEAREST as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_MAG_FILTER, Gl::NEAREST as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_S, Gl::CLAMP_TO_EDGE as i32);
+        gl.tex_parameteri(Gl::TEXTURE_2D, Gl::TEXTURE_WRAP_T, Gl::CLAMP_TO_EDGE as i32);
+        gl.pixel_storei(Gl::UNPACK_ALIGNMENT, 1);
+
+        let mut renderer = GlRenderer {
+            canvas,
+            gl,
+            program,
+            texture,
+            palette,
+            colors: Vec::new(),
+            grid: [0.0; 4],
+            texture_size: (0, 0),
+            bytes: Vec::new(),
+        };
+        renderer.set_theme(&Theme::default());
+        Ok(renderer)
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.colors = Cell::VARIANTS
+            .iter()
+            .flat_map(|&cell| theme.color(cell).to_vec())
+            .map(unit)
+            .collect();
+        self.grid = theme.grid().map_or([0.0; 4], |grid| grid.map(unit));
+    }
+
+    // Channels from 0 to 255, like `render_rgba` paints them.
+    pub fn set_color(&mut self, cell: Cell, r: u8, g: u8, b: u8, a: u8) {
+        let start = cell as usize * 4;
+        for (slot, channel) in self.colors[start..start + 4].iter_mut().zip([r, g, b, a].iter()) {
+            *slot = unit(*channel);
+        }
+    }
+
+    pub fn draw(&mut self, universe: &Universe) -> Result<(), JsValue> {
+        let gl = &self.gl;
+        self.bytes.clear();
+        self.bytes.extend(universe.cells.iter().map(|cell| cell as u8));
+
+        gl.active_texture(Gl::TEXTURE0);
+        gl.bind_texture(Gl::TEXTURE_2D, Some(&self.texture));
+        let (width, height) = (universe.width as i32, universe.height as i32);
+        if self.texture_size != (universe.width, universe.height) {
+            gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                Gl::R8UI as i32,
+                width,
+                height,
+                0,
+                Gl::RED_INTEGER,
+                Gl::UNSIGNED_BYTE,
+                Some(&self.bytes),
+            )?;
+            self.texture_size = (universe.width, universe.height);
+        } else {
+            gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_opt_u8_array(
+                Gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                width,
+                height,
+                Gl::RED_INTEGER,
+                Gl::UNSIGNED_BYTE,
+                Some(&self.bytes),
+            )?;
+        }
+
+        gl.viewport(0, 0, self.canvas.width() as i32, self.canvas.height() as i32);
+        gl.use_program(Some(&self.program));
+        gl.uniform1i(gl.get_uniform_location(&self.program, "cells").as_ref(), 0);
+        gl.uniform4fv_with_f32_array(Some(&self.palette), &self.colors);
+        gl.uniform4fv_with_f32_array(gl.get_uniform_location(&self.program, "grid").as_ref(), &self.grid);
+        gl.draw_arrays(Gl::TRIANGLES, 0, 3);
+        Ok(())
+    }
+}
+
+fn unit(channel: u8) -> f32 {
+    channel as f32 / 255.0
+}
+
+fn compile(gl: &Gl, kind: u32, source: &str) -> Result<WebGlShader, JsValue> {
+    let shader = gl
+        .create_shader(kind)
+        .ok_or_else(|| JsValue::from_str("can't create a shader"))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if gl.get_shader_parameter(&shader, Gl::COMPILE_STATUS).as_bool() == Some(true) {
+        Ok(shader)
+    } else {
+        Err(JsValue::from_str(&gl.get_shader_info_log(&shader).unwrap_or_default()))
+    }
+}
+
+fn link(gl: &Gl, vertex: &WebGlShader, fragment: &WebGlShader) -> Result<WebGlProgram, JsValue> {
+    let program = gl
+        .create_program()
+        .ok_or_else(|| JsValue::from_str("can't create a program"))?;
+    gl.attach_shader(&program, vertex);
+    gl.attach_shader(&program, fragment);
+    gl.link_program(&program);
+
+    if gl.get_program_parameter(&program, Gl::LINK_STATUS).as_bool() == Some(true) {
+        Ok(program)
+    } else {
+        Err(JsValue::from_str(&gl.get_program_info_log(&program).unwrap_or_default()))
+    }
+}
@@ -0,0 +1,1950 @@
+//! Native test suite for the game rules.
+
+use rust_snake_wasm::config::Config;
+use rust_snake_wasm::daily::{self, DailyChallenge};
+use rust_snake_wasm::effects::EffectKind;
+use rust_snake_wasm::levels::{self, Campaign, Level};
+use rust_snake_wasm::ai::{self, AiStrategy};
+use rust_snake_wasm::env::{self, Env};
+use rust_snake_wasm::fog;
+use rust_snake_wasm::food::{self, FoodKind};
+use rust_snake_wasm::hazards::HazardKind;
+use rust_snake_wasm::highscores::HighScores;
+use rust_snake_wasm::input::{self, KeyMap, TouchController};
+use rust_snake_wasm::modes::GameMode;
+use rust_snake_wasm::netcode::{LatePolicy, Lockstep, Message};
+use rust_snake_wasm::powerups::PowerUp;
+use rust_snake_wasm::presets::Preset;
+use rust_snake_wasm::replay::{self, Replay};
+use rust_snake_wasm::rules::{Collision, Rules};
+use rust_snake_wasm::scoring::Scoring;
+use rust_snake_wasm::speed::{SpeedCurve, SpeedModel};
+use rust_snake_wasm::sim;
+use rust_snake_wasm::sounds::Sound;
+use rust_snake_wasm::spectator::SnapshotStream;
+use rust_snake_wasm::terrain::Terrain;
+use rust_snake_wasm::theme::{Theme, ThemePreset};
+use rust_snake_wasm::{
+    Cell, CollisionPolicy, DirectionName, GameOverReason, GameState, InputMode, MatchMode, Position, Snake, Universe,
+    UniverseTopology,
+};
+use std::rc::Rc;
+
+fn seeded(seed: u64) -> Universe {
+    Universe::new_with_seed(Snake::new(), 60.0, 16, 16, seed).unwrap()
+}
+
+// A game with special food on whose first food is `kind`.
+fn seeded_with_food(kind: FoodKind, keep: impl Fn(&Universe) -> bool) -> Universe {
+    (0..)
+        .map(|seed| {
+            let mut universe = seeded(seed);
+            universe.set_special_food(true);
+            universe.reset();
+            universe
+        })
+        .find(|universe| universe.foods()[0].kind() == kind && keep(universe))
+        .unwrap()
+}
+
+// Steers straight at the first food, wrapping round the board when it has
+// to, and stops on the tick it is eaten.
+fn eat_first_food(universe: &mut Universe) {
+    let food = universe.foods()[0].position();
+    while universe.foods().iter().any(|other| other.position() == food) {
+        let head = universe.snake_head();
+        let direction = if head.x() < food.x() {
+            DirectionName::Right
+        } else if head.x() > food.x() {
+            DirectionName::Left
+        } else if head.y() < food.y() {
+            DirectionName::Down
+        } else {
+            DirectionName::Up
+        };
+        universe.on_click(direction);
+        universe.tick(0);
+        assert!(!universe.is_game_over());
+    }
+}
+
+#[test]
+fn same_seed_replays_same_game() {
+    let mut a = seeded(7);
+    let mut b = seeded(7);
+
+    for _ in 0..50 {
+        a.tick(0);
+        b.tick(0);
+        assert_eq!(a.render(), b.render());
+    }
+}
+
+#[test]
+fn snake_must_fit_the_board() {
+    assert!(Universe::new_with_seed(Snake::new(), 60.0, 4, 4, 1).is_err());
+}
+
+#[test]
+fn reversing_into_the_neck_is_ignored() {
+    let mut universe = seeded(3);
+    universe.on_click(DirectionName::Left);
+
+    for _ in 0..3 {
+        universe.tick(0);
+    }
+
+    assert!(!universe.is_game_over());
+}
+
+#[test]
+fn paused_universe_does_not_tick() {
+    let mut universe = seeded(5);
+    universe.pause();
+    let frame = universe.render();
+
+    universe.tick(0);
+    assert_eq!(universe.state(), GameState::Paused);
+    assert_eq!(universe.render(), frame);
+
+    universe.resume();
+    universe.tick(0);
+    assert_ne!(universe.render(), frame);
+}
+
+#[test]
+fn reset_starts_a_fresh_game() {
+    let mut universe = seeded(11);
+    universe.toggle_topology();
+
+    while !universe.is_game_over() {
+        universe.tick(0);
+    }
+
+    universe.reset();
+    assert_eq!(universe.state(), GameState::Running);
+    assert_eq!(universe.score(), 0);
+    universe.tick(0);
+    assert!(!universe.is_game_over());
+}
+
+#[test]
+fn tick_reports_wall_death() {
+    let mut universe = seeded(13);
+    universe.toggle_topology();
+
+    let mut result = universe.tick(0);
+    while result.moved() {
+        result = universe.tick(0);
+    }
+
+    assert!(result.died());
+    assert_eq!(result.died_reason(), Some(GameOverReason::HitWall));
+}
+
+#[test]
+fn biting_yourself_is_reported() {
+    let mut universe = seeded(17);
+    universe.on_click(DirectionName::Down);
+    universe.tick(0);
+    universe.on_click(DirectionName::Left);
+    universe.tick(0);
+    universe.on_click(DirectionName::Up);
+    universe.tick(0);
+
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::HitSelf));
+}
+
+#[test]
+fn changed_cells_cover_head_and_tail() {
+    let mut universe = seeded(19);
+    universe.tick(0);
+
+    let changed = universe.changed_cells();
+    assert!(changed.len() >= 6);
+    assert!(changed.chunks(2).any(|pair| pair[1] == Cell::SnakeHead as u32));
+}
+
+#[test]
+fn rgba_buffer_matches_scaled_board() {
+    let mut universe = seeded(23);
+    universe.render_rgba(3);
+
+    assert_eq!(universe.rgba_width(), 48);
+    assert_eq!(universe.rgba_len(), 48 * 48 * 4);
+}
+
+#[test]
+fn walls_kill_the_snake() {
+    let mut universe = seeded(29);
+    assert!(universe.set_wall(7, 6));
+    assert!(!universe.set_wall(4, 6));
+
+    universe.tick(0);
+    universe.tick(0);
+
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::HitObstacle));
+}
+
+#[test]
+fn map_loader_builds_the_board() {
+    let map = "
+        8#
+        #6.#
+        #.S...A#
+        #6.#
+        8#
+    ";
+    let universe = Universe::from_map_with_seed(map, 31).unwrap();
+
+    assert_eq!((universe.width(), universe.height()), (8, 5));
+    assert!(universe.is_wall(0, 2));
+    assert!(Universe::from_map_with_seed("#.#", 31).is_err());
+}
+
+#[test]
+fn filling_the_board_wins() {
+    let mut universe = Universe::from_map_with_seed("3.SA", 11).unwrap();
+    universe.tick(0);
+
+    assert!(universe.is_game_over());
+    assert_eq!(universe.state(), GameState::Victory);
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::BoardFull));
+    assert_eq!(universe.winner(), Some(0));
+    assert_eq!(universe.snake_length(), 5);
+}
+
+#[test]
+fn oversized_maps_are_rejected() {
+    assert!(Level::parse("4294967295.S").is_err());
+    assert!(Level::parse("99999999999.S").is_err());
+    assert!(Level::parse("1048576.S").is_err());
+    assert!(Level::parse(&format!("{}1023.S", "1024.\n".repeat(1024))).is_err());
+    assert!(Level::parse("0.\n0#").is_err());
+    assert!(Level::parse("1048575.S").is_ok());
+}
+
+#[test]
+fn generated_levels_are_playable() {
+    for seed in 0..20 {
+        let level = levels::generate_level(seed, seed as u32 % 11);
+        let universe = Universe::from_level(&level, seed).unwrap();
+        assert!(!universe.is_game_over());
+        assert_eq!(Level::parse(&level.to_map()).unwrap(), level);
+    }
+}
+
+#[test]
+fn campaign_advances_when_goal_is_met() {
+    let mut campaign = Campaign::new();
+    campaign.add_level("10.\n4.SA4.\n10.", 5).unwrap();
+    campaign.add_level("12.\n5.S6.\n12.", 50).unwrap();
+
+    let mut universe = Universe::start_campaign(campaign, 37).unwrap();
+    assert_eq!(universe.current_level(), Some(0));
+
+    universe.tick(0);
+    assert_eq!(universe.current_level(), Some(1));
+    assert_eq!(universe.width(), 12);
+}
+
+#[test]
+fn campaigns_stay_put_when_the_next_level_cannot_load() {
+    let mut campaign = Campaign::new();
+    campaign.add_level("10.\n4.SA4.\n10.", 5).unwrap();
+    campaign.add_level("#S#", 50).unwrap();
+
+    let mut universe = Universe::start_campaign(campaign, 37).unwrap();
+    assert!(!universe.advance_level());
+    assert_eq!(universe.current_level(), Some(0));
+    assert_eq!(universe.width(), 10);
+
+    let bytes = universe.snapshot();
+    assert!(seeded(1).restore(&bytes).is_err());
+}
+
+#[test]
+fn apple_count_controls_board_apples() {
+    let mut universe = seeded(41);
+    let apples = |u: &Universe| u.render().chars().filter(|&c| c == '●').count();
+
+    universe.set_apple_count(5);
+    assert_eq!(apples(&universe), 5);
+    universe.set_apple_count(2);
+    assert_eq!(apples(&universe), 2);
+}
+
+#[test]
+fn invincible_snake_passes_through_itself() {
+    let mut universe = seeded(17);
+    universe.activate_power_up(PowerUp::Invincible, 10);
+    for direction in [DirectionName::Down, DirectionName::Left, DirectionName::Up] {
+        universe.on_click(direction);
+        universe.tick(0);
+    }
+
+    assert!(!universe.is_game_over());
+    assert!(universe.has_effect(PowerUp::Invincible));
+}
+
+#[test]
+fn hungry_snake_starves() {
+    let mut universe = seeded(43);
+    universe.set_hunger(1);
+
+    for _ in 0..3 {
+        universe.tick(0);
+    }
+
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::Starved));
+}
+
+#[test]
+fn advance_runs_fixed_steps() {
+    let mut universe = seeded(47);
+    let step = universe.recommended_frame_delay_ms();
+
+    assert_eq!(universe.advance(step * 0.5), 0);
+    assert_eq!(universe.advance(step * 2.0), 2);
+    assert_eq!(universe.advance(step * 100.0), 8);
+}
+
+#[test]
+fn buffered_keys_turn_the_snake_on_tick() {
+    let mut universe = seeded(8);
+
+    assert!(universe.press_key("s"));
+    assert!(universe.press_key("ArrowLeft"));
+    assert!(!universe.press_key("x"));
+
+    let head = universe.tick(1).new_head_y();
+    assert_eq!(head, 7);
+    let head = universe.tick(1).new_head_x();
+    assert_eq!(head, 4);
+}
+
+#[test]
+fn swipes_map_to_their_dominant_axis() {
+    let mut touch = TouchController::new(20.0);
+
+    assert_eq!(touch.swipe(100.0, 100.0, 110.0, 105.0), None);
+    assert_eq!(touch.swipe(100.0, 100.0, 60.0, 90.0), Some(DirectionName::Left));
+    assert_eq!(touch.swipe(100.0, 100.0, 90.0, 160.0), Some(DirectionName::Down));
+
+    touch.touch_start(0.0, 50.0);
+    assert_eq!(touch.touch_end(0.0, 0.0), Some(DirectionName::Up));
+    assert_eq!(touch.touch_end(0.0, 0.0), None);
+}
+
+#[test]
+fn resting_stick_stays_in_the_dead_zone() {
+    assert_eq!(input::direction_for_stick(0.1, -0.2), None);
+    assert_eq!(input::direction_for_stick(0.9, 0.3), Some(DirectionName::Right));
+    assert_eq!(input::direction_for_stick(-0.2, -0.7), Some(DirectionName::Up));
+}
+
+#[test]
+fn relative_mode_turns_from_the_heading() {
+    let mut universe = seeded(8);
+    universe.set_input_mode(InputMode::Relative);
+
+    universe.on_click(DirectionName::Up);
+    assert_eq!(universe.tick(1).new_head_x(), 6);
+
+    universe.on_click(DirectionName::Right);
+    assert_eq!(universe.tick(1).new_head_y(), 7);
+
+    universe.on_click(DirectionName::Right);
+    assert_eq!(universe.tick(1).new_head_x(), 5);
+}
+
+#[test]
+fn key_map_round_trips_through_json() {
+    let keys = KeyMap::default();
+    assert_eq!(KeyMap::from_json(&keys.to_json()), Ok(keys));
+    assert!(KeyMap::from_json(r#"{"13":"jump"}"#).is_err());
+
+    let mut universe = seeded(8);
+    universe.set_key_map(KeyMap::from_json(r#"{ "13": "pause", "40": "down" }"#).unwrap());
+    assert!(!universe.handle_key(80));
+    assert!(universe.handle_key(13));
+    assert_eq!(universe.state(), GameState::Paused);
+    assert!(universe.handle_key(13));
+    assert!(universe.handle_key(40));
+    assert_eq!(universe.tick(1).new_head_y(), 7);
+}
+
+#[test]
+fn rival_snakes_collide() {
+    let mut universe = seeded(8);
+    assert_eq!(universe.add_snake(Snake::new_at(7, 9, DirectionName::Up, 3)), Ok(1));
+    assert!(universe.add_snake(Snake::new_at(4, 6, DirectionName::Up, 2)).is_err());
+
+    for _ in 0..3 {
+        universe.tick(1);
+    }
+    assert!(universe.is_alive(0));
+    assert!(!universe.is_alive(1));
+    assert_eq!(universe.player_death_reason(1), Some(GameOverReason::HitSnake));
+    assert_eq!(universe.state(), GameState::GameOver);
+    assert_eq!(universe.winner(), Some(0));
+
+    let mut universe = seeded(8);
+    universe.add_snake(Snake::new_at(13, 6, DirectionName::Left, 3)).unwrap();
+    for _ in 0..4 {
+        universe.tick(1);
+    }
+    assert_eq!(universe.alive_count(), 0);
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::HitSnake));
+    assert_eq!(universe.winner(), None);
+}
+
+#[test]
+fn collision_policy_picks_who_survives() {
+    let mut universe = seeded(8);
+    universe.add_snake(Snake::new_at(7, 9, DirectionName::Up, 2)).unwrap();
+    universe.set_collision_policy(CollisionPolicy::LongerWins);
+    for _ in 0..3 {
+        universe.tick(1);
+    }
+    assert_eq!(universe.winner(), Some(0));
+
+    let mut universe = seeded(8);
+    universe.add_snake(Snake::new_at(7, 9, DirectionName::Up, 2)).unwrap();
+    universe.set_collision_policy(CollisionPolicy::HeadOnKillsBoth);
+    for _ in 0..3 {
+        universe.tick(1);
+    }
+    assert_eq!(universe.alive_count(), 0);
+
+    let mut universe = seeded(8);
+    universe.add_snake(Snake::new_at(7, 9, DirectionName::Up, 2)).unwrap();
+    universe.set_collision_policy(CollisionPolicy::PassThrough);
+    for _ in 0..6 {
+        universe.tick(1);
+    }
+    assert_eq!(universe.alive_count(), 2);
+}
+
+#[test]
+fn coop_snakes_respawn() {
+    let mut universe = seeded(8);
+    universe.set_match_mode(MatchMode::Coop);
+    universe.add_snake(Snake::new_at(7, 9, DirectionName::Up, 3)).unwrap();
+
+    for _ in 0..3 {
+        universe.tick(1);
+    }
+    assert!(!universe.is_alive(1));
+    assert_eq!(universe.state(), GameState::Running);
+    assert!(universe.respawn_timer(1) > 0);
+
+    universe.on_click(DirectionName::Down);
+    while !universe.is_alive(1) && universe.is_alive(0) {
+        universe.tick(1);
+    }
+    assert!(universe.is_alive(1));
+    assert_eq!(universe.respawn_timer(1), 0);
+    // Coming back in co-op doesn't hand out a life.
+    assert_eq!(universe.player_lives(1), 0);
+}
+
+#[test]
+fn ai_snakes_find_food() {
+    let mut universe = seeded(8);
+    assert!(universe.attach_ai(0, AiStrategy::AStar));
+    while universe.score() < 3 && !universe.is_game_over() {
+        universe.tick(1);
+    }
+    assert!(universe.score() >= 3);
+
+    let mut universe = seeded(8);
+    universe.toggle_topology();
+    universe.attach_ai(0, AiStrategy::Hamiltonian);
+    for _ in 0..600 {
+        universe.tick(1);
+    }
+    assert!(!universe.is_game_over());
+    assert!(universe.score() > 0);
+}
+
+#[test]
+fn find_path_routes_around_walls() {
+    let mut universe = seeded(8);
+    for y in 0..15 {
+        universe.set_wall(10, y);
+    }
+    universe.toggle_topology();
+
+    let path = ai::find_path(&universe, 8, 2, 12, 2).unwrap();
+    assert_eq!(path.len(), 30);
+    assert_eq!(ai::find_path(&universe, 8, 2, 10, 2), None);
+}
+
+#[test]
+fn autopilot_overrides_manual_turns() {
+    let mut universe = seeded(8);
+    universe.set_autopilot(true);
+    assert!(universe.autopilot());
+
+    for _ in 0..50 {
+        universe.on_click(DirectionName::Up);
+        universe.tick(1);
+    }
+    assert!(!universe.is_game_over());
+    assert!(universe.score() > 0);
+
+    universe.set_autopilot(false);
+    assert!(!universe.autopilot());
+}
+
+#[test]
+fn assist_drops_fatal_turns() {
+    for assist in [true, false] {
+        let mut universe = seeded(8);
+        universe.toggle_topology();
+        universe.set_assist(assist);
+        for _ in 0..4 {
+            universe.tick(1);
+        }
+
+        for direction in [DirectionName::Up, DirectionName::Left, DirectionName::Down] {
+            universe.on_click(direction);
+            universe.tick(1);
+        }
+        assert_eq!(universe.is_game_over(), !assist);
+    }
+}
+
+#[test]
+fn env_steps_return_rewards_and_done() {
+    let mut env = Env::new(8, 8, 3).unwrap();
+    let observation = env.reset();
+    assert_eq!(observation.len(), env.observation_len());
+    assert_eq!(observation.iter().filter(|&&v| v == 1.0).count(), 5);
+
+    let (mut done, mut reward) = (false, 0.0);
+    for _ in 0..8 {
+        let (observation, r, d) = env.step(0).into_parts();
+        assert_eq!(observation.len(), 8 * 8 * env::PLANES);
+        reward = r;
+        done = d;
+        if done {
+            break;
+        }
+    }
+    assert!(done);
+    assert_eq!(reward, env::DEATH_REWARD);
+}
+
+#[test]
+fn batches_run_many_ticks_in_one_call() {
+    let mut universe = seeded(8);
+    let batch = universe.advance_n_ticks(300, Some(AiStrategy::AStar));
+    assert_eq!(batch.ticks(), 300);
+    assert!(batch.apples() > 0);
+    assert_eq!(batch.score(), universe.score());
+
+    universe.toggle_topology();
+    let batch = universe.advance_n_ticks(300, None);
+    assert!(batch.died());
+    assert!(batch.ticks() < 300);
+}
+
+#[test]
+fn simulated_matches_are_reproducible() {
+    let result = sim::simulate_match(5, AiStrategy::AStar, AiStrategy::Greedy, 2000);
+    assert!(result.ticks() > 0 && result.ticks() <= 2000);
+    assert!(result.score(0) + result.score(1) > 0);
+    if let Some(winner) = result.winner() {
+        assert_eq!(result.death_reason(winner), None);
+        assert!(result.death_reason(1 - winner).is_some());
+    }
+    assert_eq!(sim::simulate_match(5, AiStrategy::AStar, AiStrategy::Greedy, 2000), result);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn saved_games_resume_where_they_left_off() {
+    let mut universe = seeded(12);
+    universe.set_special_food(true);
+    universe.set_wall(0, 0);
+    universe.advance_n_ticks(40, Some(AiStrategy::AStar));
+
+    let mut loaded = Universe::from_json(&universe.to_json()).unwrap();
+    assert_eq!(loaded.render(), universe.render());
+    assert_eq!(loaded.save_state(), universe.save_state());
+
+    for _ in 0..40 {
+        universe.tick(0);
+        loaded.tick(0);
+    }
+    assert_eq!(loaded.render(), universe.render());
+    assert_eq!(loaded.score(), universe.score());
+
+    assert!(Universe::from_json("{\"width\":0}").is_err());
+}
+
+#[test]
+fn binary_snapshots_restore_the_game() {
+    let mut universe = seeded(21);
+    universe.set_special_food(true);
+    universe.set_wall(0, 0);
+    universe.advance_n_ticks(30, Some(AiStrategy::AStar));
+
+    let bytes = universe.snapshot();
+    assert!(bytes.len() < 200, "snapshot took {} bytes", bytes.len());
+
+    let mut restored = seeded(99);
+    restored.restore(&bytes).unwrap();
+    assert_eq!(restored.render(), universe.render());
+    assert_eq!(restored.snapshot(), bytes);
+
+    for _ in 0..30 {
+        universe.tick(0);
+        restored.tick(0);
+    }
+    assert_eq!(restored.render(), universe.render());
+
+    assert!(restored.restore(&bytes[..bytes.len() - 1]).is_err());
+    assert!(restored.restore(b"JSON").is_err());
+}
+
+#[test]
+fn replays_reproduce_the_recorded_game() {
+    let mut universe = seeded(17);
+    universe.toggle_topology();
+    universe.start_recording();
+    universe.advance_n_ticks(40, Some(AiStrategy::Greedy));
+    universe.on_click(DirectionName::Up);
+    universe.advance_n_ticks(100, None);
+    assert!(universe.is_game_over());
+
+    let recorded = universe.stop_recording().unwrap();
+    assert!(!universe.is_recording());
+    let mut replay = Replay::from_bytes(&recorded.to_bytes()).unwrap();
+    assert_eq!(replay, recorded);
+    assert!(replay.turn_count() > 0);
+
+    let mut playback = replay.restart().unwrap();
+    let mut steps = 0;
+    while replay.step(&mut playback) {
+        steps += 1;
+    }
+    assert_eq!(steps, replay.ticks());
+    assert!(playback.is_game_over());
+    assert_eq!(playback.render(), universe.render());
+    assert_eq!(playback.score(), universe.score());
+}
+
+#[test]
+fn ghosts_race_without_touching_the_game() {
+    let mut best = seeded(30);
+    best.start_recording();
+    best.advance_n_ticks(30, Some(AiStrategy::Greedy));
+    let replay = best.stop_recording().unwrap();
+
+    let small = Universe::new_with_seed(Snake::new(), 60.0, 12, 12, 1);
+    assert!(small.unwrap().set_ghost(replay.clone()).is_err());
+
+    let mut raced = seeded(31);
+    let mut alone = seeded(31);
+    raced.set_ghost(replay).unwrap();
+    raced.on_click(DirectionName::Down);
+    alone.on_click(DirectionName::Down);
+
+    for _ in 0..20 {
+        raced.tick(0);
+        alone.tick(0);
+        assert!(raced.render().contains('◌'));
+        assert_eq!(raced.render().replace('◌', "◻"), alone.render());
+    }
+
+    // Going back to a saved state takes the ghost back with it.
+    let (state, board) = (raced.save_state(), raced.render());
+    raced.advance_n_ticks(5, None);
+    raced.load_state(state).unwrap();
+    assert_eq!(raced.render(), board);
+
+    raced.advance_n_ticks(20, None);
+    assert!(!raced.has_ghost());
+    assert!(!raced.render().contains('◌'));
+}
+
+#[test]
+fn rewinding_undoes_a_death() {
+    let mut universe = seeded(40);
+    universe.toggle_topology();
+    universe.set_rewind_depth(5);
+    universe.start_recording();
+    universe.advance_n_ticks(100, None);
+    assert!(universe.is_game_over());
+    assert_eq!(universe.rewind_available(), 5);
+
+    assert_eq!(universe.rewind(3), 3);
+    assert_eq!(universe.state(), GameState::Running);
+    assert_eq!(universe.rewind_available(), 2);
+    universe.on_click(DirectionName::Down);
+    universe.advance_n_ticks(3, None);
+    assert_eq!(universe.state(), GameState::Running);
+
+    let mut replay = universe.stop_recording().unwrap();
+    let mut playback = replay.restart().unwrap();
+    while replay.step(&mut playback) {}
+    assert_eq!(playback.render(), universe.render());
+}
+
+#[test]
+fn rewinding_puts_board_edits_back() {
+    let mut universe = seeded(41);
+    universe.set_apple_count(0);
+    universe.set_rewind_depth(10);
+    universe.advance_n_ticks(2, None);
+    universe.set_wall(10, 10);
+    universe.set_terrain(12, 12, Terrain::Ice);
+    universe.advance_n_ticks(2, None);
+
+    assert_eq!(universe.rewind(2), 2);
+    assert_eq!(universe.cells_u8()[10 * 16 + 10], Cell::Wall as u8);
+    assert_eq!(universe.terrain_at(12, 12), Terrain::Ice);
+    assert_eq!(universe.rewind(1), 1);
+    assert_eq!(universe.cells_u8()[10 * 16 + 10], Cell::Empty as u8);
+    assert_eq!(universe.terrain_at(12, 12), Terrain::Plain);
+}
+
+#[test]
+fn state_hash_tracks_the_simulation() {
+    let mut a = seeded(50);
+    let mut b = seeded(50);
+    assert_eq!(a.state_hash(), b.state_hash());
+
+    for _ in 0..20 {
+        a.tick(0);
+        b.tick(0);
+    }
+    assert_eq!(a.state_hash(), b.state_hash());
+
+    let before = a.state_hash();
+    a.on_click(DirectionName::Up);
+    assert_ne!(a.state_hash(), before);
+    a.tick(0);
+    b.tick(0);
+    assert_ne!(a.state_hash(), b.state_hash());
+
+    let mut restored = seeded(1);
+    restored.restore(&b.snapshot()).unwrap();
+    assert_eq!(restored.state_hash(), b.state_hash());
+}
+
+fn lockstep_peer(local_player: u32) -> Lockstep {
+    let mut universe = seeded(53);
+    universe.add_snake(Snake::new_at(13, 12, DirectionName::Up, 3)).unwrap();
+    Lockstep::new(universe, local_player).unwrap()
+}
+
+#[test]
+fn lockstep_peers_stay_in_sync() {
+    let mut host = lockstep_peer(0);
+    let mut guest = lockstep_peer(1);
+
+    // Nothing is known about the other player yet past the input delay.
+    assert!(host.tick());
+    assert!(host.tick());
+    assert!(!host.tick());
+    assert_eq!(host.current_tick(), 2);
+
+    let tick = guest.local_input(DirectionName::Left);
+    host.apply_remote_input(1, tick, DirectionName::Left).unwrap();
+    let tick = host.local_input(DirectionName::Down);
+    guest.apply_remote_input(0, tick, DirectionName::Down).unwrap();
+    assert!(host.apply_remote_input(0, 9, DirectionName::Up).is_err());
+
+    for _ in 0..2 {
+        assert!(guest.tick());
+    }
+    assert_eq!(host.state_hash(), guest.state_hash());
+
+    for _ in 0..20 {
+        host.confirm_remote(1, guest.local_confirmed_tick()).unwrap();
+        guest.confirm_remote(0, host.local_confirmed_tick()).unwrap();
+        assert!(host.tick());
+        assert!(guest.tick());
+    }
+    assert_eq!(host.current_tick(), guest.current_tick());
+    assert_eq!(host.state_hash(), guest.state_hash());
+    assert_eq!(host.mispredictions(), 0);
+
+    let mut predicting = lockstep_peer(0);
+    predicting.set_late_policy(LatePolicy::Predict);
+    predicting.set_max_prediction(3);
+    let ticks = (0..10).take_while(|_| predicting.tick()).count();
+    assert_eq!(ticks, 5);
+}
+
+#[test]
+fn rollback_replays_late_inputs() {
+    let mut on_time = lockstep_peer(0);
+    let mut rolling = lockstep_peer(0);
+    rolling.set_late_policy(LatePolicy::Rollback);
+
+    on_time.apply_remote_input(1, 3, DirectionName::Left).unwrap();
+    on_time.confirm_remote(1, 6).unwrap();
+    for _ in 0..6 {
+        assert!(on_time.tick());
+        assert!(rolling.tick());
+    }
+    assert_ne!(rolling.state_hash(), on_time.state_hash());
+
+    // Ticks played again after the rollback were already heard once.
+    rolling.universe_mut().take_sounds();
+    rolling.universe_mut().take_effects();
+    rolling.apply_remote_input(1, 3, DirectionName::Left).unwrap();
+    rolling.confirm_remote(1, 6).unwrap();
+    assert!(rolling.universe_mut().take_sounds().is_empty());
+    assert!(rolling.universe_mut().take_effects().is_empty());
+    assert_eq!(rolling.rollbacks(), 1);
+    assert_eq!(rolling.mispredictions(), 0);
+    assert_eq!(rolling.current_tick(), 6);
+    assert_eq!(rolling.state_hash(), on_time.state_hash());
+}
+
+#[test]
+fn netcode_messages_round_trip() {
+    let universe = seeded(55);
+    let messages = vec![
+        Message::Join { room: "lobby ü".to_string() },
+        Message::Start {
+            player: 1,
+            state: Box::new(universe.save_state()),
+        },
+        Message::Input {
+            player: 1,
+            tick: 300,
+            direction: Some(DirectionName::Left),
+        },
+        Message::Input {
+            player: 0,
+            tick: 7,
+            direction: None,
+        },
+        Message::Ping { sent_ms: 1234.5 },
+    ];
+    for message in messages {
+        assert_eq!(Message::from_bytes(&message.to_bytes()), Ok(message));
+    }
+    assert!(Message::from_bytes(&[9]).is_err());
+
+    let mut host = lockstep_peer(0);
+    let mut guest = lockstep_peer(1);
+    let tick = guest.local_input(DirectionName::Left);
+    let wire = Message::Input {
+        player: 1,
+        tick,
+        direction: Some(DirectionName::Left),
+    };
+    host.receive(&Message::from_bytes(&wire.to_bytes()).unwrap()).unwrap();
+    for _ in 0..10 {
+        host.receive(&guest.heartbeat()).unwrap();
+        guest.receive(&host.heartbeat()).unwrap();
+        assert!(host.tick());
+        assert!(guest.tick());
+    }
+    assert_eq!(host.state_hash(), guest.state_hash());
+}
+
+#[test]
+fn spectators_follow_the_stream() {
+    let mut universe = seeded(56);
+    let mut stream = SnapshotStream::new(10);
+    let mut spectator = Universe::from_snapshot_stream(&stream.next_frame(&universe)).unwrap();
+    assert_eq!(spectator.render(), universe.render());
+
+    let keyframe = universe.snapshot().len();
+    for i in 0..25 {
+        if i == 5 {
+            universe.on_click(DirectionName::Down);
+        }
+        universe.tick(0);
+        let frame = stream.next_frame(&universe);
+        if i % 10 != 9 {
+            assert!(frame.len() < keyframe);
+        }
+        spectator.push(&frame).unwrap();
+        assert_eq!(spectator.render(), universe.render());
+        assert_eq!(spectator.universe().state_hash(), universe.state_hash());
+    }
+
+    // A spectator that missed a delta waits for the next keyframe.
+    universe.tick(0);
+    stream.next_frame(&universe);
+    universe.tick(0);
+    assert!(spectator.push(&stream.next_frame(&universe)).is_err());
+    stream.request_keyframe();
+    universe.tick(0);
+    spectator.push(&stream.next_frame(&universe)).unwrap();
+    assert_eq!(spectator.universe().state_hash(), universe.state_hash());
+}
+
+#[test]
+fn cylinders_wrap_along_one_axis() {
+    let mut universe = seeded(57);
+    universe.set_topology(UniverseTopology::CylinderX);
+    for _ in 0..20 {
+        assert!(universe.tick(0).moved());
+    }
+    universe.on_click(DirectionName::Up);
+    let mut result = universe.tick(0);
+    while result.moved() {
+        result = universe.tick(0);
+    }
+    assert_eq!(result.died_reason(), Some(GameOverReason::HitWall));
+
+    let mut universe = seeded(57);
+    universe.set_wrap(false, true);
+    assert_eq!(universe.topology(), UniverseTopology::CylinderY);
+    universe.on_click(DirectionName::Up);
+    for _ in 0..20 {
+        assert!(universe.tick(0).moved());
+    }
+
+    let custom = UniverseTopology::Custom {
+        wrap_x: false,
+        wrap_y: true,
+    };
+    universe.set_topology(custom);
+    let restored = Universe::from_snapshot(universe.save_state()).unwrap();
+    assert_eq!(restored.topology(), custom);
+    assert!(!restored.wraps_x() && restored.wraps_y());
+}
+
+#[test]
+fn portals_carry_the_snake_across() {
+    let mut universe = seeded(59);
+    universe.set_apple_count(0);
+    universe.add_portal(7, 6, 2, 12).unwrap();
+    assert!(universe.add_portal(7, 6, 9, 9).is_err());
+    assert!(universe.add_portal(5, 6, 9, 9).is_err());
+    assert!(universe.is_portal(2, 12));
+    assert!(!universe.set_wall(7, 6));
+
+    // The head at (5, 6) steps to (6, 6), then into the portal at (7, 6).
+    universe.tick(0);
+    let result = universe.tick(0);
+    assert_eq!((result.new_head_x(), result.new_head_y()), (3, 12));
+    assert!(universe.is_portal(7, 6));
+
+    let restored = Universe::from_snapshot(universe.save_state()).unwrap();
+    assert_eq!(restored.render(), universe.render());
+
+    universe.clear_portals();
+    assert_eq!(universe.portal_count(), 0);
+    assert!(!universe.is_portal(2, 12));
+}
+
+#[test]
+fn hazards_move_and_kill_on_contact() {
+    let mut universe = seeded(60);
+    universe.set_apple_count(0);
+    assert!(universe.add_hazard(HazardKind::Patrol, 4, 6, DirectionName::Up, 1).is_err());
+    assert_eq!(universe.add_hazard(HazardKind::Patrol, 8, 5, DirectionName::Down, 3), Ok(0));
+    universe.add_hazard(HazardKind::Rogue, 12, 12, DirectionName::Up, 1).unwrap();
+
+    universe.tick(0);
+    universe.tick(0);
+    assert_eq!(universe.hazard_positions()[..2], [8, 5]);
+    let restored = Universe::from_snapshot(universe.save_state()).unwrap();
+    assert_eq!(restored.state_hash(), universe.state_hash());
+
+    let result = universe.tick(0);
+    assert_eq!(result.died_reason(), Some(GameOverReason::HitHazard));
+    assert_eq!(universe.hazard_positions()[..2], [8, 6]);
+
+    universe.reset();
+    assert_eq!(universe.hazard_positions(), vec![8, 5, 12, 12]);
+    universe.clear_hazards();
+    assert_eq!(universe.hazard_count(), 0);
+}
+
+#[test]
+fn shrinking_arena_closes_rings() {
+    let mut universe = seeded(61);
+    universe.set_apple_count(0);
+    universe.set_shrinking_arena(3);
+    assert_eq!(universe.ticks_until_shrink(), Some(3));
+
+    for _ in 0..3 {
+        universe.tick(0);
+    }
+    assert_eq!(universe.arena_rings(), 1);
+    assert!(universe.is_wall(0, 0) && universe.is_wall(15, 9) && !universe.is_wall(1, 1));
+    assert_eq!(universe.ticks_until_shrink(), Some(3));
+    let restored = Universe::from_snapshot(universe.save_state()).unwrap();
+    assert_eq!(restored.state_hash(), universe.state_hash());
+
+    universe.reset();
+    assert_eq!(universe.arena_rings(), 0);
+    assert!(!universe.is_wall(0, 0));
+
+    // A snake running along the edge gets caught by the first ring.
+    let mut universe = Universe::new_with_seed(Snake::new_at(5, 0, DirectionName::Right, 3), 60.0, 16, 16, 61).unwrap();
+    universe.set_shrinking_arena(1);
+    let result = universe.tick(0);
+    assert_eq!(result.died_reason(), Some(GameOverReason::Crushed));
+    assert!(universe.is_game_over());
+}
+
+#[test]
+fn timed_matches_end_when_the_clock_runs_out() {
+    let mut universe = seeded(63);
+    universe.set_apple_count(0);
+    universe.set_time_limit(1);
+    let ticks = universe.time_remaining_ticks().unwrap();
+    assert_eq!(universe.time_remaining_seconds(), Some(1.0));
+
+    for _ in 1..ticks {
+        universe.tick(0);
+    }
+    assert_eq!(universe.state(), GameState::Running);
+    assert_eq!(universe.time_remaining_ticks(), Some(1));
+    let restored = Universe::from_snapshot(universe.save_state()).unwrap();
+    assert_eq!(restored.state_hash(), universe.state_hash());
+
+    universe.tick(0);
+    assert_eq!(universe.state(), GameState::Finished);
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::TimeUp));
+    assert!(universe.is_game_over());
+    assert_eq!(universe.winner(), Some(0));
+
+    universe.reset();
+    assert_eq!(universe.time_remaining_seconds(), Some(1.0));
+    universe.set_time_limit(0);
+    assert_eq!(universe.time_remaining_ticks(), None);
+}
+
+#[test]
+fn zen_snakes_slide_along_walls() {
+    let mut universe = seeded(64);
+    universe.set_apple_count(0);
+    universe.set_topology(UniverseTopology::Flat);
+    universe.set_zen(true);
+
+    for _ in 0..10 {
+        universe.tick(0);
+    }
+    let result = universe.tick(0);
+    assert!(!result.died());
+    assert_eq!((result.new_head_x(), result.new_head_y()), (15, 5));
+    assert_eq!(universe.state(), GameState::Running);
+
+    // Boxed into the corner it turns back along the top edge, then keeps going.
+    for _ in 0..30 {
+        assert!(!universe.tick(0).died());
+    }
+    assert_eq!(universe.state(), GameState::Running);
+}
+
+#[test]
+fn presets_configure_the_rules() {
+    let mut universe = seeded(65);
+    universe.set_zen(true);
+
+    universe.apply_preset(Preset::Hardcore);
+    assert!(!universe.wraps_x() && !universe.wraps_y());
+    assert!(!universe.assist() && !universe.is_zen());
+    assert_eq!(universe.apple_count(), 1);
+
+    universe.apply_preset(Preset::Casual);
+    assert!(universe.wraps_x() && universe.assist());
+    assert_eq!(universe.apple_count(), 3);
+    assert!(universe.ticks_per_second() < 10.0);
+
+    universe.apply_preset(Preset::Classic);
+    assert!(universe.wraps_x() && !universe.assist());
+    assert_eq!(universe.ticks_per_second(), 10.0);
+}
+
+#[test]
+fn speed_curves_grow_with_apples_up_to_the_cap() {
+    let linear = SpeedModel {
+        curve: SpeedCurve::Linear,
+        base: 10.0,
+        step: 2.0,
+        max: 30.0,
+    };
+    assert_eq!(linear.ticks_per_second(0), 10.0);
+    assert_eq!(linear.ticks_per_second(3), 16.0);
+    assert_eq!(linear.ticks_per_second(20), 30.0);
+
+    let exponential = SpeedModel {
+        curve: SpeedCurve::Exponential,
+        step: 0.5,
+        ..linear
+    };
+    assert_eq!(exponential.ticks_per_second(2), 22.5);
+    assert_eq!(exponential.ticks_per_second(3), 30.0);
+    assert_eq!(exponential.ticks_per_second(u32::MAX), 30.0);
+
+    let mut universe = seeded(27);
+    assert!(universe.set_speed_curve(SpeedCurve::Linear, 20.0, 1.0, 10.0).is_err());
+    assert!(universe.set_speed_curve(SpeedCurve::Linear, 10.0, -1.0, 30.0).is_err());
+    assert!(universe.set_speed_curve(SpeedCurve::Exponential, 0.0, 0.1, 30.0).is_err());
+    assert_eq!(universe.ticks_per_second(), 10.0);
+    universe.set_speed_curve(SpeedCurve::Exponential, 10.0, 0.5, 30.0).unwrap();
+    assert_eq!(universe.ticks_per_second(), 10.0);
+}
+
+struct Walled;
+
+impl Rules for Walled {
+    fn on_collision(&self, _universe: &Universe, _player: u32, _reason: GameOverReason) -> Collision {
+        Collision::Stop
+    }
+}
+
+#[test]
+fn custom_rules_replace_the_mode() {
+    let mut universe = seeded(66);
+    universe.set_apple_count(0);
+    universe.set_topology(UniverseTopology::Flat);
+    universe.set_rules(Rc::new(Walled));
+
+    for _ in 0..15 {
+        assert!(!universe.tick(0).died());
+    }
+    assert_eq!(universe.state(), GameState::Running);
+
+    universe.clear_rules();
+    assert!(universe.tick(0).died());
+}
+
+#[test]
+fn configs_set_up_the_game() {
+    let config = Config {
+        width: 12,
+        height: 10,
+        topology: UniverseTopology::Flat,
+        seed: Some(68),
+        apples: 3,
+        mode: GameMode::Timed { seconds: 30 },
+        walls: vec![(0, 0), (11, 9)],
+        ..Config::default()
+    };
+    let universe = Universe::from_config(&config).unwrap();
+    assert_eq!((universe.width(), universe.height(), universe.seed()), (12, 10, 68));
+    assert!(!universe.wraps_x() && universe.is_wall(0, 0) && universe.is_wall(11, 9));
+    assert_eq!(universe.apple_count(), 3);
+    assert_eq!(universe.time_remaining_seconds(), Some(30.0));
+    assert_eq!(universe.config(), Config { seed: None, ..config });
+
+    let taken = Config {
+        seed: Some(68),
+        walls: vec![(5, 6)],
+        ..Config::default()
+    };
+    assert!(Universe::from_config(&taken).is_err());
+}
+
+#[test]
+fn high_scores_keep_the_best() {
+    let mut scores = HighScores::new(3);
+    assert!(!scores.is_high_score(0));
+    assert_eq!(scores.submit_at(5, "classic", 1.0), Some(0));
+    assert_eq!(scores.submit_at(9, "zen", 2.0), Some(0));
+    assert_eq!(scores.submit_at(5, "classic", 3.0), Some(2));
+    assert!(!scores.is_high_score(5));
+    assert_eq!(scores.submit_at(7, "timed-60", 4.0), Some(1));
+
+    let list = scores.list();
+    let ranked: Vec<_> = list.iter().map(|entry| (entry.score(), entry.timestamp_ms())).collect();
+    assert_eq!(ranked, vec![(9, 2.0), (7, 4.0), (5, 1.0)]);
+    assert_eq!(scores.list_mode("classic").len(), 1);
+    assert_eq!(GameMode::Timed { seconds: 60 }.tag(), "timed-60");
+}
+
+#[test]
+fn verified_replays_report_the_real_score() {
+    let mut universe = seeded(72);
+    universe.start_recording();
+    universe.advance_n_ticks(200, Some(AiStrategy::Greedy));
+    let bytes = universe.stop_recording().unwrap().to_bytes();
+
+    let verified = replay::verify_replay(&bytes).unwrap();
+    assert!(verified.score() > 0);
+    assert_eq!(verified.score(), universe.score());
+    assert_eq!(verified.seed(), 72);
+    assert_eq!(verified.state_hash(), universe.state_hash());
+
+    // A recording started mid-game isn't proof of anything.
+    universe.start_recording();
+    universe.tick(0);
+    let partial = universe.stop_recording().unwrap().to_bytes();
+    assert!(replay::verify_replay(&partial).is_err());
+    assert!(replay::verify_replay(&bytes[..bytes.len() - 1]).is_err());
+
+    // Presets are fair game, rules set by hand are not.
+    let mut hardcore = seeded(72);
+    hardcore.apply_preset(Preset::Hardcore);
+    hardcore.reset();
+    hardcore.start_recording();
+    hardcore.advance_n_ticks(20, Some(AiStrategy::Greedy));
+    assert!(replay::verify_replay(&hardcore.stop_recording().unwrap().to_bytes()).is_ok());
+
+    let mut rigged = seeded(72);
+    rigged.set_scoring(Scoring {
+        apple: 1000,
+        ..Scoring::default()
+    });
+    rigged.start_recording();
+    let bytes = rigged.stop_recording().unwrap().to_bytes();
+    assert!(replay::verify_replay(&bytes).is_err());
+
+    let mut rigged = seeded(72);
+    rigged.set_lives(9);
+    rigged.reset();
+    rigged.start_recording();
+    assert!(replay::verify_replay(&rigged.stop_recording().unwrap().to_bytes()).is_err());
+}
+
+#[test]
+fn daily_challenges_follow_the_date() {
+    assert_eq!(daily::daily_seed("2024-05-01"), daily::daily_seed("2024-05-01"));
+    assert_ne!(daily::daily_seed("2024-05-01"), daily::daily_seed("2024-05-02"));
+    assert!(DailyChallenge::new("2024-13-01").is_err());
+    assert!(DailyChallenge::new("yesterday").is_err());
+
+    let challenge = DailyChallenge::new("2024-05-01").unwrap();
+    assert_eq!(challenge.tag(), "daily-2024-05-01");
+    let mut a = challenge.start().unwrap();
+    let mut b = DailyChallenge::new("2024-05-01").unwrap().start().unwrap();
+    assert_eq!(a.time_remaining_seconds(), Some(120.0));
+    for _ in 0..30 {
+        a.tick(0);
+        b.tick(0);
+    }
+    assert_eq!(a.state_hash(), b.state_hash());
+}
+
+#[test]
+fn snake_positions_run_head_first() {
+    let mut universe = seeded(75);
+    assert_eq!(universe.snake_length(), 4);
+    assert_eq!(universe.snake_positions(), vec![5, 6, 4, 6, 3, 6, 2, 6]);
+
+    universe.set_apple_count(0);
+    universe.tick(0);
+    assert_eq!(&universe.snake_positions()[..4], &[6, 6, 5, 6]);
+    let head = universe.snake_head();
+    assert_eq!((head.x(), head.y(), head.index(16)), (6, 6, 102));
+    assert_eq!(head, Position::new(6, 6));
+    assert!(universe.player_positions(1).is_empty());
+}
+
+#[test]
+fn poison_shrinks_the_snake() {
+    let mut universe = seeded_with_food(FoodKind::Poison, |_| true);
+    let length = universe.snake_length();
+    eat_first_food(&mut universe);
+
+    assert_eq!(universe.snake_length(), length - food::POISON_SHRINK as u32);
+    assert_eq!(universe.score(), 0);
+}
+
+#[test]
+fn golden_food_pays_extra_and_expires() {
+    let mut universe = seeded_with_food(FoodKind::Golden, |_| true);
+    eat_first_food(&mut universe);
+    assert_eq!(universe.score(), food::GOLDEN_POINTS);
+
+    // Off the snake's row, so it is left to rot.
+    let mut universe = seeded_with_food(FoodKind::Golden, |u| u.foods()[0].y() != u.snake_head().y());
+    let golden = universe.foods()[0].position();
+    universe.advance_n_ticks(food::GOLDEN_TTL - 1, None);
+    assert_eq!(universe.foods()[0].position(), golden);
+    assert_eq!(universe.foods()[0].ticks_left(), Some(1));
+
+    universe.tick(0);
+    let foods = universe.foods();
+    assert_eq!(foods.len(), 1);
+    assert_ne!(foods[0].position(), golden);
+    universe.tick(0);
+    assert_eq!(universe.foods()[0].position(), foods[0].position());
+}
+
+#[test]
+fn speed_food_boosts_for_a_while() {
+    let mut universe = seeded_with_food(FoodKind::Speed, |_| true);
+    assert_eq!(universe.speed_multiplier(), 1.0);
+    eat_first_food(&mut universe);
+
+    let mut boosted = 0;
+    while universe.speed_multiplier() > 1.0 {
+        assert_eq!(universe.speed_multiplier(), food::SPEED_BOOST_MULTIPLIER);
+        boosted += 1;
+        universe.tick(0);
+    }
+    assert_eq!(boosted, food::SPEED_BOOST_TICKS);
+}
+
+#[test]
+fn foods_can_be_read_without_scanning_cells() {
+    let mut universe = seeded(77);
+    let apple = universe.apple().unwrap();
+    let rows: Vec<_> = universe.render().lines().map(|line| line.chars().collect::<Vec<_>>()).collect();
+    assert_eq!(rows[apple.y() as usize][apple.x() as usize], '●');
+
+    universe.set_apple_count(3);
+    let foods = universe.foods();
+    assert_eq!(foods.len(), 3);
+    assert_eq!(foods[0].position(), apple);
+    assert!(foods.iter().all(|food| food.kind() == FoodKind::Normal));
+}
+
+#[test]
+fn cells_can_be_copied_or_viewed() {
+    let universe = seeded(78);
+    let cells = universe.cells_u8();
+    assert_eq!(cells.len(), 256);
+    assert_eq!(cells[6 * 16 + 5], Cell::SnakeHead as u8);
+
+    let view = universe.cells_view();
+    assert_eq!((view.len(), view.width(), view.height()), (256, 16, 16));
+    assert_eq!(view.ptr(), universe.cells());
+}
+
+#[test]
+fn the_byte_board_follows_the_packed_one() {
+    let mut universe = seeded(99);
+    universe.toggle_topology();
+    universe.set_wall(0, 0);
+    // A view taken once keeps up with the board from then on.
+    let view = universe.cells_view();
+    for _ in 0..4 {
+        universe.tick(0);
+        let bytes = unsafe { std::slice::from_raw_parts(view.ptr() as *const u8, view.len() as usize) };
+        assert_eq!(bytes, universe.cells_u8().as_slice());
+    }
+
+    let cells = universe.cells_u8();
+    assert_eq!(cells[0], Cell::Wall as u8);
+    assert_eq!(cells.iter().filter(|&&cell| cell == Cell::SnakeHead as u8).count(), 1);
+    assert_eq!(universe.render().lines().count(), 16);
+}
+
+#[test]
+fn heading_follows_the_snake() {
+    let mut universe = seeded(79);
+    universe.set_apple_count(0);
+    assert_eq!(universe.heading(), DirectionName::Right);
+
+    universe.on_click(DirectionName::Up);
+    assert_eq!(universe.heading(), DirectionName::Right);
+    universe.tick(0);
+    assert_eq!(universe.heading(), DirectionName::Up);
+    assert_eq!(universe.player_heading(0), Some(DirectionName::Up));
+    assert_eq!(universe.player_heading(1), None);
+    assert_eq!(Snake::new().direction_name(), DirectionName::Right);
+}
+
+#[test]
+fn themes_pick_colors_and_glyphs() {
+    let mut universe = seeded(82);
+    assert!(universe.render().contains('◆'));
+
+    universe.set_theme_preset(ThemePreset::HighContrast);
+    assert!(universe.render().contains('@'));
+    assert_eq!(universe.theme().color(Cell::Empty), [0, 0, 0, 0xFF]);
+
+    let mut theme = Theme::preset(ThemePreset::Dark);
+    assert!(theme.grid().is_some());
+    theme.set_glyph(Cell::SnakeHead, 'H');
+    theme.set_color(Cell::Apple, 1, 2, 3, 4);
+    universe.set_theme(&theme);
+    assert!(universe.render().contains('H'));
+    assert_eq!(universe.theme().css(Cell::Apple), "rgba(1, 2, 3, 0.01568627450980392)");
+    assert_eq!(Theme::default(), Theme::preset(ThemePreset::Classic));
+}
+
+#[test]
+fn frames_export_as_svg() {
+    let universe = seeded(83);
+    let svg = universe.render_svg();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"320\" height=\"320\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    // The apple, plus a pair of eyes on the head.
+    assert_eq!(svg.matches("<circle").count(), 3);
+    assert!(svg.contains("fill=\"#1b5e20\""));
+}
+
+#[cfg(feature = "screenshot")]
+#[test]
+fn screenshots_encode_as_png() {
+    let png = seeded(84).screenshot_png(2);
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    // IHDR holds the width and height, big-endian.
+    assert_eq!(&png[16..24], &[0, 0, 0, 32, 0, 0, 0, 32]);
+}
+
+#[cfg(feature = "gif")]
+#[test]
+fn replays_animate_as_gifs() {
+    let mut universe = seeded(85);
+    universe.start_recording();
+    universe.advance_n_ticks(12, Some(AiStrategy::Greedy));
+    let replay = universe.stop_recording().unwrap();
+
+    let gif = replay.to_gif(2, 10.0).unwrap();
+    assert_eq!(&gif[..6], b"GIF89a");
+    assert_eq!(&gif[6..10], &[32, 0, 32, 0]);
+    // One graphic control block per frame: the start plus every tick.
+    assert_eq!(gif.windows(2).filter(|pair| pair == &[0x21, 0xF9]).count(), 13);
+}
+
+#[test]
+fn segments_glide_from_their_previous_cells() {
+    let mut universe = seeded(86);
+    universe.set_apple_count(0);
+    let start = universe.interpolation_state();
+    assert_eq!(start.len(), universe.snake_length());
+    assert_eq!(start.segments()[..4], [5, 6, 5, 6]);
+
+    universe.tick(0);
+    let moved = universe.interpolation_state();
+    assert_eq!(moved.segments()[..8], [5, 6, 6, 6, 4, 6, 5, 6]);
+    assert_eq!(moved.fraction(), 0.0);
+
+    universe.advance(universe.recommended_frame_delay_ms() / 2.0);
+    assert!((universe.interpolation_state().fraction() - 0.5).abs() < 1e-9);
+    assert!(universe.player_interpolation(1).is_empty());
+}
+
+#[test]
+fn tile_ages_count_ticks_since_a_cell_changed() {
+    let mut universe = seeded(87);
+    universe.set_apple_count(0);
+    assert_eq!(universe.age(5, 6), 0);
+    assert_eq!(universe.age(0, 0), u8::MAX);
+
+    universe.tick(0);
+    // The head moved on and the tail cell emptied behind it.
+    assert_eq!(universe.age(6, 6), 0);
+    let tail = universe.snake_positions()[universe.snake_positions().len() - 2] - 1;
+    assert_eq!(universe.age(tail, 6), 0);
+    universe.tick(0);
+    assert_eq!(universe.age(tail, 6), 1);
+    assert_eq!(universe.age(0, 0), u8::MAX);
+    assert_eq!(universe.age(99, 0), u8::MAX);
+
+    let ages = unsafe { std::slice::from_raw_parts(universe.ages(), 256) };
+    assert_eq!((ages[6 * 16 + tail as usize], ages[0]), (1, u8::MAX));
+}
+
+#[test]
+fn effects_mark_where_things_happened() {
+    let mut universe = seeded(88);
+    let apple = universe.apple().unwrap();
+    while universe.score() == 0 {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+    let effects = universe.take_effects();
+    assert_eq!(effects.len(), 1);
+    assert_eq!(effects[0].kind(), EffectKind::AppleBurst);
+    assert_eq!((effects[0].x(), effects[0].y()), (apple.x(), apple.y()));
+    assert_eq!(effects[0].food(), Some(FoodKind::Normal));
+    assert!(universe.take_effects().is_empty());
+
+    universe.toggle_topology();
+    universe.set_apple_count(0);
+    while !universe.is_game_over() {
+        universe.tick(0);
+    }
+    let effects = universe.take_effects();
+    let kinds: Vec<_> = effects.iter().map(|effect| effect.kind()).collect();
+    assert_eq!(kinds, [EffectKind::WallHit, EffectKind::DeathFlash]);
+    let head = universe.snake_head();
+    assert_eq!((effects[1].x(), effects[1].y()), (head.x(), head.y()));
+    assert!(effects[0].tick() > 1);
+    assert_eq!(effects[0].tick(), effects[1].tick());
+}
+
+#[test]
+fn sound_cues_follow_the_game() {
+    let mut universe = seeded(89);
+    universe.set_apple_count(0);
+    universe.set_game_mode(GameMode::Timed { seconds: 6 });
+    universe.on_click(DirectionName::Up);
+    universe.tick(0);
+    let cues = universe.take_sounds();
+    assert_eq!(cues.len(), 1);
+    assert_eq!(cues[0].sound(), Sound::Turn);
+
+    while !universe.is_game_over() {
+        universe.tick(0);
+    }
+    let counts: Vec<_> = universe
+        .take_sounds()
+        .iter()
+        .filter(|cue| cue.sound() == Sound::Countdown)
+        .map(|cue| cue.count())
+        .collect();
+    assert_eq!(counts, [5, 4, 3, 2, 1]);
+
+    universe.reset();
+    universe.toggle_topology();
+    universe.set_apple_count(1);
+    while !universe.is_game_over() {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+    let cues = universe.take_sounds();
+    let eaten = cues.iter().filter(|cue| cue.sound() == Sound::Eat).count();
+    let bursts = universe.take_effects().into_iter().filter(|effect| effect.kind() == EffectKind::AppleBurst);
+    assert_eq!(eaten, bursts.count());
+    assert!(cues.iter().all(|cue| cue.pitch() >= 1.0));
+}
+
+#[test]
+fn apples_score_one_point_without_combos() {
+    let mut universe = seeded(89);
+    universe.toggle_topology();
+    assert!(!universe.combos());
+    while !universe.is_game_over() {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+
+    let eaten = universe.take_sounds().iter().filter(|cue| cue.sound() == Sound::Eat).count() as u32;
+    assert!(eaten > 1);
+    assert_eq!(universe.score(), eaten);
+    assert_eq!(universe.multiplier(), 1);
+}
+
+#[test]
+fn haptics_are_configurable_per_cue() {
+    let mut universe = seeded(90);
+    assert!(universe.haptics());
+    assert_eq!(universe.vibration_ms(Sound::Eat), 20);
+    assert_eq!(universe.vibration_ms(Sound::Die), 200);
+    assert_eq!(universe.vibration_ms(Sound::Turn), 0);
+
+    universe.set_vibration_ms(Sound::Turn, 5);
+    universe.set_haptics(false);
+    universe.reset();
+    assert!(!universe.haptics());
+    assert_eq!(universe.vibration_ms(Sound::Turn), 5);
+}
+
+#[test]
+fn describe_and_narrate_for_screen_readers() {
+    let mut universe = seeded(91);
+    assert_eq!(
+        universe.describe(),
+        "Length 4, score 0, apple 2 cells ahead and 3 cells to the right, own body 13 cells ahead"
+    );
+    universe.toggle_topology();
+    assert!(universe.describe().ends_with(", wall 11 cells ahead"));
+    assert!(universe.take_narration().is_empty());
+
+    universe.set_narration(true);
+    universe.set_apple_count(0);
+    universe.on_click(DirectionName::Up);
+    universe.tick(0);
+    assert!(universe.describe().ends_with("apple 4 cells behind and 2 cells to the right, wall 6 cells ahead"));
+    while !universe.is_game_over() {
+        universe.tick(0);
+    }
+    assert!(universe.describe().ends_with(", game over, hit the wall"));
+    assert_eq!(universe.take_narration(), ["Game over, hit the wall"]);
+    assert!(universe.take_narration().is_empty());
+
+    universe.set_narration(false);
+    universe.reset();
+    universe.set_game_mode(GameMode::Timed { seconds: 4 });
+    universe.set_narration(true);
+    universe.toggle_topology();
+    universe.on_click(DirectionName::Up);
+    while !universe.is_game_over() {
+        universe.tick(0);
+    }
+    assert_eq!(universe.take_narration(), ["3", "2", "1", "Game over, time's up"]);
+}
+
+#[test]
+fn quick_apples_build_a_combo() {
+    let mut universe = seeded(92);
+    universe.set_narration(true);
+    assert!(!universe.combos());
+    universe.set_combos(true);
+    assert_eq!((universe.combo(), universe.multiplier()), (0, 1));
+    while universe.combo() < 3 {
+        if universe.combo() == 2 {
+            universe.set_apple_count(0);
+        }
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+    assert_eq!(universe.multiplier(), 3);
+    assert_eq!(universe.score(), 1 + 2 + 3);
+    universe.take_narration();
+
+    // Heading straight on misses the last apple, so the combo breaks and the
+    // multiplier winds down.
+    universe.advance_n_ticks(19, None);
+    assert_eq!(universe.combo(), 3);
+    universe.advance_n_ticks(1, None);
+    assert_eq!((universe.combo(), universe.multiplier()), (0, 3));
+    assert_eq!(universe.take_narration(), ["Combo of 3 broken"]);
+    universe.advance_n_ticks(21, None);
+    assert_eq!(universe.multiplier(), 2);
+    universe.advance_n_ticks(21, None);
+    assert_eq!(universe.multiplier(), 1);
+}
+
+#[test]
+fn scoring_weights_are_configurable() {
+    let mut universe = seeded(93);
+    let scoring = Scoring {
+        apple: 10,
+        tick: 1,
+        milestone: 5,
+        milestone_length: 5,
+        ..Scoring::default()
+    };
+    universe.set_scoring(scoring);
+    universe.set_combos(true);
+    universe.advance_n_ticks(1, None);
+    assert_eq!(universe.score(), 1);
+
+    // The first apple grows the snake from 4 to 5, a milestone, and starts a
+    // combo still at the plain multiplier.
+    let mut ticks = 1;
+    while universe.combo() == 0 {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+        ticks += 1;
+    }
+    assert_eq!(universe.score(), ticks + 10 + 5);
+
+    let mut restored = seeded(0);
+    restored.restore(&universe.snapshot()).unwrap();
+    assert_eq!(restored.scoring(), scoring);
+    let config = Config {
+        seed: Some(93),
+        ..universe.config()
+    };
+    assert_eq!(config.scoring, scoring);
+    assert_eq!(Universe::from_config(&config).unwrap().scoring(), scoring);
+
+    // The same weights one at a time, as JS sets them.
+    let mut one_by_one = seeded(93);
+    one_by_one.set_apple_points(10);
+    one_by_one.set_tick_points(1);
+    one_by_one.set_milestone_points(5, 5);
+    one_by_one.set_speed_bonus(0.0);
+    assert_eq!(one_by_one.scoring(), scoring);
+}
+
+#[test]
+fn milestones_of_length_zero_are_off() {
+    let mut universe = seeded(93);
+    universe.set_milestone_points(5, 0);
+    while universe.snake_length() == 4 {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+
+    assert_eq!(universe.score(), 1);
+    assert!(universe.split_times().is_empty());
+}
+
+#[test]
+fn lives_respawn_the_snake_somewhere_safe() {
+    let mut universe = seeded(94);
+    universe.toggle_topology();
+    universe.set_apple_count(0);
+    universe.set_lives(3);
+    assert_eq!(universe.lives(), 3);
+
+    let mut deaths = 0;
+    while !universe.is_game_over() {
+        if universe.tick(0).died() {
+            deaths += 1;
+            if deaths < 3 {
+                assert!(universe.is_alive(0));
+                assert_eq!(universe.lives(), 3 - deaths);
+                assert_eq!(universe.snake_length(), 4);
+                assert!(universe.describe().ends_with(", wall 13 cells ahead"));
+            }
+        }
+    }
+    assert_eq!((deaths, universe.lives()), (3, 0));
+    assert_eq!(universe.game_over_reason(), Some(GameOverReason::HitWall));
+
+    universe.reset();
+    assert_eq!(universe.lives(), 3);
+}
+
+#[test]
+fn respawns_pick_the_longest_open_line() {
+    // A short corridor on top with room for the snake, a longer one below.
+    let map = "16#\n#8.7#\n16#\n#14.#\n16#\n3.S12#\n16#";
+    let mut universe = Universe::from_map_with_seed(map, 94).unwrap();
+    universe.set_apple_count(0);
+    universe.set_lives(2);
+    while !universe.tick(0).died() {}
+
+    assert!(universe.is_alive(0));
+    assert_eq!(universe.snake_head().y(), 3);
+}
+
+#[test]
+fn games_can_open_with_a_countdown() {
+    let mut universe = seeded(95);
+    universe.set_countdown_seconds(3);
+    assert_eq!(universe.state(), GameState::Running);
+    universe.reset();
+    assert_eq!((universe.state(), universe.countdown_left()), (GameState::Countdown, 3));
+
+    let start = universe.snake_positions();
+    for _ in 0..10 {
+        assert!(!universe.tick(0).moved());
+    }
+    assert_eq!(universe.countdown_left(), 2);
+    for _ in 0..20 {
+        assert_eq!(universe.advance(100.0), 1);
+    }
+    assert_eq!(universe.state(), GameState::Running);
+    assert_eq!(universe.snake_positions(), start);
+    let counts: Vec<_> = universe.take_sounds().iter().map(|cue| cue.count()).collect();
+    assert_eq!(counts, [3, 2, 1]);
+
+    universe.reset();
+    universe.start();
+    assert_eq!((universe.state(), universe.countdown_left()), (GameState::Running, 0));
+}
+
+#[test]
+fn fresh_snakes_wait_instead_of_crashing() {
+    let mut universe = seeded(95);
+    universe.toggle_topology();
+    let head = universe.snake_positions();
+    assert!(universe.set_wall(head[0] + 1, head[1]));
+
+    for _ in 0..3 {
+        assert!(universe.in_grace(0));
+        assert!(!universe.tick(0).moved());
+    }
+    assert!(!universe.in_grace(0));
+    assert!(universe.tick(0).died());
+}
+
+#[test]
+fn timers_run_on_game_time() {
+    let mut universe = seeded(96);
+    universe.set_scoring(Scoring {
+        milestone_length: 5,
+        ..Scoring::default()
+    });
+    universe.advance_n_ticks(10, None);
+    assert_eq!((universe.elapsed_ticks(), universe.elapsed_seconds()), (10, 1.0));
+
+    universe.pause();
+    assert_eq!(universe.advance(5000.0), 0);
+    universe.tick(0);
+    assert_eq!(universe.elapsed_seconds(), 1.0);
+    universe.resume();
+
+    assert!(universe.split_times().is_empty());
+    while universe.snake_length() < 5 {
+        universe.advance_n_ticks(1, Some(AiStrategy::Greedy));
+    }
+    assert_eq!(universe.split_times(), [universe.elapsed_seconds()]);
+    assert!(universe.stats_json().contains(&format!("\"splits\":[{}]", universe.elapsed_seconds() * 1000.0)));
+}
+
+#[test]
+fn perf_stats_cover_frames_ticks_and_renders() {
+    let mut universe = seeded(97);
+    let stats = universe.perf_stats();
+    assert_eq!((stats.tick_max_ms(), stats.render_max_ms(), stats.frame_p95_ms()), (0.0, 0.0, 0.0));
+
+    for _ in 0..5 {
+        universe.advance(100.0);
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    for ms in 0..130 {
+        universe.record_render_ms(ms as f64);
+    }
+    let stats = universe.perf_stats();
+    assert!(stats.tick_max_ms() >= stats.tick_mean_ms() && stats.tick_mean_ms() > 0.0);
+    assert!(stats.frame_min_ms() >= 1.0);
+    assert!(stats.frame_min_ms() <= stats.frame_p95_ms() && stats.frame_p95_ms() <= stats.frame_max_ms());
+    // Only the last 120 renders, 10 to 129, count.
+    assert_eq!((stats.render_mean_ms(), stats.render_max_ms()), (69.5, 129.0));
+}
+
+#[test]
+fn profile_splits_tick_time_into_sections() {
+    let mut universe = seeded(98);
+    for _ in 0..5 {
+        universe.tick(0);
+    }
+
+    let profile = universe.profile_json();
+    assert!(profile.starts_with("{\"ticks\":5,"));
+    assert!(profile.contains("\"collisions\":{\"calls\":5,"));
+    assert!(profile.contains("\"cell_updates\":{\"calls\":5,"));
+    assert!(profile.contains("\"food_spawn\":{\"calls\":"));
+
+    universe.reset_profile();
+    assert!(universe.profile_json().contains("\"collisions\":{\"calls\":0,\"total_ms\":0,"));
+}
+
+#[test]
+fn the_camera_follows_the_head_on_big_boards() {
+    let mut universe = Universe::new_with_seed(Snake::new(), 60.0, 1024, 1024, 100).unwrap();
+    let camera = universe.camera();
+    assert_eq!((camera.x(), camera.y(), camera.w(), camera.h()), (0, 0, 1024, 1024));
+
+    universe.set_viewport(20, 10);
+    let head = universe.snake_head();
+    let camera = universe.camera();
+    // Wrapping edges let the head sit in the middle of the view.
+    assert_eq!((camera.w(), camera.h()), (20, 10));
+    assert_eq!(((camera.x() + 10) % 1024, (camera.y() + 5) % 1024), (head.x(), head.y()));
+    let cells = universe.visible_cells();
+    assert_eq!(cells.len(), 200);
+    assert_eq!(cells[5 * 20 + 10], Cell::SnakeHead as u8);
+
+    universe.toggle_topology();
+    let camera = universe.camera();
+    // A solid edge stops the camera, leaving the head off center.
+    assert_eq!((camera.x(), camera.y()), (head.x().saturating_sub(10), head.y() - 5));
+    assert!(head.x() < 10);
+    assert_eq!(universe.visible_cells()[(5 * 20 + head.x()) as usize], Cell::SnakeHead as u8);
+
+    universe.set_viewport(0, 0);
+    assert_eq!(universe.camera().w(), 1024);
+}
+
+#[test]
+fn boards_can_be_resized_mid_game() {
+    let mut universe = seeded(101);
+    universe.toggle_topology();
+    universe.set_wall(15, 15);
+    universe.set_wall(1, 1);
+    let head = universe.snake_head();
+    let length = universe.snake_length();
+
+    universe.resize(32, 20).unwrap();
+    assert_eq!((universe.width(), universe.height()), (32, 20));
+    assert_eq!(universe.cells_u8().len(), 640);
+    assert_eq!((universe.snake_head().x(), universe.snake_head().y()), (head.x(), head.y()));
+    let walls = |universe: &Universe| universe.cells_u8().iter().filter(|&&cell| cell == Cell::Wall as u8).count();
+    assert_eq!(walls(&universe), 2);
+    assert_eq!(universe.foods().len(), 1);
+
+    // Too small for where the snake was: it moves over, whole.
+    universe.resize(5, 5).unwrap();
+    assert_eq!(universe.snake_length(), length);
+    assert_eq!(walls(&universe), 1);
+    let cells = universe.cells_u8();
+    assert_eq!(cells.iter().filter(|&&cell| cell == Cell::SnakeHead as u8).count(), 1);
+    assert_eq!(cells[6], Cell::Wall as u8);
+    universe.tick(0);
+    assert_eq!(universe.cells_u8().iter().filter(|&&cell| cell == Cell::SnakeHead as u8).count(), 1);
+
+    assert!(universe.resize(0, 8).is_err());
+
+    // Walls are never knocked down to make room.
+    let mut walled = seeded(101);
+    walled.set_wall(0, 0);
+    let before = walled.render();
+    assert!(walled.resize(1, 1).is_err());
+    assert_eq!((walled.width(), walled.height()), (16, 16));
+    assert_eq!(walled.render(), before);
+}
+
+#[test]
+fn fog_hides_what_the_snake_cannot_see() {
+    let mut universe = seeded(102);
+    assert!(universe.visibility_u8().is_empty());
+    assert!(universe.is_visible(15, 15));
+
+    universe.set_fog_radius(2);
+    let head = universe.snake_head();
+    let fog = universe.visibility_u8();
+    assert_eq!(fog.len(), 256);
+    // A radius two disc is thirteen cells.
+    assert_eq!(fog.iter().filter(|&&cell| cell != fog::UNKNOWN).count(), 13);
+    assert_eq!(fog[(head.y() * 16 + head.x()) as usize], Cell::SnakeHead as u8);
+    assert!(universe.is_visible(head.x(), head.y() + 2));
+    assert!(!universe.is_visible(head.x() + 2, head.y() + 1));
+
+    universe.tick(0);
+    let head = universe.snake_head();
+    assert_eq!(universe.visibility_u8()[(head.y() * 16 + head.x()) as usize], Cell::SnakeHead as u8);
+
+    universe.set_fog_radius(u32::MAX);
+    assert!(universe.visibility_u8().iter().all(|&cell| cell != fog::UNKNOWN));
+
+    universe.set_fog_radius(0);
+    assert!(universe.visibility_u8().is_empty());
+}
+
+#[test]
+fn minimaps_keep_what_matters_in_each_patch() {
+    let mut universe = seeded(103);
+    universe.set_wall(0, 0);
+    universe.set_wall(1, 0);
+    let head = universe.snake_head();
+    let apple = universe.apple().unwrap();
+
+    let minimap = universe.minimap(4, 4);
+    assert_eq!(minimap.len(), 16);
+    assert_eq!(minimap[0], Cell::Wall as u8);
+    assert_eq!(minimap[(head.y() / 4 * 4 + head.x() / 4) as usize], Cell::SnakeHead as u8);
+    assert!(minimap.contains(&(Cell::Apple as u8)) || apple.x() / 4 == head.x() / 4 && apple.y() / 4 == head.y() / 4);
+
+    // Bigger than the board, cells are simply repeated.
+    let minimap = universe.minimap(32, 32);
+    assert_eq!(minimap[0..4], [Cell::Wall as u8; 4]);
+    assert_eq!(universe.minimap(16, 16), universe.cells_u8());
+    assert!(universe.minimap(0, 3).is_empty());
+}
+
+#[test]
+fn terrain_changes_how_snakes_move() {
+    let mut universe = seeded(104);
+    assert!(universe.set_terrain(3, 3, Terrain::Mud));
+    assert!(!universe.set_terrain(16, 0, Terrain::Ice));
+    assert_eq!(universe.terrain_at(3, 3), Terrain::Mud);
+    assert_eq!(universe.terrain_u8()[3 * 16 + 3], Terrain::Mud as u8);
+    universe.clear_terrain();
+
+    // Ice: the turn waits until the head is off it.
+    let head = universe.snake_head();
+    universe.set_terrain(head.x() + 1, head.y(), Terrain::Ice);
+    universe.tick(0);
+    universe.on_click(DirectionName::Up);
+    universe.tick(0);
+    assert_eq!((universe.snake_head().x(), universe.snake_head().y()), (head.x() + 2, head.y()));
+    universe.tick(0);
+    assert_eq!((universe.snake_head().x(), universe.snake_head().y()), (head.x() + 2, head.y() - 1));
+
+    // Mud: one move every other tick.
+    let head = universe.snake_head();
+    universe.set_terrain(head.x(), head.y() - 1, Terrain::Mud);
+    let heads: Vec<_> = (0..4)
+        .map(|_| {
+            universe.tick(0);
+            universe.snake_head().y()
+        })
+        .collect();
+    assert_eq!(heads.iter().filter(|&&y| y == head.y() - 1).count(), 2);
+
+    // Conveyor: an extra cell along the belt.
+    let head = universe.snake_head();
+    universe.set_terrain(head.x(), head.y() - 1, Terrain::ConveyorLeft);
+    universe.tick(0);
+    assert_eq!((universe.snake_head().x(), universe.snake_head().y()), (head.x() - 1, head.y() - 1));
+
+    let mut restored = seeded(1);
+    restored.restore(&universe.snapshot()).unwrap();
+    assert_eq!(restored.terrain_u8(), universe.terrain_u8());
+    assert_eq!(restored.state_hash(), universe.state_hash());
+}